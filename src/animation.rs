@@ -0,0 +1,246 @@
+//! Animation helpers for YT ShortMaker planos
+//! Time values, easing curves and keyframe tracks compiled to FFmpeg expressions
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A point in time within the clip
+/// Seconds (negative = counted from the end) or a percentage such as "50%"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum TimeValue {
+    Seconds(f64),
+    Keyword(String),
+}
+
+impl TimeValue {
+    /// Resolve to seconds from the start of a clip lasting `duration` seconds
+    pub fn resolve(&self, duration: f64) -> f64 {
+        let secs = match self {
+            TimeValue::Seconds(s) if *s < 0.0 => duration + s,
+            TimeValue::Seconds(s) => *s,
+            TimeValue::Keyword(kw) => {
+                let kw = kw.trim();
+                if let Some(pct) = kw.strip_suffix('%') {
+                    pct.trim().parse::<f64>().unwrap_or(0.0) / 100.0 * duration
+                } else {
+                    // Also accept numbers written as strings ("2.5", "-3")
+                    return TimeValue::Seconds(kw.parse().unwrap_or(0.0)).resolve(duration);
+                }
+            }
+        };
+        secs.max(0.0)
+    }
+}
+
+impl Default for TimeValue {
+    fn default() -> Self {
+        TimeValue::Seconds(0.0)
+    }
+}
+
+/// Curve used to move from one keyframe to the next
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Keep the previous value and jump at the keyframe
+    Hold,
+}
+
+impl Easing {
+    /// Apply the curve to a progress value in [0, 1]
+    pub fn apply(&self, p: f64) -> f64 {
+        let p = p.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => p,
+            Easing::EaseIn => p * p,
+            Easing::EaseOut => p * (2.0 - p),
+            Easing::EaseInOut => p * p * (3.0 - 2.0 * p),
+            Easing::Hold => 0.0,
+        }
+    }
+
+    /// Same curve as an FFmpeg expression of the progress expression `p`
+    fn expr(&self, p: &str) -> String {
+        match self {
+            Easing::Linear => p.to_string(),
+            Easing::EaseIn => format!("({p})*({p})"),
+            Easing::EaseOut => format!("({p})*(2-({p}))"),
+            Easing::EaseInOut => format!("({p})*({p})*(3-2*({p}))"),
+            Easing::Hold => "0".to_string(),
+        }
+    }
+}
+
+/// One animated property: (time in seconds, value, easing into this point)
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    points: Vec<(f64, f64, Easing)>,
+}
+
+impl Track {
+    /// Build a track, sorting the points by time
+    pub fn new(mut points: Vec<(f64, f64, Easing)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Whether the value changes over time
+    pub fn is_animated(&self) -> bool {
+        self.points.windows(2).any(|w| w[0].1 != w[1].1)
+    }
+
+    /// Largest value reached (eased segments never overshoot their keyframes)
+    pub fn max_value(&self) -> f64 {
+        self.points.iter().map(|p| p.1).fold(f64::MIN, f64::max)
+    }
+
+    /// Value at `time` (held before the first and after the last keyframe)
+    pub fn value_at(&self, time: f64) -> f64 {
+        let Some(first) = self.points.first() else {
+            return 0.0;
+        };
+        if time <= first.0 {
+            return first.1;
+        }
+        for w in self.points.windows(2) {
+            let (t0, v0, _) = w[0];
+            let (t1, v1, easing) = w[1];
+            if time < t1 {
+                let span = t1 - t0;
+                let p = if span > 0.0 { (time - t0) / span } else { 1.0 };
+                return v0 + (v1 - v0) * easing.apply(p);
+            }
+        }
+        self.points.last().map(|p| p.1).unwrap_or(0.0)
+    }
+
+    /// FFmpeg expression of the time variable `var` (e.g. "t" or "T")
+    /// Commas are left unescaped: quote the result inside filter options
+    pub fn to_expr(&self, var: &str) -> String {
+        let Some(last) = self.points.last() else {
+            return "0".to_string();
+        };
+        if !self.is_animated() {
+            return format_number(last.1);
+        }
+
+        // A flat sum with one term per segment, each gated by the time range it covers:
+        // lt(t,t0)*(v0) + gte(t,t0)*lt(t,t1)*(segment0) + ... + gte(t,tn)*(vn)
+        // Nesting if() per point hits FFmpeg's expression depth limit on long tracks
+        let first = &self.points[0];
+        let mut expr = format!(
+            "lt({},{})*({})",
+            var,
+            format_number(first.0),
+            format_number(first.1)
+        );
+        for w in self.points.windows(2) {
+            let (t0, v0, _) = w[0];
+            let (t1, v1, easing) = w[1];
+            if t1 <= t0 {
+                continue;
+            }
+            let segment = if v0 == v1 {
+                format_number(v0)
+            } else {
+                let progress =
+                    format!("({}-{})/{}", var, format_number(t0), format_number(t1 - t0));
+                format!(
+                    "{}+({})*{}",
+                    format_number(v0),
+                    format_number(v1 - v0),
+                    easing.expr(&progress)
+                )
+            };
+            expr.push_str(&format!(
+                "+gte({v},{})*lt({v},{})*({})",
+                format_number(t0),
+                format_number(t1),
+                segment,
+                v = var
+            ));
+        }
+        expr.push_str(&format!(
+            "+gte({},{})*({})",
+            var,
+            format_number(last.0),
+            format_number(last.1)
+        ));
+        expr
+    }
+}
+
+/// Format a number for FFmpeg expressions (no trailing zeros)
+pub(crate) fn format_number(v: f64) -> String {
+    let s = format!("{:.4}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" || s.is_empty() {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_value_resolve() {
+        assert_eq!(TimeValue::Seconds(2.0).resolve(10.0), 2.0);
+        assert_eq!(TimeValue::Seconds(-3.0).resolve(10.0), 7.0);
+        assert_eq!(TimeValue::Keyword("50%".to_string()).resolve(10.0), 5.0);
+        assert_eq!(TimeValue::Keyword("-1.5".to_string()).resolve(10.0), 8.5);
+        assert_eq!(TimeValue::Seconds(-30.0).resolve(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_easing_curves() {
+        assert_eq!(Easing::Linear.apply(0.5), 0.5);
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::Hold.apply(0.9), 0.0);
+        assert_eq!(Easing::EaseInOut.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_track_value_at() {
+        let track = Track::new(vec![
+            (2.0, 100.0, Easing::Linear),
+            (0.0, 0.0, Easing::Linear),
+            (4.0, 100.0, Easing::Hold),
+        ]);
+        assert_eq!(track.value_at(-1.0), 0.0);
+        assert_eq!(track.value_at(1.0), 50.0);
+        assert_eq!(track.value_at(3.0), 100.0);
+        assert_eq!(track.value_at(10.0), 100.0);
+    }
+
+    #[test]
+    fn test_track_to_expr() {
+        let track = Track::new(vec![
+            (0.0, 0.0, Easing::Linear),
+            (2.0, 100.0, Easing::EaseIn),
+        ]);
+        assert_eq!(
+            track.to_expr("t"),
+            "lt(t,0)*(0)+gte(t,0)*lt(t,2)*(0+(100)*((t-0)/2)*((t-0)/2))+gte(t,2)*(100)"
+        );
+
+        // Constant tracks collapse to a plain number
+        let flat = Track::new(vec![(0.0, 0.5, Easing::Linear), (3.0, 0.5, Easing::Linear)]);
+        assert!(!flat.is_animated());
+        assert_eq!(flat.to_expr("T"), "0.5");
+    }
+}
@@ -0,0 +1,268 @@
+//! Asset paths for YT ShortMaker planos
+//! Expands `~` and environment variables, resolves relative paths against the
+//! plano file, and opens plano bundles
+//!
+//! A bundle is a folder or a .zip holding `plano.json` next to its assets, so
+//! a plano can be shared together with the images and videos it uses.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Plano file name looked up inside bundles
+pub const BUNDLE_PLANO_NAME: &str = "plano.json";
+
+/// Expand `~`, `$VAR`, `${VAR}` and `%VAR%`, then resolve a relative result
+/// against `base_dir`
+///
+/// Unknown variables and URLs ("https://...") are left untouched.
+pub fn resolve_path(raw: &str, base_dir: &Path) -> String {
+    if raw.contains("://") {
+        return raw.to_string();
+    }
+    let expanded = expand_vars(raw);
+    let expanded = expand_home(&expanded);
+    let path = Path::new(&expanded);
+    if path.is_absolute() || expanded.is_empty() {
+        expanded
+    } else {
+        base_dir.join(path).to_string_lossy().to_string()
+    }
+}
+
+/// Replace a leading `~` with the home directory
+fn expand_home(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => rest,
+        _ => return path.to_string(),
+    };
+    match dirs::home_dir() {
+        Some(home) => format!("{}{}", home.to_string_lossy(), rest),
+        None => path.to_string(),
+    }
+}
+
+/// Replace `$VAR`, `${VAR}` and `%VAR%` with the value of set variables
+/// `%VAR%` only matches when the variable exists, so "%03d" sequences are safe
+fn expand_vars(path: &str) -> String {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::new();
+    let mut rest = path;
+
+    while let Some(pos) = rest.find(['$', '%']) {
+        out.push_str(&rest[..pos]);
+        let marker = rest[pos..].chars().next().unwrap_or('$');
+        let after = &rest[pos + 1..];
+
+        // (variable name, length of the whole reference)
+        let reference = match marker {
+            '$' if after.starts_with('{') => after
+                .find('}')
+                .map(|end| (&after[1..end], end + 2))
+                .filter(|(name, _)| !name.is_empty() && name.chars().all(is_name)),
+            '$' => {
+                let len = after.find(|c: char| !is_name(c)).unwrap_or(after.len());
+                (len > 0).then(|| (&after[..len], len + 1))
+            }
+            _ => after
+                .find('%')
+                .map(|end| (&after[..end], end + 2))
+                .filter(|(name, _)| !name.is_empty() && name.chars().all(is_name)),
+        };
+
+        match reference.and_then(|(name, len)| std::env::var(name).ok().map(|v| (v, len))) {
+            Some((value, len)) => {
+                out.push_str(&value);
+                rest = &rest[pos + len..];
+            }
+            None => {
+                out.push(marker);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The JSON file to read for a plano path: the file itself, `plano.json`
+/// inside a bundle folder, or the same inside an extracted .zip bundle
+pub fn plano_file(path: &Path) -> Result<PathBuf> {
+    if path.is_dir() {
+        return find_plano_in_dir(path);
+    }
+    let is_zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if is_zip {
+        let dir = extract_bundle(path)?;
+        return find_plano_in_dir(&dir);
+    }
+    Ok(path.to_path_buf())
+}
+
+/// `plano.json`, the only .json file, or the same inside a single subfolder
+fn find_plano_in_dir(dir: &Path) -> Result<PathBuf> {
+    let named = dir.join(BUNDLE_PLANO_NAME);
+    if named.is_file() {
+        return Ok(named);
+    }
+
+    let mut json_files = Vec::new();
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {:?}", dir))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            subdirs.push(path);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            json_files.push(path);
+        }
+    }
+
+    match (json_files.len(), subdirs.len()) {
+        (1, _) => Ok(json_files.remove(0)),
+        // Zips made from a folder have it as their only entry
+        (0, 1) => find_plano_in_dir(&subdirs[0]),
+        _ => Err(anyhow!(
+            "No {} found in plano bundle {:?}",
+            BUNDLE_PLANO_NAME,
+            dir
+        )),
+    }
+}
+
+/// Extract a .zip bundle to the temp dir, reusing an earlier extraction of
+/// the same file (same name, size and modification time)
+fn extract_bundle(zip_path: &Path) -> Result<PathBuf> {
+    let meta = fs::metadata(zip_path)
+        .with_context(|| format!("Failed to read plano bundle {:?}", zip_path))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let stem = zip_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "bundle".to_string());
+
+    let root = std::env::temp_dir().join("yt_shortmaker_bundles");
+    let name = format!("{}_{}_{}", stem, meta.len(), modified);
+    let dest = root.join(&name);
+    if dest.is_dir() {
+        return Ok(dest);
+    }
+
+    // Extract next to the final folder, then move it in place in one step
+    let partial = root.join(format!("{}.partial", name));
+    let _ = fs::remove_dir_all(&partial);
+    fs::create_dir_all(&partial)?;
+
+    let file = fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Invalid plano bundle {:?}", zip_path))?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        // Entries pointing outside the bundle ("../x") are skipped
+        let Some(name) = entry.enclosed_name().map(|n| n.to_path_buf()) else {
+            continue;
+        };
+        let target = partial.join(name);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = fs::File::create(&target)?;
+        std::io::copy(&mut entry, &mut out)?;
+    }
+
+    fs::rename(&partial, &dest)
+        .with_context(|| format!("Failed to extract plano bundle {:?}", zip_path))?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_resolve_path() {
+        let base = Path::new("/planos/gaming");
+        assert_eq!(
+            resolve_path("assets/logo.png", base),
+            "/planos/gaming/assets/logo.png"
+        );
+        assert_eq!(resolve_path("/abs/logo.png", base), "/abs/logo.png");
+        assert_eq!(
+            resolve_path("https://example.com/x.png", base),
+            "https://example.com/x.png"
+        );
+
+        std::env::set_var("YTSM_TEST_ASSETS", "/shared");
+        assert_eq!(
+            resolve_path("$YTSM_TEST_ASSETS/a.png", base),
+            "/shared/a.png"
+        );
+        assert_eq!(
+            resolve_path("${YTSM_TEST_ASSETS}/b.png", base),
+            "/shared/b.png"
+        );
+        assert_eq!(
+            resolve_path("%YTSM_TEST_ASSETS%/c.png", base),
+            "/shared/c.png"
+        );
+        // Sequence placeholders and unknown variables are kept
+        assert_eq!(
+            resolve_path("frames/%03d.png", base),
+            "/planos/gaming/frames/%03d.png"
+        );
+        assert_eq!(
+            resolve_path("$YTSM_UNSET_VAR/d.png", base),
+            "/planos/gaming/$YTSM_UNSET_VAR/d.png"
+        );
+
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                resolve_path("~/logo.png", base),
+                format!("{}/logo.png", home.to_string_lossy())
+            );
+        }
+    }
+
+    #[test]
+    fn test_zip_bundle() {
+        let dir = std::env::temp_dir().join(format!("ytsm_bundle_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let zip_path = dir.join("style.zip");
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        writer.start_file("style/plano.json", options).unwrap();
+        writer.write_all(b"[]").unwrap();
+        writer.start_file("style/logo.png", options).unwrap();
+        writer.write_all(b"png").unwrap();
+        writer.start_file("../escape.txt", options).unwrap();
+        writer.write_all(b"no").unwrap();
+        writer.finish().unwrap();
+
+        let plano = plano_file(&zip_path).unwrap();
+        assert!(plano.ends_with("style/plano.json"));
+        assert!(plano.with_file_name("logo.png").is_file());
+        // Second load reuses the extraction
+        assert_eq!(plano_file(&zip_path).unwrap(), plano);
+
+        let extracted = plano.parent().unwrap().parent().unwrap().to_path_buf();
+        assert!(!extracted.parent().unwrap().join("escape.txt").exists());
+        let _ = fs::remove_dir_all(&extracted);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -55,6 +55,10 @@ pub struct ShortsConfig {
     /// Image overlays with positions
     #[serde(default)]
     pub overlays: Vec<ImageOverlay>,
+    /// Encoder settings for shorts built from this layout (default x264
+    /// `medium` at CRF 23, what they have always been encoded with)
+    #[serde(default = "default_shorts_profile")]
+    pub profile: ExportProfile,
 }
 
 fn default_bg_opacity() -> f32 {
@@ -93,6 +97,47 @@ impl Default for ShortsConfig {
             main_video_zoom: 0.7,
             main_video_y_offset: -150,
             overlays: Vec::new(),
+            profile: default_shorts_profile(),
+        }
+    }
+}
+
+/// Jump-cut settings: silences removed from extracted clips
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JumpCutConfig {
+    /// Whether to remove silences after extracting each clip
+    #[serde(default)]
+    pub enabled: bool,
+    /// Audio level treated as silence, in dB (default -35)
+    #[serde(default = "default_silence_threshold")]
+    pub threshold_db: f64,
+    /// Shortest pause that gets cut, in seconds (default 0.6)
+    #[serde(default = "default_min_silence")]
+    pub min_silence: f64,
+    /// Silence kept around speech on each side, in seconds (default 0.15)
+    #[serde(default = "default_silence_padding")]
+    pub padding: f64,
+}
+
+fn default_silence_threshold() -> f64 {
+    -35.0
+}
+
+fn default_min_silence() -> f64 {
+    0.6
+}
+
+fn default_silence_padding() -> f64 {
+    0.15
+}
+
+impl Default for JumpCutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: default_silence_threshold(),
+            min_silence: default_min_silence(),
+            padding: default_silence_padding(),
         }
     }
 }
@@ -118,6 +163,7 @@ fn default_true() -> bool {
     true
 }
 
+use crate::encoding::ExportProfile;
 use crate::security::{EncryptionMode, SecuredConfig};
 
 /// Application configuration stored in settings.json
@@ -146,6 +192,20 @@ pub struct AppConfig {
     /// Whether to use the fast model (gemini-3-flash-preview) or pro model
     #[serde(default = "default_true")]
     pub use_fast_model: bool,
+    /// Encoder settings for extracted clips (intermediate files)
+    #[serde(default = "default_extract_profile")]
+    pub extract_profile: ExportProfile,
+    /// Encoder settings for final shorts (exporter and transform)
+    /// A plano's own `profile` takes precedence over this one
+    #[serde(default)]
+    pub export_profile: ExportProfile,
+    /// Silence removal for extracted clips
+    #[serde(default)]
+    pub jump_cut: JumpCutConfig,
+    /// Write EDL, FCPXML, Premiere XML and OTIO timelines of the extracted
+    /// moments, keeping the high-res source next to the shorts
+    #[serde(default)]
+    pub export_timelines: bool,
 
     // Internal State for Security (Not saved to JSON body)
     #[serde(skip)]
@@ -154,6 +214,21 @@ pub struct AppConfig {
     pub active_password: Option<String>,
 }
 
+pub fn default_extract_profile() -> ExportProfile {
+    ExportProfile {
+        preset: "ultrafast".to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn default_shorts_profile() -> ExportProfile {
+    ExportProfile {
+        preset: "medium".to_string(),
+        crf: Some(23),
+        ..Default::default()
+    }
+}
+
 fn default_language() -> String {
     "en".to_string()
 }
@@ -249,6 +324,10 @@ impl AppConfig {
             cookies_path: default_cookies_path(),
             shorts_config: ShortsConfig::default(),
             use_fast_model: true,
+            extract_profile: default_extract_profile(),
+            export_profile: ExportProfile::default(),
+            jump_cut: JumpCutConfig::default(),
+            export_timelines: false,
 
             active_encryption_mode: EncryptionMode::None,
             active_password: None,
@@ -343,6 +422,10 @@ mod tests {
             cookies_path: "./cookies.json".to_string(),
             shorts_config: ShortsConfig::default(),
             use_fast_model: true,
+            extract_profile: default_extract_profile(),
+            export_profile: ExportProfile::default(),
+            jump_cut: JumpCutConfig::default(),
+            export_timelines: false,
 
             active_encryption_mode: EncryptionMode::None,
             active_password: None,
@@ -368,6 +451,23 @@ mod tests {
         assert_eq!(parsed.google_api_keys[0].enabled, true);
     }
 
+    #[test]
+    fn test_encoding_profiles_default_when_missing() {
+        let json = r#"{ "default_output_dir": "./output" }"#;
+        let parsed: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.extract_profile.preset, "ultrafast");
+        assert_eq!(parsed.export_profile.preset, "superfast");
+        assert_eq!(parsed.shorts_config.profile.preset, "medium");
+        assert_eq!(parsed.shorts_config.profile.crf, Some(23));
+
+        // An explicit layout profile is used as written
+        let json = r#"{ "default_output_dir": "./output",
+                        "shorts_config": { "profile": { "preset": "superfast" } } }"#;
+        let parsed: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.shorts_config.profile.preset, "superfast");
+        assert_eq!(parsed.shorts_config.profile.crf, None);
+    }
+
     #[test]
     fn test_shorts_config_defaults() {
         let config = ShortsConfig::default();
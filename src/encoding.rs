@@ -0,0 +1,590 @@
+//! Encoding profiles for YT ShortMaker
+//! Describes how clips and shorts are encoded (codec, rate control, preset...)
+//!
+//! A profile can live in settings.json (`export_profile`) or inside a plano
+//! document (`profile`), in which case the plano's profile wins. Profiles used
+//! to export shorts can also cap the output length (see `timeline`).
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::timeline::DurationPolicy;
+
+/// Video codec used for the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    /// H.264 via libx264 (most compatible)
+    #[default]
+    X264,
+    /// H.265/HEVC via libx265
+    X265,
+    /// VP9 via libvpx-vp9
+    Vp9,
+    /// AV1 via libsvtav1
+    Av1,
+}
+
+impl VideoCodec {
+    /// FFmpeg encoder name
+    pub fn encoder(&self) -> &'static str {
+        match self {
+            VideoCodec::X264 => "libx264",
+            VideoCodec::X265 => "libx265",
+            VideoCodec::Vp9 => "libvpx-vp9",
+            VideoCodec::Av1 => "libsvtav1",
+        }
+    }
+
+    /// CRF used when neither `crf`, `bitrate` nor `max_file_size_mb` is set
+    pub fn default_crf(&self) -> u8 {
+        match self {
+            VideoCodec::X264 => 23,
+            VideoCodec::X265 => 28,
+            VideoCodec::Vp9 => 32,
+            VideoCodec::Av1 => 35,
+        }
+    }
+}
+
+/// Encoder settings shared by extraction and export
+///
+/// Rate control precedence: `max_file_size_mb` (two-pass) > `bitrate` > `crf`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportProfile {
+    /// Video codec (x264, x265, vp9, av1)
+    #[serde(default)]
+    pub codec: VideoCodec,
+    /// Constant rate factor (lower = better quality). Codec default if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crf: Option<u8>,
+    /// Target video bitrate (e.g. "6M", "4500k")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<String>,
+    /// Maximum output size in megabytes. Enables two-pass encoding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_mb: Option<f64>,
+    /// Encoder preset (x264 names; mapped to speed levels for VP9/AV1)
+    #[serde(default = "default_preset")]
+    pub preset: String,
+    /// Output pixel format
+    #[serde(default = "default_pixel_format")]
+    pub pixel_format: String,
+    /// Keyframe interval in frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gop: Option<u32>,
+    /// AAC audio bitrate
+    #[serde(default = "default_audio_bitrate")]
+    pub audio_bitrate: String,
+    /// Maximum output duration (`max_duration`, `over_limit`, `max_speed`)
+    #[serde(flatten)]
+    pub length: DurationPolicy,
+}
+
+fn default_preset() -> String {
+    "superfast".to_string()
+}
+
+fn default_pixel_format() -> String {
+    "yuv420p".to_string()
+}
+
+fn default_audio_bitrate() -> String {
+    "192k".to_string()
+}
+
+impl Default for ExportProfile {
+    fn default() -> Self {
+        Self {
+            codec: VideoCodec::default(),
+            crf: None,
+            bitrate: None,
+            max_file_size_mb: None,
+            preset: default_preset(),
+            pixel_format: default_pixel_format(),
+            gop: None,
+            audio_bitrate: default_audio_bitrate(),
+            length: DurationPolicy::default(),
+        }
+    }
+}
+
+/// Parse a bitrate string such as "192k", "6M" or "800000" into bits per second
+pub fn parse_bitrate(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, multiplier) = match value.chars().last()? {
+        'k' | 'K' => (&value[..value.len() - 1], 1_000.0),
+        'm' | 'M' => (&value[..value.len() - 1], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    if number <= 0.0 {
+        return None;
+    }
+    Some((number * multiplier) as u64)
+}
+
+/// Map an x264-style preset name to a (VP9 cpu-used, SVT-AV1 preset) pair
+fn speed_levels(preset: &str) -> (u8, u8) {
+    match preset {
+        "ultrafast" => (5, 12),
+        "superfast" => (5, 11),
+        "veryfast" => (4, 10),
+        "faster" => (4, 9),
+        "fast" => (3, 8),
+        "slow" => (1, 4),
+        "slower" => (1, 3),
+        "veryslow" | "placebo" => (0, 2),
+        _ => (2, 6), // medium
+    }
+}
+
+impl ExportProfile {
+    /// Whether this profile needs two-pass encoding
+    pub fn is_two_pass(&self) -> bool {
+        self.max_file_size_mb.is_some()
+    }
+
+    /// Video bitrate (bits/s) that keeps `duration` seconds under `max_file_size_mb`
+    ///
+    /// Leaves 3% headroom for container overhead and subtracts the audio bitrate.
+    pub fn target_video_bitrate(&self, duration: f64) -> Option<u64> {
+        let max_mb = self.max_file_size_mb?;
+        if duration <= 0.0 || max_mb <= 0.0 {
+            return None;
+        }
+        let total_bits = max_mb * 1024.0 * 1024.0 * 8.0 * 0.97;
+        let audio = parse_bitrate(&self.audio_bitrate).unwrap_or(192_000) as f64;
+        let video = total_bits / duration - audio;
+        // Never go below 100 kb/s, the result would be unwatchable anyway
+        Some(video.max(100_000.0) as u64)
+    }
+
+    /// Preset arguments for the selected codec
+    fn preset_args(&self) -> Vec<String> {
+        match self.codec {
+            VideoCodec::X264 | VideoCodec::X265 => {
+                vec!["-preset".to_string(), self.preset.clone()]
+            }
+            VideoCodec::Vp9 => {
+                let level = self
+                    .preset
+                    .parse::<u8>()
+                    .unwrap_or_else(|_| speed_levels(&self.preset).0);
+                vec![
+                    "-deadline".to_string(),
+                    "good".to_string(),
+                    "-cpu-used".to_string(),
+                    level.to_string(),
+                    "-row-mt".to_string(),
+                    "1".to_string(),
+                ]
+            }
+            VideoCodec::Av1 => {
+                let level = self
+                    .preset
+                    .parse::<u8>()
+                    .unwrap_or_else(|_| speed_levels(&self.preset).1);
+                vec!["-preset".to_string(), level.to_string()]
+            }
+        }
+    }
+
+    /// Rate control arguments for single-pass encodes (CRF or target bitrate)
+    fn rate_args(&self) -> Vec<String> {
+        if let Some(ref bitrate) = self.bitrate {
+            return vec!["-b:v".to_string(), bitrate.clone()];
+        }
+
+        let crf = self.crf.unwrap_or_else(|| self.codec.default_crf());
+        let mut args = vec!["-crf".to_string(), crf.to_string()];
+        if self.codec == VideoCodec::Vp9 {
+            // libvpx only does constant quality when the bitrate is zero
+            args.push("-b:v".to_string());
+            args.push("0".to_string());
+        }
+        args
+    }
+
+    /// Video encoder arguments for one pass
+    ///
+    /// `pass` is `None` for single-pass encodes, or `Some((n, passlog))` for two-pass.
+    pub fn video_args(&self, duration: f64, pass: Option<(u8, &str)>) -> Vec<String> {
+        let mut args = vec!["-c:v".to_string(), self.codec.encoder().to_string()];
+        args.extend(self.preset_args());
+
+        match (self.target_video_bitrate(duration), pass) {
+            (Some(bits), Some((n, passlog))) => {
+                args.push("-b:v".to_string());
+                args.push(format!("{}k", bits / 1000));
+                if self.codec == VideoCodec::X265 {
+                    args.push("-x265-params".to_string());
+                    args.push(format!("pass={}:stats={}.log", n, passlog));
+                } else {
+                    args.push("-pass".to_string());
+                    args.push(n.to_string());
+                    args.push("-passlogfile".to_string());
+                    args.push(passlog.to_string());
+                }
+            }
+            (Some(bits), None) => {
+                // Single-pass size cap (AV1): SVT-AV1 two-pass is not exposed by
+                // every ffmpeg build, so cap a VBR encode at the target bitrate.
+                let kbps = format!("{}k", bits / 1000);
+                args.push("-b:v".to_string());
+                args.push(kbps.clone());
+                args.push("-maxrate".to_string());
+                args.push(kbps);
+            }
+            (None, _) => args.extend(self.rate_args()),
+        }
+
+        args.push("-pix_fmt".to_string());
+        args.push(self.pixel_format.clone());
+
+        if let Some(gop) = self.gop {
+            args.push("-g".to_string());
+            args.push(gop.to_string());
+        }
+
+        args
+    }
+
+    /// Audio encoder arguments
+    pub fn audio_args(&self) -> Vec<String> {
+        vec![
+            "-c:a".to_string(),
+            "aac".to_string(),
+            "-b:a".to_string(),
+            self.audio_bitrate.clone(),
+        ]
+    }
+
+    /// Number of ffmpeg runs needed for this profile
+    fn pass_count(&self) -> u8 {
+        if self.is_two_pass() && self.codec != VideoCodec::Av1 {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// Null output target for the analysis pass
+fn null_output() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "NUL"
+    } else {
+        "/dev/null"
+    }
+}
+
+/// One output file of an ffmpeg run
+pub struct EncodeTarget<'a> {
+    /// Output options placed before the encoder settings (`-map`, `-t`...)
+    pub args: Vec<String>,
+    /// Encoder settings for this output
+    pub profile: &'a ExportProfile,
+    /// Output file path
+    pub path: String,
+    /// Output length when it differs from the shared duration (e.g. with bumpers)
+    pub duration: Option<f64>,
+}
+
+/// Encode with ffmpeg using the given profile
+///
+/// `input_args` holds everything before the encoder settings (inputs, filters,
+/// maps, `-t`...). Two-pass profiles run ffmpeg twice, discarding the first
+/// output and cleaning the pass logs afterwards.
+pub async fn run_encode(
+    input_args: &[String],
+    profile: &ExportProfile,
+    duration: f64,
+    output_path: &str,
+    cancellation_token: Arc<AtomicBool>,
+) -> Result<()> {
+    let target = EncodeTarget {
+        args: Vec::new(),
+        profile,
+        path: output_path.to_string(),
+        duration: None,
+    };
+    run_encode_targets(input_args, &[target], duration, cancellation_token).await
+}
+
+/// Encode several outputs from a single ffmpeg invocation
+///
+/// The inputs are decoded once and fed to every output. If any target needs
+/// two-pass encoding, an analysis run that only encodes those targets goes
+/// first; the final run then writes all outputs together.
+pub async fn run_encode_targets(
+    input_args: &[String],
+    targets: &[EncodeTarget<'_>],
+    duration: f64,
+    cancellation_token: Arc<AtomicBool>,
+) -> Result<()> {
+    let two_pass = targets.iter().any(|t| t.profile.pass_count() > 1);
+    let passes: u8 = if two_pass { 2 } else { 1 };
+    let passlog = passlog_prefix();
+    let passlogs: Vec<String> = (0..targets.len())
+        .map(|i| format!("{}_{}", passlog.to_string_lossy(), i))
+        .collect();
+
+    let result = async {
+        for n in 1..=passes {
+            if cancellation_token.load(Ordering::Relaxed) {
+                return Err(anyhow!("Process cancelled by user"));
+            }
+
+            let args = pass_args(input_args, targets, duration, n, passes, &passlogs);
+            log::debug!("FFmpeg encode pass {}/{}: {:?}", n, passes, args);
+
+            let mut command = tokio::process::Command::new("ffmpeg");
+            command.args(&args);
+
+            let output =
+                crate::video::run_command_with_cancellation(command, cancellation_token.clone())
+                    .await?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow!(
+                    "ffmpeg encode failed (pass {}/{}): {}",
+                    n,
+                    passes,
+                    stderr.trim()
+                ));
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    if passes > 1 {
+        cleanup_passlogs(&passlog);
+    }
+
+    result
+}
+
+/// Arguments of encode run `n` out of `passes`
+///
+/// In the analysis run of a two-pass encode, single-pass targets are not
+/// encoded but their outputs still go to a null muxer: every labelled output
+/// of a shared `-filter_complex` has to be consumed.
+fn pass_args(
+    input_args: &[String],
+    targets: &[EncodeTarget<'_>],
+    duration: f64,
+    n: u8,
+    passes: u8,
+    passlogs: &[String],
+) -> Vec<String> {
+    let null_args = [
+        "-an".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-y".to_string(),
+        null_output().to_string(),
+    ];
+
+    let mut args = input_args.to_vec();
+    for (target, log) in targets.iter().zip(passlogs) {
+        let target_two_pass = target.profile.pass_count() > 1;
+        args.extend(target.args.iter().cloned());
+        if n < passes && !target_two_pass {
+            // Written in the final run; the null muxer takes raw frames
+            args.extend_from_slice(&null_args);
+            continue;
+        }
+
+        let pass = if target_two_pass {
+            Some((n, log.as_str()))
+        } else {
+            None
+        };
+        args.extend(
+            target
+                .profile
+                .video_args(target.duration.unwrap_or(duration), pass),
+        );
+
+        if n < passes {
+            args.extend_from_slice(&null_args);
+        } else {
+            args.extend(target.profile.audio_args());
+            args.push("-y".to_string());
+            args.push(target.path.clone());
+        }
+    }
+    args
+}
+
+/// Unique prefix for two-pass log files in the system temp dir
+fn passlog_prefix() -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d%H%M%S%3f");
+    std::env::temp_dir().join(format!(
+        "yt_shortmaker_pass_{}_{}",
+        std::process::id(),
+        stamp
+    ))
+}
+
+/// Remove every file created by the encoder for a given passlog prefix
+fn cleanup_passlogs(prefix: &std::path::Path) {
+    let (Some(dir), Some(name)) = (prefix.parent(), prefix.file_name()) else {
+        return;
+    };
+    let name = name.to_string_lossy();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(name.as_ref())
+            {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bitrate() {
+        assert_eq!(parse_bitrate("192k"), Some(192_000));
+        assert_eq!(parse_bitrate("6M"), Some(6_000_000));
+        assert_eq!(parse_bitrate("2.5M"), Some(2_500_000));
+        assert_eq!(parse_bitrate("800000"), Some(800_000));
+        assert_eq!(parse_bitrate("fast"), None);
+    }
+
+    #[test]
+    fn test_profile_defaults_from_empty_json() {
+        let profile: ExportProfile = serde_json::from_str("{}").unwrap();
+        assert_eq!(profile.codec, VideoCodec::X264);
+        assert_eq!(profile.preset, "superfast");
+        assert_eq!(profile.pixel_format, "yuv420p");
+        assert_eq!(profile.audio_bitrate, "192k");
+        assert!(!profile.is_two_pass());
+    }
+
+    #[test]
+    fn test_profile_duration_policy() {
+        let json = r#"{"preset": "fast", "max_duration": 60, "over_limit": "speed"}"#;
+        let profile: ExportProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.length.max_duration, Some(60.0));
+        assert_eq!(profile.length.over_limit, crate::timeline::OverLimit::Speed);
+
+        // Unset policy fields stay out of saved files
+        let saved = serde_json::to_string(&ExportProfile::default()).unwrap();
+        assert!(!saved.contains("max_duration") && !saved.contains("over_limit"));
+    }
+
+    #[test]
+    fn test_crf_args_per_codec() {
+        let profile = ExportProfile {
+            codec: VideoCodec::Vp9,
+            ..Default::default()
+        };
+        let args = profile.video_args(10.0, None).join(" ");
+        assert!(args.contains("-c:v libvpx-vp9"));
+        assert!(args.contains("-crf 32 -b:v 0"));
+        assert!(args.contains("-cpu-used 5"));
+
+        let profile = ExportProfile {
+            codec: VideoCodec::X265,
+            crf: Some(20),
+            gop: Some(60),
+            ..Default::default()
+        };
+        let args = profile.video_args(10.0, None).join(" ");
+        assert!(args.contains("-c:v libx265 -preset superfast -crf 20"));
+        assert!(args.contains("-g 60"));
+    }
+
+    #[test]
+    fn test_bitrate_overrides_crf() {
+        let profile = ExportProfile {
+            crf: Some(18),
+            bitrate: Some("6M".to_string()),
+            ..Default::default()
+        };
+        let args = profile.video_args(10.0, None).join(" ");
+        assert!(args.contains("-b:v 6M"));
+        assert!(!args.contains("-crf"));
+    }
+
+    #[test]
+    fn test_max_file_size_two_pass() {
+        let profile = ExportProfile {
+            max_file_size_mb: Some(10.0),
+            audio_bitrate: "128k".to_string(),
+            ..Default::default()
+        };
+        assert!(profile.is_two_pass());
+        assert_eq!(profile.pass_count(), 2);
+
+        // 10 MB over 60s with 128k audio leaves roughly 1.2 Mb/s for video
+        let bits = profile.target_video_bitrate(60.0).unwrap();
+        assert!(bits > 1_100_000 && bits < 1_300_000, "got {}", bits);
+
+        let args = profile.video_args(60.0, Some((1, "/tmp/log"))).join(" ");
+        assert!(args.contains("-pass 1 -passlogfile /tmp/log"));
+
+        let x265 = ExportProfile {
+            codec: VideoCodec::X265,
+            ..profile.clone()
+        };
+        let args = x265.video_args(60.0, Some((2, "/tmp/log"))).join(" ");
+        assert!(args.contains("-x265-params pass=2:stats=/tmp/log.log"));
+
+        let av1 = ExportProfile {
+            codec: VideoCodec::Av1,
+            ..profile
+        };
+        assert_eq!(av1.pass_count(), 1);
+        let args = av1.video_args(60.0, None).join(" ");
+        assert!(args.contains("-maxrate"));
+        assert!(!args.contains("-crf"));
+    }
+
+    #[test]
+    fn test_two_pass_analysis_keeps_every_output() {
+        let two_pass = ExportProfile {
+            max_file_size_mb: Some(10.0),
+            ..Default::default()
+        };
+        let single = ExportProfile::default();
+        let target = |label: &str, profile, path: &str| EncodeTarget {
+            args: vec!["-map".to_string(), label.to_string()],
+            profile,
+            path: path.to_string(),
+            duration: None,
+        };
+        let targets = [
+            target("[out0]", &two_pass, "a.mp4"),
+            target("[out1]", &single, "b.mp4"),
+        ];
+        let logs = vec!["/tmp/log_0".to_string(), "/tmp/log_1".to_string()];
+        let input = vec!["-filter_complex".to_string(), "graph".to_string()];
+
+        // Analysis run: both labels consumed, only the two-pass one encoded
+        let first = pass_args(&input, &targets, 60.0, 1, 2, &logs).join(" ");
+        assert!(first.contains("-map [out0] -c:v libx264"));
+        assert!(first.contains(&format!("-map [out1] -an -f null -y {}", null_output())));
+        assert!(!first.contains("b.mp4"));
+
+        let last = pass_args(&input, &targets, 60.0, 2, 2, &logs).join(" ");
+        assert!(last.contains("-pass 2 -passlogfile /tmp/log_0"));
+        assert!(last.ends_with("-y b.mp4"));
+    }
+}
@@ -7,13 +7,21 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::Local;
 use log::{debug, error, info};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
+use crate::animation::{Easing, TimeValue, Track};
+use crate::encoding::ExportProfile;
+use crate::reframe::{ReframeMode, ReframePath};
+use crate::timeline::Timeline;
+use crate::variables::{PlanoVars, Unset};
+
 // ============================================================================
 // Data Structures for Plano (Template) System
 // ============================================================================
@@ -24,12 +32,20 @@ const EXAMPLE_IMAGE_DATA: &[u8] = include_bytes!("../example.png");
 
 /// Output resolution for shorts (9:16 aspect ratio)
 #[allow(dead_code)]
-const OUTPUT_WIDTH: u32 = 1080;
+pub(crate) const OUTPUT_WIDTH: u32 = 1080;
 #[allow(dead_code)]
-const OUTPUT_HEIGHT: u32 = 1920;
+pub(crate) const OUTPUT_HEIGHT: u32 = 1920;
+
+/// Clip length assumed when previewing without a real clip
+const PREVIEW_NOMINAL_DURATION: f64 = 30.0;
+
+/// Length of a preview clip when none is given, in seconds
+pub const PREVIEW_CLIP_SECONDS: f64 = 10.0;
+/// Preview clips are rendered at 1/4 of the output resolution
+const PREVIEW_CLIP_SCALE: u32 = 4;
 
 /// Position value that can be pixels, centered, or a special keyword
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(untagged)]
 pub enum PositionValue {
     /// Absolute pixel position
@@ -40,14 +56,14 @@ pub enum PositionValue {
 
 impl PositionValue {
     /// Resolve to actual pixel value given the container size and element size
-    #[allow(dead_code)]
+    /// A centred element larger than its container gets a negative position
     pub fn resolve(&self, container_size: u32, element_size: u32) -> i32 {
         match self {
             PositionValue::Pixels(px) => *px,
             PositionValue::Keyword(kw) => {
                 let kw_lower = kw.to_lowercase();
                 if kw_lower == "center" {
-                    ((container_size - element_size) / 2) as i32
+                    (container_size as i32 - element_size as i32) / 2
                 } else if kw_lower.ends_with('%') {
                     // Parse percentage
                     if let Ok(pct) = kw_lower.trim_end_matches('%').parse::<f32>() {
@@ -63,6 +79,13 @@ impl PositionValue {
     }
 }
 
+impl PositionValue {
+    /// Whether this is the "center" keyword
+    pub fn is_center(&self) -> bool {
+        matches!(self, PositionValue::Keyword(kw) if kw.eq_ignore_ascii_case("center"))
+    }
+}
+
 impl Default for PositionValue {
     fn default() -> Self {
         PositionValue::Pixels(0)
@@ -70,7 +93,7 @@ impl Default for PositionValue {
 }
 
 /// Size value that can be pixels, "full", or percentage
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(untagged)]
 pub enum SizeValue {
     /// Absolute pixel size
@@ -81,7 +104,6 @@ pub enum SizeValue {
 
 impl SizeValue {
     /// Resolve to actual pixel value given the reference size
-    #[allow(dead_code)]
     pub fn resolve(&self, reference_size: u32) -> u32 {
         match self {
             SizeValue::Pixels(px) => *px,
@@ -111,7 +133,7 @@ impl Default for SizeValue {
 }
 
 /// Position and size of an element in the composition
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
 pub struct Position {
     /// X position (from left)
     #[serde(default)]
@@ -128,7 +150,7 @@ pub struct Position {
 }
 
 /// Crop configuration for clips
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
 pub struct Crop {
     /// Start X position for crop (from left)
     #[serde(default)]
@@ -153,7 +175,9 @@ impl Crop {
 }
 
 /// Shader effect types
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Used by `shader` layers (applied to a region of the composition) and in the
+/// `effects` list of clip/image/video layers (applied to that layer only)
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ShaderEffect {
     /// Blur effect with configurable intensity
@@ -161,14 +185,158 @@ pub enum ShaderEffect {
         #[serde(default = "default_blur_intensity")]
         intensity: u32,
     },
+    /// Darken the edges of the region
+    Vignette {
+        /// Lens angle in radians (bigger = stronger), default PI/5
+        #[serde(default = "default_vignette_angle")]
+        angle: f32,
+    },
+    /// Colour grading with a 3D LUT file (.cube)
+    Lut {
+        /// Path to the .cube file
+        path: String,
+    },
+    /// Brightness / contrast / saturation adjustment
+    Eq {
+        /// -1.0 to 1.0, default 0.0
+        #[serde(default)]
+        brightness: f32,
+        /// 0.0 to 2.0 (1.0 = unchanged)
+        #[serde(default = "default_one")]
+        contrast: f32,
+        /// 0.0 to 3.0 (1.0 = unchanged, 0.0 = grayscale)
+        #[serde(default = "default_one")]
+        saturation: f32,
+    },
+    /// Sharpen details
+    Sharpen {
+        /// Strength, default 1.0 (negative values blur)
+        #[serde(default = "default_one")]
+        amount: f32,
+    },
+    /// Mosaic effect, useful to censor chat or faces
+    Pixelate {
+        /// Size of each block in pixels
+        #[serde(default = "default_pixel_block")]
+        block_size: u32,
+    },
+    /// Remove all colour
+    Grayscale,
+    /// Make a colour transparent (green-screen facecams)
+    /// Only meaningful in a layer's `effects`
+    Chromakey {
+        /// Colour to remove, e.g. "0x00FF00" or "green"
+        #[serde(default = "default_key_color")]
+        color: String,
+        /// How close a colour must be to be removed (0.01 - 1.0)
+        #[serde(default = "default_key_similarity")]
+        similarity: f32,
+        /// Edge softness (0.0 - 1.0)
+        #[serde(default = "default_key_blend")]
+        blend: f32,
+    },
 }
 
 fn default_blur_intensity() -> u32 {
     20
 }
 
+fn default_vignette_angle() -> f32 {
+    std::f32::consts::PI / 5.0
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_pixel_block() -> u32 {
+    16
+}
+
+fn default_key_color() -> String {
+    "0x00FF00".to_string()
+}
+
+fn default_key_similarity() -> f32 {
+    0.1
+}
+
+fn default_key_blend() -> f32 {
+    0.05
+}
+
+impl ShaderEffect {
+    /// FFmpeg filter chain for this effect on a `width`x`height` frame
+    /// Returns None when the effect cannot be applied (e.g. missing LUT file)
+    pub fn to_filter(&self, width: u32, height: u32) -> Option<String> {
+        match self {
+            ShaderEffect::Blur { intensity } => {
+                // boxblur fails if the radius exceeds the (chroma) plane size
+                let max_radius = (width.min(height) / 4).max(1);
+                let radius = (*intensity).min(max_radius);
+                Some(format!("boxblur={}:{}", radius, radius))
+            }
+            ShaderEffect::Vignette { angle } => Some(format!("vignette=angle={}", angle)),
+            ShaderEffect::Lut { path } => {
+                if Path::new(path).exists() {
+                    Some(format!("lut3d=file={}", escape_filter_path(path)))
+                } else {
+                    log::warn!("LUT file not found, skipping: {}", path);
+                    None
+                }
+            }
+            ShaderEffect::Eq {
+                brightness,
+                contrast,
+                saturation,
+            } => Some(format!(
+                "eq=brightness={}:contrast={}:saturation={}",
+                brightness, contrast, saturation
+            )),
+            ShaderEffect::Sharpen { amount } => Some(format!("unsharp=5:5:{}:5:5:0", amount)),
+            ShaderEffect::Pixelate { block_size } => {
+                let block = (*block_size).max(1);
+                let small_w = (width / block).max(1);
+                let small_h = (height / block).max(1);
+                Some(format!(
+                    "scale={}:{}:flags=neighbor,scale={}:{}:flags=neighbor",
+                    small_w, small_h, width, height
+                ))
+            }
+            ShaderEffect::Grayscale => Some("hue=s=0".to_string()),
+            ShaderEffect::Chromakey {
+                color,
+                similarity,
+                blend,
+            } => Some(format!(
+                "format=rgba,colorkey={}:{}:{}",
+                color, similarity, blend
+            )),
+        }
+    }
+}
+
+/// Build the filter suffix (",f1,f2") for a layer's own effects
+fn effects_chain(effects: &[ShaderEffect], width: u32, height: u32) -> String {
+    effects
+        .iter()
+        .filter_map(|e| e.to_filter(width, height))
+        .map(|f| format!(",{}", f))
+        .collect()
+}
+
+/// Quote a file path for use as a filter option value
+/// Backslashes become forward slashes and ':' is escaped (Windows drive letters)
+fn escape_filter_path(path: &str) -> String {
+    let escaped = path
+        .replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "'\\''");
+    format!("'{}'", escaped)
+}
+
 /// Scaling mode for the video/clip
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Fit {
     /// Cover the area (crop excess), maintaining aspect ratio
@@ -183,20 +351,277 @@ fn default_fit() -> Fit {
     Fit::Stretch
 }
 
+/// Values of a layer at a point in time
+/// Properties left out keep their static value (or the value of other keyframes)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct Keyframe {
+    /// When the values are reached: seconds, negative seconds from the end, or "NN%"
+    pub time: TimeValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<PositionValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<PositionValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<SizeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<SizeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+    /// Curve used to reach this keyframe from the previous one
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// When a layer is visible within the clip, with optional fades
+/// Times accept the same formats as keyframes (seconds, negative, "NN%")
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct LayerTiming {
+    /// First moment the layer is shown (default: start of the clip)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<TimeValue>,
+    /// Moment the layer disappears (default: end of the clip)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<TimeValue>,
+    /// Fade-in length in seconds, starting at `start`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub fade_in: f64,
+    /// Fade-out length in seconds, ending at `end`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub fade_out: f64,
+}
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+fn is_default_zoom(v: &f64) -> bool {
+    *v == 1.0
+}
+
+/// Smallest zoom accepted on clip layers
+pub(crate) const MIN_ZOOM: f64 = 0.1;
+
+fn is_reframe_off(mode: &ReframeMode) -> bool {
+    *mode == ReframeMode::Off
+}
+
+fn is_true(v: &bool) -> bool {
+    *v
+}
+
+fn is_zero(v: &f64) -> bool {
+    *v == 0.0
+}
+
+impl LayerTiming {
+    /// Visible range in seconds (end is None when the layer lasts until the end)
+    pub fn window(&self, duration: f64) -> (f64, Option<f64>) {
+        let start = self
+            .start
+            .as_ref()
+            .map(|t| t.resolve(duration))
+            .unwrap_or(0.0);
+        let end = self.end.as_ref().map(|t| t.resolve(duration));
+        (start, end)
+    }
+
+    /// Opacity multiplier at `time` (0 = hidden, 1 = fully visible)
+    pub fn visibility_at(&self, time: f64, duration: f64) -> f64 {
+        let (start, end) = self.window(duration);
+        let end = end.unwrap_or(duration.max(start));
+        if time < start || time > end {
+            return 0.0;
+        }
+        let mut factor: f64 = 1.0;
+        if self.fade_in > 0.0 {
+            factor = factor.min((time - start) / self.fade_in);
+        }
+        if self.fade_out > 0.0 {
+            factor = factor.min((end - time) / self.fade_out);
+        }
+        factor.clamp(0.0, 1.0)
+    }
+
+    /// `enable` expression for the overlay (None when always visible)
+    fn enable_expr(&self, duration: f64) -> Option<String> {
+        match self.window(duration) {
+            (start, Some(end)) => Some(format!(
+                "between(t,{},{})",
+                format_seconds(start),
+                format_seconds(end)
+            )),
+            (start, None) if start > 0.0 => Some(format!("gte(t,{})", format_seconds(start))),
+            _ => None,
+        }
+    }
+
+    /// Alpha fade filters for the layer chain (None without fades)
+    fn fade_filters(&self, duration: f64) -> Option<String> {
+        if self.fade_in <= 0.0 && self.fade_out <= 0.0 {
+            return None;
+        }
+        let (start, end) = self.window(duration);
+        let end = end.unwrap_or(duration);
+        let mut filters = vec!["format=rgba".to_string()];
+        if self.fade_in > 0.0 {
+            filters.push(format!(
+                "fade=t=in:st={}:d={}:alpha=1",
+                format_seconds(start),
+                format_seconds(self.fade_in)
+            ));
+        }
+        if self.fade_out > 0.0 {
+            filters.push(format!(
+                "fade=t=out:st={}:d={}:alpha=1",
+                format_seconds((end - self.fade_out).max(start)),
+                format_seconds(self.fade_out)
+            ));
+        }
+        Some(filters.join(","))
+    }
+}
+
+fn format_seconds(v: f64) -> String {
+    format!("{:.3}", v)
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Solid outline drawn along the edge of a layer (inside its box)
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct Border {
+    /// Thickness in pixels
+    #[serde(default = "default_border_width")]
+    pub width: u32,
+    /// FFmpeg colour (name or 0xRRGGBB)
+    #[serde(default = "default_border_color")]
+    pub color: String,
+}
+
+fn default_border_width() -> u32 {
+    6
+}
+
+fn default_border_color() -> String {
+    "white".to_string()
+}
+
+/// Blurred shadow drawn behind a layer
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct Shadow {
+    #[serde(default = "default_shadow_offset")]
+    pub offset_x: i32,
+    #[serde(default = "default_shadow_offset")]
+    pub offset_y: i32,
+    /// Blur radius in pixels
+    #[serde(default = "default_shadow_blur")]
+    pub blur: u32,
+    /// Opacity (0.0 - 1.0)
+    #[serde(default = "default_shadow_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_shadow_color")]
+    pub color: String,
+}
+
+fn default_shadow_offset() -> i32 {
+    12
+}
+
+fn default_shadow_blur() -> u32 {
+    20
+}
+
+fn default_shadow_opacity() -> f32 {
+    0.5
+}
+
+fn default_shadow_color() -> String {
+    "black".to_string()
+}
+
+/// Frame styling of a layer: rounded corners, border and shadow
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct LayerStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<Border>,
+    /// Corner radius in pixels
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub corner_radius: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<Shadow>,
+}
+
+fn is_zero_u32(v: &u32) -> bool {
+    *v == 0
+}
+
+/// Alpha (0-255) of a rounded rectangle of `w`x`h` placed at (`inset`, `inset`)
+/// With `ring`, only the band of that width along the edge is opaque
+/// Edges are anti-aliased over one pixel
+fn rounded_rect_alpha(w: u32, h: u32, radius: u32, inset: u32, ring: Option<u32>) -> String {
+    let radius = radius.min(w.min(h) / 2) as f64;
+    let cx = inset as f64 + w as f64 / 2.0;
+    let cy = inset as f64 + h as f64 / 2.0;
+    let hx = w as f64 / 2.0 - radius;
+    let hy = h as f64 / 2.0 - radius;
+    // Signed distance to the rounded rectangle (negative inside)
+    let sdf = format!(
+        "st(0,abs(X+0.5-{cx})-{hx});st(1,abs(Y+0.5-{cy})-{hy});st(2,hypot(max(ld(0),0),max(ld(1),0))+min(max(ld(0),ld(1)),0)-{radius})"
+    );
+    match ring {
+        Some(width) => format!(
+            "{};255*clip(0.5-ld(2),0,1)*clip(ld(2)+{}+0.5,0,1)",
+            sdf, width
+        ),
+        None => format!("{};255*clip(0.5-ld(2),0,1)", sdf),
+    }
+}
+
 /// A single object in the plano (template)
 /// Order in the array determines layer order (index 0 = back, higher = front)
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PlanoObject {
     /// Original clip from source video
     /// Can be used multiple times (e.g., blurred background + main video)
     Clip {
+        /// Name used to override this layer from a plano that extends or includes it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// Another video file to use instead of the main clip (e.g. a facecam recording)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
+        /// Seconds skipped from the start of the source
+        #[serde(default, skip_serializing_if = "is_zero")]
+        offset: f64,
         position: Position,
         #[serde(default)]
         crop: Option<Crop>,
+        /// Show only the centre of the source (0.7 = 70%, 1.0 = all of it)
+        #[serde(default = "default_zoom", skip_serializing_if = "is_default_zoom")]
+        zoom: f64,
         /// How to fit the video into the position box
         #[serde(default = "default_fit")]
         fit: Fit,
+        /// "auto" follows the subject with a moving crop (ignores `crop`)
+        #[serde(default, skip_serializing_if = "is_reframe_off")]
+        reframe: ReframeMode,
+        /// Write the computed reframe path next to the clip for manual tweaking
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        save_reframe: bool,
+        /// Effects applied to this layer only (e.g. chromakey, grayscale)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        effects: Vec<ShaderEffect>,
+        /// Animated position/size/opacity over time
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        keyframes: Vec<Keyframe>,
+        /// Rounded corners, border and shadow
+        #[serde(flatten)]
+        style: LayerStyle,
+        /// Visible time range and fades
+        #[serde(flatten)]
+        timing: LayerTiming,
         /// User comment (ignored during processing)
         #[serde(default)]
         comment: Option<String>,
@@ -204,21 +629,56 @@ pub enum PlanoObject {
 
     /// Static image overlay (frames, watermarks, promo)
     Image {
+        /// Name used to override this layer from a plano that extends or includes it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         /// Path to the image file
+        /// Animated GIF/APNG/WebP files and numbered sequences ("frames/%03d.png") are played
         path: String,
         position: Position,
         /// Opacity (0.0 - 1.0, default 1.0)
         #[serde(default = "default_opacity")]
         opacity: f32,
+        /// Repeat an animated image until the end of the clip
+        #[serde(default = "default_true", skip_serializing_if = "is_true")]
+        loop_animation: bool,
+        /// Playback rate of an animated image (default: its own timing, 25 for sequences)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fps: Option<f64>,
+        /// Leave the layer out without complaint when the file is missing
+        /// (e.g. a title image that only some clips have)
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        optional: bool,
+        /// Effects applied to this layer only (e.g. chromakey, grayscale)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        effects: Vec<ShaderEffect>,
+        /// Animated position/size/opacity over time
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        keyframes: Vec<Keyframe>,
+        /// Rounded corners, border and shadow
+        #[serde(flatten)]
+        style: LayerStyle,
+        /// Visible time range and fades
+        #[serde(flatten)]
+        timing: LayerTiming,
         /// User comment (ignored during processing)
         #[serde(default)]
         comment: Option<String>,
     },
 
-    /// Shader effect applied to previous layer or source
+    /// Shader effect applied to the composition below, inside `position`
     Shader {
+        /// Name used to override this layer from a plano that extends or includes it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         effect: ShaderEffect,
         position: Position,
+        /// Animated region position (only `x` and `y` are used)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        keyframes: Vec<Keyframe>,
+        /// Visible time range and fades
+        #[serde(flatten)]
+        timing: LayerTiming,
         /// User comment (ignored during processing)
         #[serde(default)]
         comment: Option<String>,
@@ -226,6 +686,9 @@ pub enum PlanoObject {
 
     /// Background video (gameplay, animations, etc.)
     Video {
+        /// Name used to override this layer from a plano that extends or includes it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
         /// Path to the video file
         path: String,
         position: Position,
@@ -241,12 +704,395 @@ pub enum PlanoObject {
         /// How to fit the video into the position box
         #[serde(default = "default_fit")]
         fit: Fit,
+        /// Effects applied to this layer only (e.g. chromakey, grayscale)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        effects: Vec<ShaderEffect>,
+        /// Animated position/size/opacity over time
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        keyframes: Vec<Keyframe>,
+        /// Rounded corners, border and shadow
+        #[serde(flatten)]
+        style: LayerStyle,
+        /// Visible time range and fades
+        #[serde(flatten)]
+        timing: LayerTiming,
         /// User comment (ignored during processing)
         #[serde(default)]
         comment: Option<String>,
     },
 }
 
+impl PlanoObject {
+    /// Visible time range of the layer
+    pub fn timing(&self) -> &LayerTiming {
+        match self {
+            PlanoObject::Clip { timing, .. }
+            | PlanoObject::Image { timing, .. }
+            | PlanoObject::Shader { timing, .. }
+            | PlanoObject::Video { timing, .. } => timing,
+        }
+    }
+
+    /// Placement of the layer
+    pub fn position(&self) -> &Position {
+        match self {
+            PlanoObject::Clip { position, .. }
+            | PlanoObject::Image { position, .. }
+            | PlanoObject::Shader { position, .. }
+            | PlanoObject::Video { position, .. } => position,
+        }
+    }
+
+    /// Box of the layer on the output canvas (x, y, width, height), ignoring keyframes
+    pub fn canvas_box(&self) -> (i32, i32, u32, u32) {
+        let position = self.position();
+        let width = position.width.resolve(OUTPUT_WIDTH);
+        let height = position.height.resolve(OUTPUT_HEIGHT);
+        let x = position.x.resolve(OUTPUT_WIDTH, width);
+        let y = position.y.resolve(OUTPUT_HEIGHT, height);
+        (x, y, width, height)
+    }
+
+    /// Layer id used for overrides, if any
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            PlanoObject::Clip { id, .. }
+            | PlanoObject::Image { id, .. }
+            | PlanoObject::Shader { id, .. }
+            | PlanoObject::Video { id, .. } => id.as_deref(),
+        }
+    }
+
+    /// Divide every pixel value of the layer by `divisor`, for a canvas that
+    /// many times smaller (keywords, percentages and source crops are kept)
+    fn scale_down(&mut self, divisor: u32) {
+        match self {
+            PlanoObject::Clip {
+                position,
+                effects,
+                keyframes,
+                style,
+                ..
+            }
+            | PlanoObject::Image {
+                position,
+                effects,
+                keyframes,
+                style,
+                ..
+            }
+            | PlanoObject::Video {
+                position,
+                effects,
+                keyframes,
+                style,
+                ..
+            } => {
+                position.scale_down(divisor);
+                effects.iter_mut().for_each(|e| e.scale_down(divisor));
+                keyframes.iter_mut().for_each(|k| k.scale_down(divisor));
+                style.scale_down(divisor);
+            }
+            PlanoObject::Shader {
+                effect,
+                position,
+                keyframes,
+                ..
+            } => {
+                effect.scale_down(divisor);
+                position.scale_down(divisor);
+                keyframes.iter_mut().for_each(|k| k.scale_down(divisor));
+            }
+        }
+    }
+}
+
+/// A pixel amount on a canvas `divisor` times smaller
+fn scale_px(px: i32, divisor: u32) -> i32 {
+    (px as f64 / divisor.max(1) as f64).round() as i32
+}
+
+/// Like `scale_px`, but a non-zero size stays at least one pixel
+fn scale_size(px: u32, divisor: u32) -> u32 {
+    if px == 0 {
+        0
+    } else {
+        (scale_px(px as i32, divisor) as u32).max(1)
+    }
+}
+
+impl PositionValue {
+    fn scale_down(&mut self, divisor: u32) {
+        if let PositionValue::Pixels(px) = self {
+            *px = scale_px(*px, divisor);
+        }
+    }
+}
+
+impl SizeValue {
+    fn scale_down(&mut self, divisor: u32) {
+        if let SizeValue::Pixels(px) = self {
+            *px = scale_size(*px, divisor);
+        }
+    }
+}
+
+impl Position {
+    fn scale_down(&mut self, divisor: u32) {
+        self.x.scale_down(divisor);
+        self.y.scale_down(divisor);
+        self.width.scale_down(divisor);
+        self.height.scale_down(divisor);
+    }
+}
+
+impl Keyframe {
+    fn scale_down(&mut self, divisor: u32) {
+        self.x.iter_mut().for_each(|v| v.scale_down(divisor));
+        self.y.iter_mut().for_each(|v| v.scale_down(divisor));
+        self.width.iter_mut().for_each(|v| v.scale_down(divisor));
+        self.height.iter_mut().for_each(|v| v.scale_down(divisor));
+    }
+}
+
+impl LayerStyle {
+    fn scale_down(&mut self, divisor: u32) {
+        self.corner_radius = scale_size(self.corner_radius, divisor);
+        if let Some(border) = &mut self.border {
+            border.width = scale_size(border.width, divisor);
+        }
+        if let Some(shadow) = &mut self.shadow {
+            shadow.offset_x = scale_px(shadow.offset_x, divisor);
+            shadow.offset_y = scale_px(shadow.offset_y, divisor);
+            shadow.blur = scale_size(shadow.blur, divisor);
+        }
+    }
+}
+
+impl ShaderEffect {
+    fn scale_down(&mut self, divisor: u32) {
+        match self {
+            ShaderEffect::Blur { intensity } => *intensity = scale_size(*intensity, divisor),
+            ShaderEffect::Pixelate { block_size } => *block_size = scale_size(*block_size, divisor),
+            _ => {}
+        }
+    }
+}
+
+/// A plano document: the layer list plus document-level settings
+///
+/// On disk a plano is either a bare array of layers (the original format)
+/// or an object with a `layers` array and optional settings such as `profile`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct Plano {
+    /// JSON Schema reference for editors (ignored during processing)
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Encoder settings for shorts exported with this plano
+    /// Overrides the `export_profile` from settings.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<ExportProfile>,
+    /// Video played before every short
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intro: Option<Bumper>,
+    /// Video played after every short
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outro: Option<Bumper>,
+    /// Base plano this one builds on: its layers go below these, its settings
+    /// apply unless set here, and layers with a matching `id` are replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Plano files whose layers are added on top of these (watermarks, frames...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Plano used instead of this one for moments of a category (e.g. "Funny": "meme.json")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, String>,
+    /// Whether a file of the plano has `{{name}}` placeholders, so it has to
+    /// be loaded again with the variables of each clip
+    #[serde(skip)]
+    pub variables: bool,
+    /// Placeholders that had no value when loading (see `schema::fill_placeholders`)
+    #[serde(skip)]
+    pub unset: Vec<Unset>,
+    /// Layers, back to front
+    #[serde(default)]
+    pub layers: Vec<PlanoObject>,
+}
+
+/// Intro or outro video joined to every exported short
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct Bumper {
+    /// Path to the video file
+    pub path: String,
+    /// Crossfade with the short in seconds (0 = hard cut)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub crossfade: f64,
+}
+
+impl Plano {
+    /// Whether the document only carries layers (saved as a bare array)
+    fn is_bare(&self) -> bool {
+        self.schema.is_none()
+            && self.extends.is_none()
+            && self.include.is_empty()
+            && self.categories.is_empty()
+            && self.profile.is_none()
+            && self.intro.is_none()
+            && self.outro.is_none()
+    }
+
+    /// Expand `~`/variables in every asset path and make relative ones start at `base_dir`
+    pub fn resolve_paths(&mut self, base_dir: &Path) {
+        let resolve = |path: &mut String| *path = crate::assets::resolve_path(path, base_dir);
+        let resolve_effects = |effects: &mut [ShaderEffect]| {
+            for effect in effects {
+                if let ShaderEffect::Lut { path } = effect {
+                    resolve(path);
+                }
+            }
+        };
+
+        for bumper in [&mut self.intro, &mut self.outro].into_iter().flatten() {
+            resolve(&mut bumper.path);
+        }
+        let referenced = self.extends.iter_mut().chain(&mut self.include);
+        for plano in referenced.chain(self.categories.values_mut()) {
+            resolve(plano);
+        }
+        for layer in &mut self.layers {
+            match layer {
+                PlanoObject::Clip {
+                    source, effects, ..
+                } => {
+                    if let Some(source) = source {
+                        resolve(source);
+                    }
+                    resolve_effects(effects);
+                }
+                PlanoObject::Image { path, effects, .. }
+                | PlanoObject::Video { path, effects, .. } => {
+                    resolve(path);
+                    resolve_effects(effects);
+                }
+                PlanoObject::Shader { effect, .. } => {
+                    resolve_effects(std::slice::from_mut(effect));
+                }
+            }
+        }
+    }
+
+    /// Merge the `extends` base and the `include` fragments into this plano
+    /// `load` reads a referenced plano, already composed itself
+    ///
+    /// Layers end up as base, own, included (back to front). A layer whose `id`
+    /// matches an inherited one replaces it in place instead of being added.
+    fn compose(mut self, mut load: impl FnMut(&str) -> Result<Plano>) -> Result<Plano> {
+        let mut below = Vec::new();
+        if let Some(path) = self.extends.take() {
+            let base =
+                load(&path).with_context(|| format!("Failed to load base plano: {}", path))?;
+            self.profile = self.profile.or(base.profile);
+            self.intro = self.intro.or(base.intro);
+            self.outro = self.outro.or(base.outro);
+            for (category, plano) in base.categories {
+                self.categories.entry(category).or_insert(plano);
+            }
+            self.variables |= base.variables;
+            self.unset.extend(referenced_unset(&path, base.unset));
+            below = base.layers;
+        }
+
+        let mut above = Vec::new();
+        for path in std::mem::take(&mut self.include) {
+            let fragment =
+                load(&path).with_context(|| format!("Failed to load included plano: {}", path))?;
+            self.variables |= fragment.variables;
+            self.unset.extend(referenced_unset(&path, fragment.unset));
+            for layer in fragment.layers {
+                if let Some(layer) = override_layer(&mut [&mut below, &mut above], layer) {
+                    above.push(layer);
+                }
+            }
+        }
+
+        let mut own = Vec::new();
+        for layer in std::mem::take(&mut self.layers) {
+            if let Some(layer) = override_layer(&mut [&mut below, &mut above], layer) {
+                own.push(layer);
+            }
+        }
+
+        self.layers = below.into_iter().chain(own).chain(above).collect();
+        Ok(self)
+    }
+
+    /// Plano mapped to a moment category, matched ignoring case
+    pub fn category_plano(&self, category: &str) -> Option<&str> {
+        self.categories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .map(|(_, path)| path.as_str())
+    }
+
+    /// Profile to encode with, falling back to the given default
+    pub fn profile_or<'a>(&'a self, fallback: &'a ExportProfile) -> &'a ExportProfile {
+        self.profile.as_ref().unwrap_or(fallback)
+    }
+}
+
+impl From<Vec<PlanoObject>> for Plano {
+    fn from(layers: Vec<PlanoObject>) -> Self {
+        Self {
+            layers,
+            ..Default::default()
+        }
+    }
+}
+
+/// A plano loaded from disk, kept together with its path
+#[derive(Debug, Clone)]
+pub struct LoadedPlano {
+    pub path: String,
+    pub plano: Plano,
+}
+
+impl LoadedPlano {
+    /// Load a plano file
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Self {
+            path: path.to_string(),
+            plano: load_plano(path)?,
+        })
+    }
+
+    /// Short name used for output folders (the file name without extension)
+    pub fn name(&self) -> String {
+        Path::new(&self.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "plano".to_string())
+    }
+}
+
+/// Put `layer` in place of the layer with the same id in `groups`
+/// Returns it back when it has no id or nothing matches
+fn override_layer(groups: &mut [&mut Vec<PlanoObject>], layer: PlanoObject) -> Option<PlanoObject> {
+    let Some(id) = layer.id() else {
+        return Some(layer);
+    };
+    let slot = groups
+        .iter_mut()
+        .find_map(|group| group.iter_mut().find(|l| l.id() == Some(id)));
+    match slot {
+        Some(slot) => {
+            *slot = layer;
+            None
+        }
+        None => Some(layer),
+    }
+}
+
 fn default_opacity() -> f32 {
     1.0
 }
@@ -259,30 +1105,127 @@ fn default_true() -> bool {
 // Plano (Template) Management
 // ============================================================================
 
-/// Load a plano from a JSON file
-pub fn load_plano(path: &str) -> Result<Vec<PlanoObject>> {
-    let content =
-        fs::read_to_string(path).with_context(|| format!("Failed to read plano file: {}", path))?;
+/// Load a plano from a JSON file or a bundle (folder or .zip with `plano.json`)
+/// Asset paths are resolved against the folder of the plano file, and the
+/// `extends`/`include` planos are merged in
+/// Placeholders get their default values (see `load_plano_with`)
+pub fn load_plano(path: &str) -> Result<Plano> {
+    load_plano_with(path, &PlanoVars::new())
+}
+
+/// Load a plano filling its `{{name}}` placeholders with `vars`
+pub fn load_plano_with(path: &str, vars: &PlanoVars) -> Result<Plano> {
+    load_plano_chain(path, vars, &mut Vec::new())
+}
+
+/// `chain` holds the planos being loaded, to catch one that includes itself
+fn load_plano_chain(path: &str, vars: &PlanoVars, chain: &mut Vec<PathBuf>) -> Result<Plano> {
+    let key = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    if chain.contains(&key) {
+        anyhow::bail!("Plano extends or includes itself: {}", path);
+    }
+
+    let file = crate::assets::plano_file(Path::new(path))?;
+    let content = fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read plano file: {}", path))?;
+
+    let content = remove_js_comments(&content);
+    let variables = crate::variables::has_placeholders(&content);
+    let content = crate::variables::substitute(&content, vars);
+
+    let parsed = match variables {
+        true => parse_plano_filled(&content),
+        false => parse_plano(&content),
+    };
+    let mut plano = parsed.with_context(|| format!("Failed to parse plano JSON: {}", path))?;
+    plano.variables = variables;
+    if !vars.is_empty() {
+        for unset in &plano.unset {
+            log::warn!(
+                "{}: {}: variable `{}` has no value and no default",
+                path,
+                unset.field,
+                unset.name
+            );
+        }
+    }
+
+    chain.push(key);
+    let plano = compose_document(plano, &file, vars, chain);
+    chain.pop();
+    plano
+}
+
+/// Compose a plano document as if it had been read from `file`, e.g. one
+/// being edited that is not saved yet (same result as `load_plano`)
+pub fn compose_plano_document(plano: Plano, file: &Path) -> Result<Plano> {
+    let key = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+    compose_document(plano, file, &PlanoVars::new(), &mut vec![key])
+}
+
+/// Resolve the paths of a parsed document against its folder and merge its
+/// `extends`/`include` planos
+fn compose_document(
+    mut plano: Plano,
+    file: &Path,
+    vars: &PlanoVars,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Plano> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    plano.resolve_paths(&dir);
+    plano.compose(|referenced| load_plano_chain(referenced, vars, chain))
+}
 
+/// Parse plano JSON, accepting both the bare array and the document form
+pub fn parse_plano(content: &str) -> Result<Plano> {
     // Remove // comments (for tech-savvy users)
-    let cleaned = remove_js_comments(&content);
+    let cleaned = remove_js_comments(content);
 
-    let plano: Vec<PlanoObject> = serde_json::from_str(&cleaned)
-        .with_context(|| format!("Failed to parse plano JSON: {}", path))?;
+    if cleaned.trim_start().starts_with('[') {
+        let layers: Vec<PlanoObject> = serde_json::from_str(&cleaned)?;
+        Ok(Plano::from(layers))
+    } else {
+        Ok(serde_json::from_str(&cleaned)?)
+    }
+}
 
+/// Parse substituted plano JSON, filling the placeholders left without a value
+fn parse_plano_filled(content: &str) -> Result<Plano> {
+    let mut value: serde_json::Value = serde_json::from_str(content)?;
+    let unset = crate::schema::fill_placeholders(&mut value);
+    let mut plano = if value.is_array() {
+        Plano::from(serde_json::from_value::<Vec<PlanoObject>>(value)?)
+    } else {
+        serde_json::from_value::<Plano>(value)?
+    };
+    plano.unset = unset;
     Ok(plano)
 }
 
+/// Unset placeholders of a referenced plano, named after its file
+fn referenced_unset(path: &str, unset: Vec<Unset>) -> impl Iterator<Item = Unset> + '_ {
+    unset.into_iter().map(move |u| Unset {
+        field: format!("{}: {}", path, u.field),
+        ..u
+    })
+}
+
 /// Save a plano to a JSON file
-pub fn save_plano(path: &str, plano: &[PlanoObject]) -> Result<()> {
-    let json = serde_json::to_string_pretty(plano)?;
+/// Planos without document-level settings are kept as a bare array
+pub fn save_plano(path: &str, plano: &Plano) -> Result<()> {
+    let json = if plano.is_bare() {
+        serde_json::to_string_pretty(&plano.layers)?
+    } else {
+        serde_json::to_string_pretty(plano)?
+    };
     fs::write(path, json)?;
     Ok(())
 }
 
 /// Remove JavaScript-style // comments from JSON
 /// This allows tech users to add inline comments
-fn remove_js_comments(content: &str) -> String {
+pub(crate) fn remove_js_comments(content: &str) -> String {
     let mut result = String::new();
     let mut in_string = false;
     let mut chars = content.chars().peekable();
@@ -320,6 +1263,9 @@ pub fn create_default_plano() -> Vec<PlanoObject> {
     vec![
         // Layer 0: Blurred background (full screen)
         PlanoObject::Clip {
+            id: None,
+            source: None,
+            offset: 0.0,
             position: Position {
                 x: PositionValue::Pixels(0),
                 y: PositionValue::Pixels(0),
@@ -327,11 +1273,19 @@ pub fn create_default_plano() -> Vec<PlanoObject> {
                 height: SizeValue::Keyword("full".to_string()),
             },
             crop: None,
+            zoom: 1.0,
             fit: Fit::Cover,
-            comment: Some("Fondo desenfocado del clip original".to_string()),
+            reframe: ReframeMode::Off,
+            save_reframe: false,
+            effects: Vec::new(),
+            keyframes: Vec::new(),
+            style: LayerStyle::default(),
+            timing: LayerTiming::default(),
+            comment: Some(rust_i18n::t!("plano_comment_blurred_background").to_string()),
         },
         // Layer 1: Blur shader on background
         PlanoObject::Shader {
+            id: None,
             effect: ShaderEffect::Blur { intensity: 20 },
             position: Position {
                 x: PositionValue::Pixels(0),
@@ -339,10 +1293,15 @@ pub fn create_default_plano() -> Vec<PlanoObject> {
                 width: SizeValue::Keyword("full".to_string()),
                 height: SizeValue::Keyword("full".to_string()),
             },
-            comment: Some("Shader de blur sobre el fondo".to_string()),
+            keyframes: Vec::new(),
+            timing: LayerTiming::default(),
+            comment: Some(rust_i18n::t!("plano_comment_blur_shader").to_string()),
         },
         // Layer 2: Main video in center
         PlanoObject::Clip {
+            id: None,
+            source: None,
+            offset: 0.0,
             position: Position {
                 x: PositionValue::Pixels(0),
                 y: PositionValue::Keyword("center".to_string()),
@@ -350,283 +1309,1429 @@ pub fn create_default_plano() -> Vec<PlanoObject> {
                 height: SizeValue::Pixels(1200),
             },
             crop: None,
+            zoom: 1.0,
             fit: Fit::Cover,
-            comment: Some("Video principal del clip".to_string()),
+            reframe: ReframeMode::Off,
+            save_reframe: false,
+            effects: Vec::new(),
+            keyframes: Vec::new(),
+            style: LayerStyle::default(),
+            timing: LayerTiming::default(),
+            comment: Some(rust_i18n::t!("plano_comment_main_video").to_string()),
         },
     ]
 }
 
-// ============================================================================
-// FFmpeg Filter Generation
+/// Ready-made layouts shipped as presets in the plano library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanoLayout {
+    /// Blurred background with the clip in the middle
+    Default,
+    /// Gameplay on top, facecam below
+    TopBottom,
+    /// Full-screen gameplay with a small facecam in a corner
+    Facecam,
+    /// Two moments of the clip next to each other
+    SideBySide,
+    /// Two speakers of a wide shot, one above the other
+    Podcast,
+    /// The whole 16:9 clip on black with a title bar above it
+    Letterbox,
+}
+
+impl PlanoLayout {
+    pub const ALL: [PlanoLayout; 6] = [
+        PlanoLayout::Default,
+        PlanoLayout::TopBottom,
+        PlanoLayout::Facecam,
+        PlanoLayout::SideBySide,
+        PlanoLayout::Podcast,
+        PlanoLayout::Letterbox,
+    ];
+
+    /// Translation key of the layout name
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            PlanoLayout::Default => "plano_layout_default",
+            PlanoLayout::TopBottom => "plano_layout_top_bottom",
+            PlanoLayout::Facecam => "plano_layout_facecam",
+            PlanoLayout::SideBySide => "plano_layout_side_by_side",
+            PlanoLayout::Podcast => "plano_layout_podcast",
+            PlanoLayout::Letterbox => "plano_layout_letterbox",
+        }
+    }
+
+    /// File name of the preset in the plano library
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            PlanoLayout::Default => "plano.json",
+            PlanoLayout::TopBottom => "plano_top_bottom.json",
+            PlanoLayout::Facecam => "plano_facecam.json",
+            PlanoLayout::SideBySide => "plano_side_by_side.json",
+            PlanoLayout::Podcast => "plano_podcast.json",
+            PlanoLayout::Letterbox => "plano_letterbox.json",
+        }
+    }
+}
+
+/// Facecam area assumed by the layouts: bottom-right quarter of a 1920x1080 stream
+fn facecam_crop() -> Crop {
+    Crop {
+        x_from: Some(1440),
+        x_to: Some(1920),
+        y_from: Some(810),
+        y_to: Some(1080),
+    }
+}
+
+/// Clip layer with the usual defaults
+fn clip_layer(position: Position, crop: Option<Crop>, offset: f64, comment: &str) -> PlanoObject {
+    let comment = rust_i18n::t!(comment).to_string();
+    PlanoObject::Clip {
+        id: None,
+        source: None,
+        offset,
+        position,
+        crop,
+        zoom: 1.0,
+        fit: Fit::Cover,
+        reframe: ReframeMode::Off,
+        save_reframe: false,
+        effects: Vec::new(),
+        keyframes: Vec::new(),
+        style: LayerStyle::default(),
+        timing: LayerTiming::default(),
+        comment: Some(comment),
+    }
+}
+
+/// Horizontal slice of a 1920x1080 source, full height
+fn columns(x_from: i32, x_to: i32) -> Option<Crop> {
+    Some(Crop {
+        x_from: Some(x_from),
+        x_to: Some(x_to),
+        y_from: None,
+        y_to: None,
+    })
+}
+
+fn boxed(x: i32, y: i32, width: u32, height: u32) -> Position {
+    Position {
+        x: PositionValue::Pixels(x),
+        y: PositionValue::Pixels(y),
+        width: SizeValue::Pixels(width),
+        height: SizeValue::Pixels(height),
+    }
+}
+
+/// Create a plano for one of the ready-made layouts
+pub fn create_layout_plano(layout: PlanoLayout) -> Vec<PlanoObject> {
+    match layout {
+        PlanoLayout::Default => create_default_plano(),
+        PlanoLayout::TopBottom => vec![
+            clip_layer(
+                boxed(0, 0, OUTPUT_WIDTH, 1152),
+                columns(0, 1440),
+                0.0,
+                "plano_comment_gameplay_top",
+            ),
+            clip_layer(
+                boxed(0, 1152, OUTPUT_WIDTH, 768),
+                Some(facecam_crop()),
+                0.0,
+                "plano_comment_facecam_bottom",
+            ),
+        ],
+        PlanoLayout::Facecam => vec![
+            clip_layer(
+                Position::default(),
+                None,
+                0.0,
+                "plano_comment_gameplay_full",
+            ),
+            clip_layer(
+                boxed(60, 1340, 480, 480),
+                Some(facecam_crop()),
+                0.0,
+                "plano_comment_facecam_corner",
+            ),
+        ],
+        PlanoLayout::SideBySide => {
+            let mut layers = create_default_plano();
+            layers.truncate(2); // Keep the blurred background
+            layers.push(clip_layer(
+                boxed(0, 480, 540, 960),
+                None,
+                0.0,
+                "plano_comment_moment_a",
+            ));
+            layers.push(clip_layer(
+                boxed(540, 480, 540, 960),
+                None,
+                5.0,
+                "plano_comment_moment_b",
+            ));
+            layers
+        }
+        PlanoLayout::Podcast => vec![
+            clip_layer(
+                boxed(0, 0, OUTPUT_WIDTH, 960),
+                columns(0, 960),
+                0.0,
+                "plano_comment_speaker_a",
+            ),
+            clip_layer(
+                boxed(0, 960, OUTPUT_WIDTH, 960),
+                columns(960, 1920),
+                0.0,
+                "plano_comment_speaker_b",
+            ),
+        ],
+        PlanoLayout::Letterbox => vec![
+            // The canvas is black; a lifted strip of it becomes the title bar
+            PlanoObject::Shader {
+                id: None,
+                effect: ShaderEffect::Eq {
+                    brightness: 0.12,
+                    contrast: 1.0,
+                    saturation: 1.0,
+                },
+                position: boxed(0, 400, OUTPUT_WIDTH, 220),
+                keyframes: Vec::new(),
+                timing: LayerTiming::default(),
+                comment: Some(rust_i18n::t!("plano_comment_title_bar").to_string()),
+            },
+            // Title of the clip (`title_image` in its .vars.json) or a shared one
+            PlanoObject::Image {
+                id: Some("title".to_string()),
+                path: "{{title_image|title.png}}".to_string(),
+                position: boxed(40, 420, 1000, 180),
+                opacity: 1.0,
+                loop_animation: true,
+                fps: None,
+                optional: true,
+                effects: Vec::new(),
+                keyframes: Vec::new(),
+                style: LayerStyle::default(),
+                timing: LayerTiming::default(),
+                comment: Some(rust_i18n::t!("plano_comment_title_image").to_string()),
+            },
+            clip_layer(
+                boxed(0, 656, OUTPUT_WIDTH, 608),
+                None,
+                0.0,
+                "plano_comment_letterboxed_video",
+            ),
+        ],
+    }
+}
+
+// ============================================================================
+// FFmpeg Filter Generation
 // ============================================================================
 
 /// Context for building FFmpeg filter chain
-#[allow(dead_code)]
+/// Several planos can be composed into the same graph, sharing the clip input
 struct FilterContext {
     /// List of input files (indices for FFmpeg)
     inputs: Vec<String>,
-    /// Current output label
-    current_label: String,
     /// Filter chain parts
     filters: Vec<String>,
     /// Counter for generating unique labels
     label_counter: usize,
 }
 
-#[allow(dead_code)]
 impl FilterContext {
-    fn new() -> Self {
+    /// Create a context with the main clip as input 0
+    fn new(clip_path: &str) -> Self {
         Self {
-            inputs: Vec::new(),
-            current_label: String::new(),
+            inputs: vec![clip_path.to_string()],
             filters: Vec::new(),
             label_counter: 0,
         }
     }
 
-    fn next_label(&mut self) -> String {
-        let label = format!("layer{}", self.label_counter);
+    fn next_label(&mut self, prefix: &str) -> String {
+        let label = format!("{}{}", prefix, self.label_counter);
         self.label_counter += 1;
         label
     }
-}
-
-/// Build FFmpeg filter_complex string from a plano
-/// Returns (filter_string, input_files_needed)
-#[allow(dead_code)]
-pub fn build_ffmpeg_filter(plano: &[PlanoObject], clip_path: &str) -> (String, Vec<String>) {
-    let mut ctx = FilterContext::new();
-    let mut inputs: Vec<String> = vec![clip_path.to_string()]; // Main clip is always input 0
-    let clip_input_used = 0; // Track which input index to use for clips
-
-    // First pass: collect all additional inputs needed
-    let mut additional_inputs: Vec<(usize, String)> = Vec::new(); // (plano_index, path)
 
-    for (idx, obj) in plano.iter().enumerate() {
-        match obj {
-            PlanoObject::Image { path, .. } => {
-                if Path::new(path).exists() {
-                    additional_inputs.push((idx, path.clone()));
-                }
-            }
-            PlanoObject::Video { path, .. } => {
-                if Path::new(path).exists() {
-                    additional_inputs.push((idx, path.clone()));
-                }
-            }
-            _ => {}
+    /// Register an input file, reusing the index if it was already added
+    fn add_input(&mut self, path: &str) -> usize {
+        if let Some(idx) = self.inputs.iter().position(|p| p == path) {
+            return idx;
         }
+        self.inputs.push(path.to_string());
+        self.inputs.len() - 1
     }
-
-    // Add additional inputs
-    for (_, path) in &additional_inputs {
-        inputs.push(path.clone());
-    }
-
-    // Build input index mapping for additional files
-    let mut input_mapping: std::collections::HashMap<usize, usize> =
-        std::collections::HashMap::new();
-    for (i, (plano_idx, _)) in additional_inputs.iter().enumerate() {
-        input_mapping.insert(*plano_idx, i + 1); // +1 because main clip is 0
-    }
-
-    let mut current_label = "base".to_string();
-
-    ctx.filters.push(format!(
-        "color=c=black:s={}x{}:r=60:d=36000[base]",
-        OUTPUT_WIDTH, OUTPUT_HEIGHT
-    ));
-
-    for (idx, obj) in plano.iter().enumerate() {
-        let next_label = if idx == plano.len() - 1 {
-            "out".to_string()
+    /// Compose one plano on its own canvas, ending in `[out_label]`
+    fn compose(&mut self, plano: &[PlanoObject], out_label: &str, options: &RenderOptions) {
+        // A smaller canvas needs the pixel values of the layers scaled with it
+        let scaled: Vec<PlanoObject>;
+        let plano = if options.scale > 1 {
+            scaled = plano
+                .iter()
+                .cloned()
+                .map(|mut obj| {
+                    obj.scale_down(options.scale);
+                    obj
+                })
+                .collect();
+            &scaled
         } else {
-            ctx.next_label()
+            plano
         };
+        let (canvas_w, canvas_h) = options.canvas();
+
+        let mut current_label = self.next_label("base");
+        self.filters.push(format!(
+            "color=c=black:s={}x{}:r=60:d=36000[{}]",
+            canvas_w, canvas_h, current_label
+        ));
+        let mut main_clip = options.main_clip.iter();
+
+        for obj in plano {
+            // Previews skip layers that are hidden at the preview time
+            let timing = obj.timing();
+            let visibility = options
+                .still_at
+                .map(|t| timing.visibility_at(t, options.duration))
+                .unwrap_or(1.0);
+            if visibility <= 0.0 {
+                continue;
+            }
 
-        match obj {
-            PlanoObject::Clip {
-                position,
-                crop,
-                fit,
-                ..
-            } => {
-                let w = position.width.resolve(OUTPUT_WIDTH);
-                let h = position.height.resolve(OUTPUT_HEIGHT);
-
-                // Start with input
-                // Fix: Force timestamp to start at 0 to avoid invisible first frame/sync issues
-                // This is critical for overlays to work correctly from frame 0
-                let mut base_filter = format!("[{}:v]setpts=PTS-STARTPTS,", clip_input_used);
-
-                // Apply user crop first if specified
-                if let Some(c) = crop {
-                    if c.is_specified() {
-                        let x_from = c.x_from.unwrap_or(0);
-                        let x_to = c.x_to.unwrap_or(0);
-                        let y_from = c.y_from.unwrap_or(0);
-                        let y_to = c.y_to.unwrap_or(0);
-
-                        if x_to > x_from {
-                            let crop_w = x_to - x_from;
-                            let crop_x = x_from;
-                            base_filter =
-                                format!("{}crop={}:ih:{}:0,", base_filter, crop_w, crop_x);
-                        }
-                        if y_to > y_from {
-                            let crop_h = y_to - y_from;
-                            let crop_y = y_from;
-                            base_filter =
-                                format!("{}crop=iw:{}:{}:{},", base_filter, crop_h, 0, crop_y);
+            match obj {
+                PlanoObject::Clip {
+                    source,
+                    offset,
+                    position,
+                    crop,
+                    zoom,
+                    fit,
+                    reframe,
+                    effects,
+                    keyframes,
+                    style,
+                    ..
+                } => {
+                    // Main clip is always input 0; other sources get their own input
+                    let input_idx = match source {
+                        Some(src) if Path::new(src).exists() => self.add_input(src),
+                        Some(src) => {
+                            log::warn!("Clip source not found, skipping layer: {}", src);
+                            continue;
                         }
-                    }
-                }
+                        None => 0,
+                    };
 
-                // Now apply scaling based on Fit mode
-                let scale_filter = match fit {
-                    Fit::Cover => format!(
-                        "{}scale={}:{}:force_original_aspect_ratio=increase,crop={}:{}",
-                        base_filter, w, h, w, h
-                    ),
-                    Fit::Contain => format!(
-                        "{}scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2,setsar=1",
-                        base_filter, w, h, w, h
-                    ),
-                    Fit::Stretch => format!(
-                        "{}scale={}:{},setsar=1",
-                        base_filter, w, h
-                    ),
-                };
-
-                // Overlay on previous
-                let x = position.x.resolve(OUTPUT_WIDTH, w);
-                let y = position.y.resolve(OUTPUT_HEIGHT, h);
-
-                ctx.filters.push(format!("{}[tmp{}]", scale_filter, idx));
-                ctx.filters.push(format!(
-                    "[{}][tmp{}]overlay={}:{}[{}]",
-                    current_label, idx, x, y, next_label
-                ));
+                    let placement = Placement::resolve(position, keyframes, 1.0, timing, options);
+                    let (w, h) = (placement.w, placement.h);
 
-                current_label = next_label;
-            }
+                    // Skip into the source. Previews of the main clip already
+                    // start from the preview frame, so they cannot be offset
+                    let skip = match options.still_at {
+                        Some(_) if input_idx == 0 => 0.0,
+                        Some(time) => offset + time,
+                        None => *offset,
+                    };
 
-            PlanoObject::Shader {
-                effect, position, ..
-            } => {
-                let _w = position.width.resolve(OUTPUT_WIDTH);
-                let _h = position.height.resolve(OUTPUT_HEIGHT);
-
-                match effect {
-                    ShaderEffect::Blur { intensity } => {
-                        // Apply blur to current composition
-                        ctx.filters.push(format!(
-                            "[{}]boxblur={}:{}[{}]",
-                            current_label, intensity, intensity, next_label
-                        ));
-                    }
-                }
-                current_label = next_label;
-            }
+                    // Start with input (the main clip may already be cut to the timeline)
+                    // Fix: Force timestamp to start at 0 to avoid invisible first frame/sync issues
+                    // This is critical for overlays to work correctly from frame 0
+                    let input = source
+                        .is_none()
+                        .then(|| main_clip.next())
+                        .flatten()
+                        .map(|label| format!("[{}]", label))
+                        .unwrap_or_else(|| format!("[{}:v]", input_idx));
+                    let mut base_filter = if skip > 0.0 {
+                        format!(
+                            "{}trim=start={},setpts=PTS-STARTPTS,",
+                            input,
+                            format_seconds(skip)
+                        )
+                    } else {
+                        format!("{}setpts=PTS-STARTPTS,", input)
+                    };
 
-            PlanoObject::Image {
-                position, opacity, ..
-            } => {
-                if let Some(&input_idx) = input_mapping.get(&idx) {
-                    let w = position.width.resolve(OUTPUT_WIDTH);
-                    let h = position.height.resolve(OUTPUT_HEIGHT);
-                    let x = position.x.resolve(OUTPUT_WIDTH, w);
-                    let y = position.y.resolve(OUTPUT_HEIGHT, h);
-
-                    // Scale and apply opacity to image
-                    let mut img_filter = format!("[{}:v]scale={}:{}", input_idx, w, h);
-
-                    if *opacity < 1.0 {
-                        img_filter = format!(
-                            "{},format=rgba,colorchannelmixer=aa={}",
-                            img_filter, opacity
+                    if *reframe == ReframeMode::Auto {
+                        // Follow the subject with a window of the layer's aspect ratio
+                        // (the analysed path belongs to the main clip)
+                        let path = match source {
+                            None => options.reframe.clone().unwrap_or_default(),
+                            Some(_) => ReframePath::default(),
+                        };
+                        base_filter = format!(
+                            "{}{},",
+                            base_filter,
+                            path.crop_filter(w as f64 / h.max(1) as f64, options.still_at)
                         );
+                    } else if let Some(c) = crop {
+                        // Apply user crop first if specified
+                        if c.is_specified() {
+                            let x_from = c.x_from.unwrap_or(0);
+                            let x_to = c.x_to.unwrap_or(0);
+                            let y_from = c.y_from.unwrap_or(0);
+                            let y_to = c.y_to.unwrap_or(0);
+
+                            if x_to > x_from {
+                                let crop_w = x_to - x_from;
+                                let crop_x = x_from;
+                                base_filter =
+                                    format!("{}crop={}:ih:{}:0,", base_filter, crop_w, crop_x);
+                            }
+                            if y_to > y_from {
+                                let crop_h = y_to - y_from;
+                                let crop_y = y_from;
+                                base_filter =
+                                    format!("{}crop=iw:{}:{}:{},", base_filter, crop_h, 0, crop_y);
+                            }
+                        }
                     }
 
-                    ctx.filters.push(format!("{}[img{}]", img_filter, idx));
-                    ctx.filters.push(format!(
-                        "[{}][img{}]overlay={}:{}[{}]",
-                        current_label, idx, x, y, next_label
-                    ));
-
-                    current_label = next_label;
-                }
-            }
-
-            PlanoObject::Video {
-                position,
-                loop_video,
-                keep_last_frame,
-                opacity,
-                fit,
-                ..
-            } => {
-                if let Some(&input_idx) = input_mapping.get(&idx) {
-                    let w = position.width.resolve(OUTPUT_WIDTH);
-                    let h = position.height.resolve(OUTPUT_HEIGHT);
-                    let x = position.x.resolve(OUTPUT_WIDTH, w);
-                    let y = position.y.resolve(OUTPUT_HEIGHT, h);
-
-                    // Start with input
-                    // Fix: Force timestamp to start at 0
-                    let mut base_filter = format!("[{}:v]setpts=PTS-STARTPTS,", input_idx);
-
-                    if *loop_video {
-                        base_filter = format!("{}loop=loop=-1:size=32767:start=0,", base_filter);
-                    } else if *keep_last_frame {
-                        // tpad with infinite stop_mode=clone
-                        // stop_duration is in seconds. Use a large number.
-                        base_filter =
-                            format!("{}tpad=stop_mode=clone:stop_duration=99999,", base_filter);
+                    // Digital zoom: keep the centre of what is left
+                    if *zoom < 1.0 {
+                        let z = format_seconds(zoom.max(MIN_ZOOM));
+                        base_filter = format!("{}crop=iw*{}:ih*{},", base_filter, z, z);
                     }
 
-                    // Apply Fit scaling
+                    // Now apply scaling based on Fit mode
                     let scale_filter = match fit {
                         Fit::Cover => format!(
                             "{}scale={}:{}:force_original_aspect_ratio=increase,crop={}:{}",
                             base_filter, w, h, w, h
                         ),
                         Fit::Contain => format!(
-                            "{}scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:-1:-1:color=0x00000000",
+                            "{}scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2,setsar=1",
                             base_filter, w, h, w, h
                         ),
-                        Fit::Stretch => format!("{}scale={}:{}", base_filter, w, h),
+                        Fit::Stretch => format!(
+                            "{}scale={}:{},setsar=1",
+                            base_filter, w, h
+                        ),
                     };
+                    let scale_filter = format!("{}{}", scale_filter, effects_chain(effects, w, h));
+
+                    // Overlay on previous
+                    current_label =
+                        self.overlay_layer(&current_label, scale_filter, &placement, style, "tmp");
+                }
+
+                PlanoObject::Shader {
+                    effect,
+                    position,
+                    keyframes,
+                    ..
+                } => {
+                    // Keep the region inside the canvas
+                    let w = position.width.resolve(canvas_w).clamp(1, canvas_w);
+                    let h = position.height.resolve(canvas_h).clamp(1, canvas_h);
+                    let max_x = (canvas_w - w) as f64;
+                    let max_y = (canvas_h - h) as f64;
+
+                    let x_track =
+                        position_track(keyframes, |k| k.x.as_ref(), canvas_w, |_| w, options);
+                    let y_track =
+                        position_track(keyframes, |k| k.y.as_ref(), canvas_h, |_| h, options);
+                    let x = axis_value(&x_track, position.x.resolve(canvas_w, w), max_x, options);
+                    let y = axis_value(&y_track, position.y.resolve(canvas_h, h), max_y, options);
+
+                    let Some(effect_filter) = effect.to_filter(w, h) else {
+                        continue;
+                    };
+
+                    // Timed shaders fade the processed region over the original
+                    let mut fx_chain = effect_filter.clone();
+                    let mut enable = String::new();
+                    if options.still_at.is_some() {
+                        if visibility < 1.0 {
+                            fx_chain = format!(
+                                "{},format=rgba,colorchannelmixer=aa={}",
+                                fx_chain, visibility as f32
+                            );
+                        }
+                    } else {
+                        if let Some(fade) = timing.fade_filters(options.duration) {
+                            fx_chain = format!("{},{}", fx_chain, fade);
+                        }
+                        if let Some(expr) = timing.enable_expr(options.duration) {
+                            enable = format!(":enable='{}'", expr);
+                        }
+                    }
 
-                    let mut vid_filter = format!("{},setsar=1", scale_filter);
+                    let next_label = self.next_label("layer");
+                    if w == canvas_w
+                        && h == canvas_h
+                        && fx_chain == effect_filter
+                        && enable.is_empty()
+                    {
+                        // Apply to the whole composition
+                        self.filters.push(format!(
+                            "[{}]{}[{}]",
+                            current_label, effect_filter, next_label
+                        ));
+                    } else {
+                        // Cut the region out, apply the effect and paste it back
+                        let bg = self.next_label("shbg");
+                        let region = self.next_label("shrg");
+                        let fx = self.next_label("shfx");
+                        self.filters
+                            .push(format!("[{}]split[{}][{}]", current_label, bg, region));
+                        self.filters.push(format!(
+                            "[{}]crop={}:{}:{}:{},{}[{}]",
+                            region, w, h, x, y, fx_chain, fx
+                        ));
+                        self.filters.push(format!(
+                            "[{}][{}]overlay={}:{}{}[{}]",
+                            bg, fx, x, y, enable, next_label
+                        ));
+                    }
+                    current_label = next_label;
+                }
 
-                    // Add opacity if < 1.0
-                    if *opacity < 1.0 {
-                        vid_filter = format!(
-                            "{},format=rgba,colorchannelmixer=aa={}",
-                            vid_filter, opacity
+                PlanoObject::Image {
+                    path,
+                    position,
+                    opacity,
+                    loop_animation,
+                    fps,
+                    effects,
+                    keyframes,
+                    style,
+                    ..
+                } => {
+                    if image_exists(path) {
+                        let input_idx = self.add_input(path);
+                        let placement =
+                            Placement::resolve(position, keyframes, *opacity, timing, options);
+                        let (w, h) = (placement.w, placement.h);
+
+                        let frames = if is_animated_image(path) {
+                            animated_image_frames(*loop_animation, *fps, timing, options)
+                        } else if placement.is_animated() {
+                            // A still image is a single frame: repeat it so that
+                            // per-frame animations and fades have frames to work on
+                            "loop=loop=-1:size=1:start=0,".to_string()
+                        } else {
+                            String::new()
+                        };
+
+                        // Scale the image (opacity is applied by overlay_layer)
+                        let img_filter = format!(
+                            "[{}:v]{}scale={}:{}{}",
+                            input_idx,
+                            frames,
+                            w,
+                            h,
+                            effects_chain(effects, w, h)
+                        );
+
+                        current_label = self.overlay_layer(
+                            &current_label,
+                            img_filter,
+                            &placement,
+                            style,
+                            "img",
                         );
                     }
+                }
 
-                    ctx.filters.push(format!("{}[vid{}]", vid_filter, idx));
-                    ctx.filters.push(format!(
-                        "[{}][vid{}]overlay={}:{}[{}]",
-                        current_label, idx, x, y, next_label
-                    ));
+                PlanoObject::Video {
+                    path,
+                    position,
+                    loop_video,
+                    keep_last_frame,
+                    opacity,
+                    fit,
+                    effects,
+                    keyframes,
+                    style,
+                    ..
+                } => {
+                    if Path::new(path).exists() {
+                        let input_idx = self.add_input(path);
+                        let placement =
+                            Placement::resolve(position, keyframes, *opacity, timing, options);
+                        let (w, h) = (placement.w, placement.h);
+
+                        // Start with input
+                        // Fix: Force timestamp to start at 0
+                        let mut base_filter = format!("[{}:v]setpts=PTS-STARTPTS,", input_idx);
+
+                        if *loop_video {
+                            base_filter =
+                                format!("{}loop=loop=-1:size=32767:start=0,", base_filter);
+                        } else if *keep_last_frame {
+                            // tpad with infinite stop_mode=clone
+                            // stop_duration is in seconds. Use a large number.
+                            base_filter =
+                                format!("{}tpad=stop_mode=clone:stop_duration=99999,", base_filter);
+                        }
 
-                    current_label = next_label;
+                        // Apply Fit scaling
+                        let scale_filter = match fit {
+                            Fit::Cover => format!(
+                                "{}scale={}:{}:force_original_aspect_ratio=increase,crop={}:{}",
+                                base_filter, w, h, w, h
+                            ),
+                            Fit::Contain => format!(
+                                "{}scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:-1:-1:color=0x00000000",
+                                base_filter, w, h, w, h
+                            ),
+                            Fit::Stretch => format!("{}scale={}:{}", base_filter, w, h),
+                        };
+
+                        let vid_filter =
+                            format!("{},setsar=1{}", scale_filter, effects_chain(effects, w, h));
+
+                        current_label = self.overlay_layer(
+                            &current_label,
+                            vid_filter,
+                            &placement,
+                            style,
+                            "vid",
+                        );
+                    }
                 }
             }
         }
+
+        // Rename the last layer (also covers empty planos and skipped assets)
+        self.filters
+            .push(format!("[{}]null[{}]", current_label, out_label));
+    }
+
+    /// Finish a layer chain (animated size, opacity) and overlay it on `current_label`
+    /// Returns the label of the resulting composition
+    fn overlay_layer(
+        &mut self,
+        current_label: &str,
+        chain: String,
+        placement: &Placement,
+        style: &LayerStyle,
+        prefix: &str,
+    ) -> String {
+        let mut chain = self.apply_style(chain, placement.w, placement.h, style, prefix);
+        if let Some(ref scale) = placement.scale {
+            chain = format!("{},{}", chain, scale);
+        }
+        if let Some(ref alpha) = placement.opacity {
+            chain = format!("{},{}", chain, alpha);
+        }
+        if let Some(ref fade) = placement.fade {
+            chain = format!("{},{}", chain, fade);
+        }
+        let enable = placement
+            .enable
+            .as_ref()
+            .map(|e| format!(":enable='{}'", e))
+            .unwrap_or_default();
+
+        let mut current_label = current_label.to_string();
+        if let Some(ref shadow) = style.shadow {
+            current_label = self.overlay_shadow(&current_label, shadow, placement, style, &enable);
+        }
+
+        let layer = self.next_label(prefix);
+        let next_label = self.next_label("layer");
+        self.filters.push(format!("{}[{}]", chain, layer));
+        self.filters.push(format!(
+            "[{}][{}]overlay={}:{}{}[{}]",
+            current_label, layer, placement.x, placement.y, enable, next_label
+        ));
+        next_label
+    }
+
+    /// Single-frame colour source, for masks and plates
+    fn still_source(&mut self, color: &str, w: u32, h: u32, prefix: &str, filters: &str) -> String {
+        let label = self.next_label(prefix);
+        self.filters.push(format!(
+            "color=c={}:s={}x{}:r=1:d=1,{}[{}]",
+            color, w, h, filters, label
+        ));
+        label
+    }
+
+    /// Add border and rounded corners to a layer chain of `w`x`h`
+    fn apply_style(
+        &mut self,
+        chain: String,
+        w: u32,
+        h: u32,
+        style: &LayerStyle,
+        prefix: &str,
+    ) -> String {
+        if style.border.is_none() && style.corner_radius == 0 {
+            return chain;
+        }
+
+        let content = self.next_label(prefix);
+        self.filters.push(format!("{}[{}]", chain, content));
+        let mut chain = format!("[{}]null", content);
+
+        if let Some(ref border) = style.border {
+            let ring_mask = format!(
+                "format=gray,geq=lum='{}'",
+                rounded_rect_alpha(w, h, style.corner_radius, 0, Some(border.width))
+            );
+            let mask = self.still_source("black", w, h, "brdm", &ring_mask);
+            let plate = self.next_label("brdp");
+            self.filters.push(format!(
+                "color=c={}:s={}x{}:r=1:d=1,format=rgba[{}]",
+                border.color, w, h, plate
+            ));
+            let ring = self.next_label("brd");
+            self.filters.push(format!(
+                "[{}][{}]alphamerge,loop=loop=-1:size=1[{}]",
+                plate, mask, ring
+            ));
+            chain = format!("[{}][{}]overlay=0:0:format=auto", content, ring);
+        }
+
+        if style.corner_radius > 0 {
+            let mask_filters = format!(
+                "format=gray,geq=lum='{}',loop=loop=-1:size=1",
+                rounded_rect_alpha(w, h, style.corner_radius, 0, None)
+            );
+            let mask = self.still_source("black", w, h, "rndm", &mask_filters);
+            // Multiply with the layer's own alpha (chromakey, transparent PNGs)
+            // instead of replacing it
+            let rounded = self.next_label(prefix);
+            let source = self.next_label("rnds");
+            let alpha = self.next_label("rnda");
+            let cut = self.next_label("rndc");
+            self.filters.push(format!(
+                "{},format=rgba,split[{}][{}]",
+                chain, rounded, source
+            ));
+            self.filters
+                .push(format!("[{}]alphaextract[{}]", source, alpha));
+            self.filters.push(format!(
+                "[{}][{}]blend=all_mode=multiply[{}]",
+                alpha, mask, cut
+            ));
+            chain = format!("[{}][{}]alphamerge", rounded, cut);
+        }
+
+        chain
+    }
+
+    /// Overlay the blurred shadow of a layer, returning the new composition label
+    fn overlay_shadow(
+        &mut self,
+        current_label: &str,
+        shadow: &Shadow,
+        placement: &Placement,
+        style: &LayerStyle,
+        enable: &str,
+    ) -> String {
+        // Leave room around the shape so the blur is not cut off
+        let margin = shadow.blur * 2;
+        let (w, h) = (placement.w, placement.h);
+        let mask_filters = format!(
+            "format=gray,geq=lum='{}'",
+            rounded_rect_alpha(w, h, style.corner_radius, margin, None)
+        );
+        let (pw, ph) = (w + 2 * margin, h + 2 * margin);
+        let mask = self.still_source("black", pw, ph, "shdm", &mask_filters);
+        let plate = self.next_label("shdp");
+        self.filters.push(format!(
+            "color=c={}:s={}x{}:r=1:d=1,format=rgba[{}]",
+            shadow.color, pw, ph, plate
+        ));
+
+        let mut chain = format!("[{}][{}]alphamerge", plate, mask);
+        if shadow.blur > 0 {
+            chain = format!("{},gblur=sigma={}", chain, shadow.blur as f32 / 2.0);
+        }
+        chain = format!(
+            "{},colorchannelmixer=aa={},loop=loop=-1:size=1",
+            chain,
+            shadow.opacity.clamp(0.0, 1.0)
+        );
+        if let Some(ref fade) = placement.fade {
+            chain = format!("{},{}", chain, fade);
+        }
+
+        let layer = self.next_label("shd");
+        let next_label = self.next_label("layer");
+        self.filters.push(format!("{}[{}]", chain, layer));
+        self.filters.push(format!(
+            "[{}][{}]overlay={}:{}{}[{}]",
+            current_label,
+            layer,
+            shifted_coord(&placement.x, shadow.offset_x, margin),
+            shifted_coord(&placement.y, shadow.offset_y, margin),
+            enable,
+            next_label
+        ));
+        next_label
+    }
+
+    /// Compose a plano on the output timeline of the clip, ending in `[out_label]`
+    ///
+    /// With speed ramps or a length limit the main clip is cut before composing,
+    /// so layer timing ("50%", negative times, keyframes, fades) and the reframe
+    /// path follow the output rather than the source. `base` gives the reframe
+    /// path (in clip time) and canvas scale; its duration is replaced by the
+    /// output length. Returns the audio label (None if the clip is silent) and
+    /// the output length.
+    fn compose_timeline(
+        &mut self,
+        plano: &[PlanoObject],
+        out_label: &str,
+        timeline: &Timeline,
+        clip_duration: f64,
+        clip_has_audio: bool,
+        base: &RenderOptions,
+    ) -> (Option<String>, f64) {
+        if timeline.is_identity(clip_duration) {
+            let options = RenderOptions {
+                duration: clip_duration,
+                ..base.clone()
+            };
+            self.compose(plano, out_label, &options);
+            return (clip_has_audio.then(|| "0:a".to_string()), clip_duration);
+        }
+
+        let prefix = self.next_label("tl");
+        let (filters, video, audio) =
+            timeline.filters("0:v", clip_has_audio.then_some("0:a"), &prefix);
+        self.filters.extend(filters);
+
+        // A label is read once: one copy per clip layer showing the main clip
+        let uses = plano
+            .iter()
+            .filter(|obj| matches!(obj, PlanoObject::Clip { source: None, .. }))
+            .count();
+        let main_clip = match uses {
+            0 => {
+                self.filters.push(format!("[{}]nullsink", video));
+                Vec::new()
+            }
+            1 => vec![video],
+            n => {
+                let copies: Vec<String> = (0..n).map(|i| format!("{}c{}", prefix, i)).collect();
+                self.filters.push(format!(
+                    "[{}]split={}{}",
+                    video,
+                    n,
+                    copies
+                        .iter()
+                        .map(|c| format!("[{}]", c))
+                        .collect::<String>()
+                ));
+                copies
+            }
+        };
+
+        let length = timeline.output_duration();
+        let options = RenderOptions {
+            duration: length,
+            reframe: base
+                .reframe
+                .as_ref()
+                .map(|path| path.retime(|time| timeline.output_time(time))),
+            main_clip,
+            ..base.clone()
+        };
+        self.compose(plano, out_label, &options);
+        (audio, length)
+    }
+
+    /// End the endless canvas of `[label]` after `length` seconds
+    fn trim_output(&mut self, label: &str, length: f64) -> String {
+        let trimmed = self.next_label("tl");
+        self.filters.push(format!(
+            "[{}]trim=duration={},setpts=PTS-STARTPTS[{}]",
+            label,
+            format_seconds(length),
+            trimmed
+        ));
+        trimmed
+    }
+
+    /// Join intro/outro bumpers around the main part (see `compose_timeline`)
+    /// Every part is normalised to the canvas size, 60 fps and stereo 48 kHz audio
+    /// Returns the joined video and audio labels and the total length
+    fn join_bumpers(
+        &mut self,
+        main: (String, Option<String>, f64),
+        intro: Option<&BumperInput>,
+        outro: Option<&BumperInput>,
+        canvas: (u32, u32),
+    ) -> (String, String, f64) {
+        const VIDEO_NORM: &str = "setsar=1,fps=60,format=yuv420p";
+        const AUDIO_NORM: &str = "aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo";
+
+        let (video, audio, duration) = main;
+        let main_v = self.next_label("mainv");
+        self.filters
+            .push(format!("[{}]{}[{}]", video, VIDEO_NORM, main_v));
+        let main_a = self.audio_segment(audio.as_deref(), duration, AUDIO_NORM);
+        let mut joined = (main_v, main_a, duration);
+
+        let bumper_segment = |ctx: &mut Self, input: &BumperInput| {
+            let idx = ctx.add_input(&input.bumper.path);
+            let v = ctx.next_label("bumpv");
+            ctx.filters.push(format!(
+                "[{}:v]setpts=PTS-STARTPTS,scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:color=black,{}[{}]",
+                idx,
+                VIDEO_NORM,
+                v,
+                w = canvas.0,
+                h = canvas.1
+            ));
+            let audio = format!("{}:a", idx);
+            let a = ctx.audio_segment(
+                input.has_audio.then_some(audio.as_str()),
+                input.duration,
+                AUDIO_NORM,
+            );
+            (v, a, input.duration)
+        };
+
+        if let Some(input) = intro {
+            let segment = bumper_segment(self, input);
+            joined = self.join_segments(segment, joined, input.bumper.crossfade);
+        }
+        if let Some(input) = outro {
+            let segment = bumper_segment(self, input);
+            joined = self.join_segments(joined, segment, input.bumper.crossfade);
+        }
+        joined
+    }
+
+    /// Audio of one part: the `[source]` audio, or silence of the same length
+    fn audio_segment(&mut self, source: Option<&str>, duration: f64, norm: &str) -> String {
+        let label = self.next_label("aud");
+        match source {
+            Some(source) => self.filters.push(format!(
+                "[{}]atrim=duration={},asetpts=PTS-STARTPTS,{}[{}]",
+                source,
+                format_seconds(duration),
+                norm,
+                label
+            )),
+            None => self.filters.push(format!(
+                "anullsrc=r=48000:cl=stereo,atrim=duration={},{}[{}]",
+                format_seconds(duration),
+                norm,
+                label
+            )),
+        }
+        label
+    }
+
+    /// Play `second` after `first`, crossfading both video and audio when `crossfade` > 0
+    fn join_segments(
+        &mut self,
+        first: (String, String, f64),
+        second: (String, String, f64),
+        crossfade: f64,
+    ) -> (String, String, f64) {
+        let (v1, a1, len1) = first;
+        let (v2, a2, len2) = second;
+        // A crossfade cannot be longer than half of either part
+        let crossfade = crossfade.min(len1 / 2.0).min(len2 / 2.0).max(0.0);
+
+        let v = self.next_label("joinv");
+        let a = self.next_label("joina");
+        if crossfade > 0.0 {
+            self.filters.push(format!(
+                "[{}][{}]xfade=transition=fade:duration={}:offset={}[{}]",
+                v1,
+                v2,
+                format_seconds(crossfade),
+                format_seconds(len1 - crossfade),
+                v
+            ));
+            self.filters.push(format!(
+                "[{}][{}]acrossfade=d={}[{}]",
+                a1,
+                a2,
+                format_seconds(crossfade),
+                a
+            ));
+            (v, a, len1 + len2 - crossfade)
+        } else {
+            self.filters.push(format!(
+                "[{}][{}][{}][{}]concat=n=2:v=1:a=1[{}][{}]",
+                v1, a1, v2, a2, v, a
+            ));
+            (v, a, len1 + len2)
+        }
+    }
+
+    /// Consume the context, returning (filter_string, input_files)
+    fn finish(self) -> (String, Vec<String>) {
+        (self.filters.join(";"), self.inputs)
+    }
+}
+
+/// How a plano is rendered: the whole clip (animated) or a single still frame
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Clip duration in seconds, resolves "%" and negative times
+    pub duration: f64,
+    /// Render the state at this time as a still image (previews)
+    pub still_at: Option<f64>,
+    /// Subject path for clip layers with `reframe: "auto"`
+    pub reframe: Option<ReframePath>,
+    /// Render on a canvas this many times smaller than 1080x1920, with the
+    /// pixel values of the layers scaled to match (quick previews)
+    pub scale: u32,
+    /// Labels of the main clip already cut to the output timeline, one per
+    /// clip layer reading it (input 0 is used when empty)
+    main_clip: Vec<String>,
+}
+
+impl RenderOptions {
+    /// Render the full clip with animations compiled to expressions
+    pub fn animated(duration: f64) -> Self {
+        Self {
+            duration,
+            still_at: None,
+            reframe: None,
+            scale: 1,
+            main_clip: Vec::new(),
+        }
+    }
+
+    /// Render a still frame showing the plano at `time`
+    pub fn still(duration: f64, time: f64) -> Self {
+        Self {
+            duration,
+            still_at: Some(time),
+            reframe: None,
+            scale: 1,
+            main_clip: Vec::new(),
+        }
+    }
+
+    /// Use this subject path for auto-reframed clip layers
+    pub fn with_reframe(mut self, reframe: Option<ReframePath>) -> Self {
+        self.reframe = reframe;
+        self
+    }
+
+    /// Render on a canvas `scale` times smaller
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Size of the canvas layers are composed on
+    fn canvas(&self) -> (u32, u32) {
+        let scale = self.scale.max(1);
+        (OUTPUT_WIDTH / scale, OUTPUT_HEIGHT / scale)
+    }
+}
+
+/// Where and how a layer is drawn, possibly changing over time
+struct Placement {
+    /// Size the layer is fitted to
+    w: u32,
+    h: u32,
+    /// Extra scale filter for animated sizes
+    scale: Option<String>,
+    /// Overlay coordinates (numbers or quoted expressions)
+    x: String,
+    y: String,
+    /// Opacity filter, if the layer is not fully opaque
+    opacity: Option<String>,
+    /// Alpha fades from the layer timing
+    fade: Option<String>,
+    /// Overlay `enable` expression from the layer timing
+    enable: Option<String>,
+}
+
+impl Placement {
+    fn resolve(
+        position: &Position,
+        keyframes: &[Keyframe],
+        base_opacity: f32,
+        timing: &LayerTiming,
+        options: &RenderOptions,
+    ) -> Self {
+        let (canvas_w, canvas_h) = options.canvas();
+        let w0 = position.width.resolve(canvas_w);
+        let h0 = position.height.resolve(canvas_h);
+
+        let w_track = size_track(keyframes, |k| k.width.as_ref(), canvas_w, options);
+        let h_track = size_track(keyframes, |k| k.height.as_ref(), canvas_h, options);
+        let width_at = |t: f64| track_or(&w_track, t, w0);
+        let height_at = |t: f64| track_or(&h_track, t, h0);
+
+        let x_track = position_track(keyframes, |k| k.x.as_ref(), canvas_w, width_at, options);
+        let y_track = position_track(keyframes, |k| k.y.as_ref(), canvas_h, height_at, options);
+        let o_track = Track::new(
+            keyframes
+                .iter()
+                .filter_map(|k| {
+                    k.opacity
+                        .map(|o| (k.time.resolve(options.duration), o as f64, k.easing))
+                })
+                .collect(),
+        );
+
+        if let Some(time) = options.still_at {
+            // Previews: evaluate everything at the requested time
+            let w = width_at(time);
+            let h = height_at(time);
+            let x = if x_track.is_empty() {
+                position.x.resolve(canvas_w, w)
+            } else {
+                x_track.value_at(time).round() as i32
+            };
+            let y = if y_track.is_empty() {
+                position.y.resolve(canvas_h, h)
+            } else {
+                y_track.value_at(time).round() as i32
+            };
+            let opacity = if o_track.is_empty() {
+                base_opacity as f64
+            } else {
+                o_track.value_at(time)
+            };
+            let visibility = timing.visibility_at(time, options.duration);
+            return Self {
+                w,
+                h,
+                scale: None,
+                x: x.to_string(),
+                y: y.to_string(),
+                opacity: static_opacity(opacity * visibility),
+                fade: None,
+                enable: None,
+            };
+        }
+
+        let size_animated = w_track.is_animated() || h_track.is_animated();
+        // Fit at the largest size and scale down, so zooms stay sharp
+        let w = if w_track.is_empty() {
+            w0
+        } else {
+            track_max(&w_track)
+        };
+        let h = if h_track.is_empty() {
+            h0
+        } else {
+            track_max(&h_track)
+        };
+        let scale = if size_animated {
+            Some(format!(
+                "scale=w='{}':h='{}':eval=frame",
+                size_expr(&w_track, w0),
+                size_expr(&h_track, h0)
+            ))
+        } else {
+            None
+        };
+
+        let x = overlay_coord(&x_track, &position.x, canvas_w, w, size_animated, "W-w");
+        let y = overlay_coord(&y_track, &position.y, canvas_h, h, size_animated, "H-h");
+
+        let opacity = if o_track.is_animated() {
+            Some(format!(
+                "format=rgba,geq=r='r(X,Y)':g='g(X,Y)':b='b(X,Y)':a='alpha(X,Y)*clip({},0,1)'",
+                o_track.to_expr("T")
+            ))
+        } else if o_track.is_empty() {
+            static_opacity(base_opacity as f64)
+        } else {
+            static_opacity(o_track.value_at(0.0))
+        };
+
+        Self {
+            w,
+            h,
+            scale,
+            x,
+            y,
+            opacity,
+            fade: timing.fade_filters(options.duration),
+            enable: timing.enable_expr(options.duration),
+        }
+    }
+
+    /// Whether the layer content changes from frame to frame
+    fn is_animated(&self) -> bool {
+        self.scale.is_some()
+            || self.fade.is_some()
+            || self.opacity.as_ref().is_some_and(|o| o.contains("geq"))
+    }
+}
+
+/// Opacity filter for a constant opacity (None when fully opaque)
+fn static_opacity(opacity: f64) -> Option<String> {
+    if opacity < 1.0 {
+        Some(format!(
+            "format=rgba,colorchannelmixer=aa={}",
+            opacity.max(0.0) as f32
+        ))
+    } else {
+        None
+    }
+}
+
+/// Keyframe track for a size property, in pixels
+fn size_track(
+    keyframes: &[Keyframe],
+    pick: impl Fn(&Keyframe) -> Option<&SizeValue>,
+    reference: u32,
+    options: &RenderOptions,
+) -> Track {
+    Track::new(
+        keyframes
+            .iter()
+            .filter_map(|k| {
+                pick(k).map(|v| {
+                    (
+                        k.time.resolve(options.duration),
+                        v.resolve(reference) as f64,
+                        k.easing,
+                    )
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Keyframe track for a position property, in pixels
+/// `size_at` gives the element size at a time (needed by "center")
+fn position_track(
+    keyframes: &[Keyframe],
+    pick: impl Fn(&Keyframe) -> Option<&PositionValue>,
+    container: u32,
+    size_at: impl Fn(f64) -> u32,
+    options: &RenderOptions,
+) -> Track {
+    Track::new(
+        keyframes
+            .iter()
+            .filter_map(|k| {
+                pick(k).map(|v| {
+                    let time = k.time.resolve(options.duration);
+                    (time, v.resolve(container, size_at(time)) as f64, k.easing)
+                })
+            })
+            .collect(),
+    )
+}
+
+fn track_or(track: &Track, time: f64, fallback: u32) -> u32 {
+    if track.is_empty() {
+        fallback
+    } else {
+        track.value_at(time).round().max(1.0) as u32
+    }
+}
+
+fn track_max(track: &Track) -> u32 {
+    track.max_value().round().max(1.0) as u32
+}
+
+fn size_expr(track: &Track, fallback: u32) -> String {
+    if track.is_empty() {
+        fallback.to_string()
+    } else {
+        track.to_expr("t")
+    }
+}
+
+/// Overlay coordinate: static number, quoted keyframe expression, or a
+/// centring expression when the size changes over time
+fn overlay_coord(
+    track: &Track,
+    value: &PositionValue,
+    container: u32,
+    size: u32,
+    size_animated: bool,
+    centre_span: &str,
+) -> String {
+    if track.is_animated() {
+        format!("'{}'", track.to_expr("t"))
+    } else if !track.is_empty() {
+        (track.value_at(0.0).round() as i32).to_string()
+    } else if size_animated && value.is_center() {
+        format!("({})/2", centre_span)
+    } else {
+        value.resolve(container, size).to_string()
+    }
+}
+
+/// Whether an image path exists (numbered sequences need their first frame)
+pub(crate) fn image_exists(path: &str) -> bool {
+    if is_image_sequence(path) {
+        // image2 looks for the first frame among numbers 0-4
+        (0..=4).any(|n| Path::new(&sequence_frame_path(path, n)).exists())
+    } else {
+        Path::new(path).exists()
+    }
+}
+
+/// Whether the path is a numbered sequence pattern like "frames/%03d.png"
+fn is_image_sequence(path: &str) -> bool {
+    sequence_placeholder(path).is_some()
+}
+
+/// Position and width of the "%d" / "%0Nd" placeholder
+fn sequence_placeholder(path: &str) -> Option<(usize, usize, usize)> {
+    let start = path.find('%')?;
+    let rest = &path[start + 1..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if !rest[digits.len()..].starts_with('d') {
+        return None;
+    }
+    let width = digits.parse().unwrap_or(0);
+    Some((start, start + 1 + digits.len() + 1, width))
+}
+
+/// Path of frame `n` in a numbered sequence
+fn sequence_frame_path(path: &str, n: u32) -> String {
+    match sequence_placeholder(path) {
+        Some((start, end, width)) => {
+            format!(
+                "{}{:0width$}{}",
+                &path[..start],
+                n,
+                &path[end..],
+                width = width
+            )
+        }
+        None => path.to_string(),
+    }
+}
+
+/// Whether an image file holds more than one frame
+fn is_animated_image(path: &str) -> bool {
+    if is_image_sequence(path) {
+        return true;
+    }
+    let ext = Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "gif" | "apng" => true,
+        // Animated PNG and WebP files share the extension with stills:
+        // look for the animation chunk near the start of the file
+        "png" => file_head_contains(path, b"acTL"),
+        "webp" => file_head_contains(path, b"ANIM"),
+        _ => false,
+    }
+}
+
+fn file_head_contains(path: &str, needle: &[u8]) -> bool {
+    use std::io::Read;
+    let mut head = Vec::new();
+    match fs::File::open(path) {
+        Ok(file) => {
+            if file.take(4096).read_to_end(&mut head).is_err() {
+                return false;
+            }
+        }
+        Err(_) => return false,
     }
+    head.windows(needle.len()).any(|w| w == needle)
+}
 
-    if plano.is_empty() {
-        ctx.filters.push("[base]null[out]".to_string());
+/// Filters that play an animated image: retime, loop and start with the layer
+fn animated_image_frames(
+    loop_animation: bool,
+    fps: Option<f64>,
+    timing: &LayerTiming,
+    options: &RenderOptions,
+) -> String {
+    let mut filters = String::from("setpts=PTS-STARTPTS,");
+    if let Some(fps) = fps.filter(|f| *f > 0.0) {
+        filters.push_str(&format!("setpts=N/({}*TB),", format_seconds(fps)));
+    }
+    if loop_animation {
+        filters.push_str("loop=loop=-1:size=32767:start=0,");
+    } else if options.still_at.is_some() {
+        // Hold the last frame, as the overlay does when exporting
+        filters.push_str("tpad=stop_mode=clone:stop_duration=99999,");
+    }
+    match options.still_at {
+        // Previews show the frame at the preview time, at least one second in
+        // (animations often start empty)
+        Some(time) => filters.push_str(&format!(
+            "trim=start={},setpts=PTS-STARTPTS,",
+            format_seconds(time.max(1.0))
+        )),
+        None => {
+            // Play from the beginning when the layer appears
+            let (start, _) = timing.window(options.duration);
+            if start > 0.0 {
+                filters.push_str(&format!("setpts=PTS+{}/TB,", format_seconds(start)));
+            }
+        }
     }
+    // Keep transparency (GIF palettes included) through scaling
+    filters.push_str("format=rgba,");
+    filters
+}
 
-    let filter_str = ctx.filters.join(";");
+/// Overlay coordinate of a shadow plate: the layer position moved by `offset`,
+/// minus the blur `margin` (centring expressions already account for the size)
+fn shifted_coord(coord: &str, offset: i32, margin: u32) -> String {
+    if let Ok(v) = coord.parse::<i32>() {
+        return (v + offset - margin as i32).to_string();
+    }
+    if coord.starts_with('(') {
+        return format!("'{}{:+}'", coord, offset);
+    }
+    let expr = coord.trim_matches('\'');
+    format!("'{}{:+}'", expr, offset - margin as i32)
+}
 
-    (filter_str, inputs)
+/// Shader region coordinate, clamped to the canvas (static or expression)
+fn axis_value(track: &Track, fallback: i32, max: f64, options: &RenderOptions) -> String {
+    if let Some(time) = options.still_at {
+        if !track.is_empty() {
+            return (track.value_at(time).clamp(0.0, max).round() as i32).to_string();
+        }
+    } else if track.is_animated() {
+        return format!("'clip({},0,{})'", track.to_expr("t"), max);
+    } else if !track.is_empty() {
+        return (track.value_at(0.0).clamp(0.0, max).round() as i32).to_string();
+    }
+    (fallback as f64).clamp(0.0, max).round().to_string()
+}
+
+/// Build FFmpeg filter_complex string from a plano
+/// Returns (filter_string, input_files_needed)
+pub fn build_ffmpeg_filter(
+    plano: &[PlanoObject],
+    clip_path: &str,
+    options: &RenderOptions,
+) -> (String, Vec<String>) {
+    let mut ctx = FilterContext::new(clip_path);
+    ctx.compose(plano, "out", options);
+    ctx.finish()
+}
+
+/// Whether any plano has an auto-reframed clip layer, and whether one of them
+/// asks for the path to be saved
+fn reframe_requirements(planos: &[&[PlanoObject]]) -> (bool, bool) {
+    let mut needs = false;
+    let mut save = false;
+    for obj in planos.iter().flat_map(|p| p.iter()) {
+        if let PlanoObject::Clip {
+            reframe: ReframeMode::Auto,
+            save_reframe,
+            ..
+        } = obj
+        {
+            needs = true;
+            save |= *save_reframe;
+        }
+    }
+    (needs, save)
 }
 
 // ============================================================================
@@ -638,12 +2743,13 @@ pub fn generate_preview(
     source_image: &str,
     plano: &[PlanoObject],
     output_path: &str,
+    options: &RenderOptions,
 ) -> Result<()> {
     if !Path::new(source_image).exists() {
         return Err(anyhow!("Source image not found: {}", source_image));
     }
 
-    let (filter, inputs) = build_ffmpeg_filter(plano, source_image);
+    let (filter, inputs) = build_ffmpeg_filter(plano, source_image, options);
     debug!("Preview Source: {}", source_image);
     debug!("Preview Filter: {}", filter);
 
@@ -693,13 +2799,20 @@ pub fn generate_preview(
 pub fn generate_preview_embedded(plano: &[PlanoObject], output_path: &str) -> Result<()> {
     // Write embedded image to temp file (FFmpeg needs a file path)
     let temp_dir = std::env::temp_dir();
-    let temp_image_path = temp_dir.join("yt_shortmaker_example.png");
+    let temp_image_path = temp_dir.join(format!("yt_shortmaker_example_{}.png", unique_stamp()));
 
     fs::write(&temp_image_path, EXAMPLE_IMAGE_DATA)
         .context("Failed to write embedded image to temp file")?;
 
     // Use the temp file for preview generation
-    let result = generate_preview(temp_image_path.to_str().unwrap_or(""), plano, output_path);
+    // Animations are shown as they look on the first frame of a nominal clip
+    let options = RenderOptions::still(PREVIEW_NOMINAL_DURATION, 0.0);
+    let result = generate_preview(
+        temp_image_path.to_str().unwrap_or(""),
+        plano,
+        output_path,
+        &options,
+    );
 
     // Clean up temp file (ignore errors)
     let _ = fs::remove_file(&temp_image_path);
@@ -707,22 +2820,50 @@ pub fn generate_preview_embedded(plano: &[PlanoObject], output_path: &str) -> Re
     result
 }
 
+/// Name part that keeps the files of renders running at the same time apart
+/// (process id, milliseconds and a counter for renders within the same one)
+pub fn unique_stamp() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}_{}_{}",
+        std::process::id(),
+        Local::now().format("%Y%m%d%H%M%S%3f"),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
 /// Generate a preview using a frame extracted from a video file
 pub fn generate_preview_from_video(
     video_path: &str,
     plano: &[PlanoObject],
     output_path: &str,
+) -> Result<()> {
+    // Try to get the frame at 5 seconds
+    generate_preview_from_video_at(video_path, plano, output_path, 5.0)
+}
+
+/// Generate a preview using the frame of a video at `time` seconds
+/// Falls back to the first frame when the video is shorter
+pub fn generate_preview_from_video_at(
+    video_path: &str,
+    plano: &[PlanoObject],
+    output_path: &str,
+    time: f64,
 ) -> Result<()> {
     // 1. Extract a frame from the video to a temp file
     let temp_dir = std::env::temp_dir();
-    let temp_frame_path = temp_dir.join("yt_shortmaker_frame.png");
+    let temp_frame_path = temp_dir.join(format!("yt_shortmaker_frame_{}.png", unique_stamp()));
 
     info!("Extracting preview frame from: {}", video_path);
 
+    let duration =
+        crate::video::get_video_duration_precise(video_path).unwrap_or(PREVIEW_NOMINAL_DURATION);
+    let mut frame_time = time.max(0.0);
+
     let status = Command::new("ffmpeg")
         .args([
             "-ss",
-            "00:00:05", // Try to get frame at 5 seconds
+            &format_seconds(frame_time),
             "-i",
             video_path,
             "-frames:v",
@@ -737,6 +2878,7 @@ pub fn generate_preview_from_video(
 
     if !status.success() {
         // Fallback: try at 0 seconds if video is short
+        frame_time = 0.0;
         let _ = Command::new("ffmpeg")
             .args([
                 "-i",
@@ -756,7 +2898,24 @@ pub fn generate_preview_from_video(
     }
 
     // 2. Use the extracted frame for preview generation
-    let result = generate_preview(temp_frame_path.to_str().unwrap_or(""), plano, output_path);
+    // Render the plano as it looks at the moment the frame was taken
+    // Without a sidecar only the seconds around the frame are analysed
+    let reframe = if reframe_requirements(&[plano]).0 {
+        crate::reframe::load_sidecar(video_path).or_else(|| {
+            crate::reframe::analyze_around_blocking(video_path, frame_time)
+                .map_err(|e| log::warn!("Reframe analysis failed, using centre crop: {}", e))
+                .ok()
+        })
+    } else {
+        None
+    };
+    let options = RenderOptions::still(duration, frame_time).with_reframe(reframe);
+    let result = generate_preview(
+        temp_frame_path.to_str().unwrap_or(""),
+        plano,
+        output_path,
+        &options,
+    );
 
     // 3. Clean up temp frame
     let _ = fs::remove_file(&temp_frame_path);
@@ -764,18 +2923,172 @@ pub fn generate_preview_from_video(
     result
 }
 
+/// Render the first `seconds` of a short as a small, quickly encoded video
+/// The whole clip is composed (so timing, speed ramps, the reframe path and
+/// the clip's variables match the real short) and the output is cut short.
+/// Without a video the embedded example image is used as the clip
+pub async fn generate_preview_clip(
+    video_path: Option<&str>,
+    loaded: &LoadedPlano,
+    output_path: &str,
+    seconds: f64,
+    cancellation_token: Arc<AtomicBool>,
+) -> Result<()> {
+    let temp_dir = std::env::temp_dir();
+    let stamp = unique_stamp();
+
+    // 1. Turn the example image into a clip when there is no video
+    let mut example_clip = None;
+    let clip = match video_path {
+        Some(video) => video.to_string(),
+        None => {
+            let image_path = temp_dir.join(format!("yt_shortmaker_example_{}.png", stamp));
+            let source_path = temp_dir.join(format!("yt_shortmaker_example_{}.mp4", stamp));
+            fs::write(&image_path, EXAMPLE_IMAGE_DATA)
+                .context("Failed to write embedded image to temp file")?;
+            let args = example_clip_args(
+                &image_path.to_string_lossy(),
+                seconds,
+                &source_path.to_string_lossy(),
+            );
+            let mut command = tokio::process::Command::new("ffmpeg");
+            command.args(&args);
+            let output =
+                crate::video::run_command_with_cancellation(command, cancellation_token.clone())
+                    .await
+                    .context("Failed to execute ffmpeg for preview clip");
+            let _ = fs::remove_file(&image_path);
+            let output = output?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow!("Failed to create preview source: {}", stderr));
+            }
+            let source = source_path.to_string_lossy().to_string();
+            example_clip = Some(source_path);
+            source
+        }
+    };
+
+    // 2. Export it like a short, on a small canvas and with the fastest preset
+    let vars = crate::variables::load_clip_vars(&clip);
+    let clip_plano = plano_for_clip(loaded, &vars).unwrap_or_else(|e| {
+        log::warn!("Using {} as is for the preview: {:#}", loaded.path, e);
+        None
+    });
+    let plano = clip_plano.as_ref().unwrap_or(&loaded.plano);
+    let profile = ExportProfile {
+        preset: "ultrafast".to_string(),
+        ..Default::default()
+    };
+    let target = ExportTarget {
+        layers: &plano.layers,
+        profile: &profile,
+        output_path: output_path.to_string(),
+        intro: None,
+        outro: None,
+        output_size: None,
+        scale: PREVIEW_CLIP_SCALE,
+        limit: Some(seconds),
+    };
+    let result = export_clip(&clip, &[target], cancellation_token, None)
+        .await
+        .map(|_| ());
+
+    // 3. Clean up the temp source
+    if let Some(path) = example_clip {
+        let _ = fs::remove_file(path);
+    }
+
+    if result.is_ok() {
+        info!("Preview clip generated at: {}", output_path);
+    }
+    result
+}
+
+/// FFmpeg arguments turning a still image into a clip of `seconds`
+fn example_clip_args(image: &str, seconds: f64, output: &str) -> Vec<String> {
+    [
+        "-loop",
+        "1",
+        "-i",
+        image,
+        "-t",
+        &format_seconds(seconds),
+        // yuv420p needs even dimensions
+        "-vf",
+        "scale=trunc(iw/2)*2:trunc(ih/2)*2",
+        "-pix_fmt",
+        "yuv420p",
+        "-y",
+        output,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
 // ============================================================================
 // Export Functions
 // ============================================================================
 
-/// Export a single clip using the plano template
+/// One plano to render from a clip, with its encoder settings and output file
+pub struct ExportTarget<'a> {
+    pub layers: &'a [PlanoObject],
+    pub profile: &'a ExportProfile,
+    pub output_path: String,
+    pub intro: Option<&'a Bumper>,
+    pub outro: Option<&'a Bumper>,
+    /// Resize the 1080x1920 canvas to this resolution (None = keep it)
+    pub output_size: Option<(u32, u32)>,
+    /// Compose on a canvas this many times smaller (1 = full size)
+    pub scale: u32,
+    /// Stop the output after this many seconds (None = the whole short)
+    pub limit: Option<f64>,
+}
+
+/// A bumper file with its probed properties
+struct BumperInput<'a> {
+    bumper: &'a Bumper,
+    duration: f64,
+    has_audio: bool,
+}
+
+impl<'a> BumperInput<'a> {
+    /// Probe a bumper; missing or unreadable files are skipped with a warning
+    fn probe(bumper: Option<&'a Bumper>, log_callback: Option<&ExportLogCallback>) -> Option<Self> {
+        let bumper = bumper?;
+        let duration = if Path::new(&bumper.path).exists() {
+            crate::video::get_video_duration_precise(&bumper.path).ok()
+        } else {
+            None
+        };
+        match duration {
+            Some(duration) => Some(Self {
+                bumper,
+                duration,
+                has_audio: crate::video::has_audio_stream(&bumper.path),
+            }),
+            None => {
+                let msg = format!("Skipping intro/outro, cannot read: {}", bumper.path);
+                if let Some(cb) = log_callback {
+                    cb(ExportLogLevel::Warning, msg.clone());
+                }
+                log::warn!("{}", msg);
+                None
+            }
+        }
+    }
+}
+
+/// Export a single clip using one or more plano templates
+/// All targets are rendered by one ffmpeg process, so the clip is decoded once.
+/// Returns the files written (targets over a `fail` length limit are skipped)
 pub async fn export_clip(
     clip_path: &str,
-    plano: &[PlanoObject],
-    output_path: &str,
+    targets: &[ExportTarget<'_>],
     cancellation_token: Arc<AtomicBool>,
     log_callback: Option<&ExportLogCallback>,
-) -> Result<()> {
+) -> Result<Vec<String>> {
     // Check cancellation immediately at start
     if cancellation_token.load(Ordering::Relaxed) {
         return Err(anyhow!("process cancelled by user"));
@@ -790,98 +3103,229 @@ pub async fn export_clip(
         return Err(anyhow!("Clip not found: {}", clip_path));
     }
 
-    let msg = format!("Exporting clip: {} -> {}", clip_path, output_path);
-    if let Some(cb) = log_callback {
-        cb(ExportLogLevel::Info, msg.clone());
+    for target in targets {
+        let msg = format!("Exporting clip: {} -> {}", clip_path, target.output_path);
+        if let Some(cb) = log_callback {
+            cb(ExportLogLevel::Info, msg.clone());
+        }
+        info!("{}", msg);
     }
-    info!("{}", msg);
-
-    let (filter, inputs) = build_ffmpeg_filter(plano, clip_path);
-    debug!("Export Filter: {}", filter);
 
-    // Build FFmpeg command
-    let mut args: Vec<String> = Vec::new();
+    // Limit output duration to the length of the main clip, or of its
+    // timeline (which also resolves keyframe times)
+    // This prevents infinite loops if background video is looping
+    // CRITICAL: We MUST have a duration, otherwise the 10h black canvas will make the video 10h long
 
-    // Add all inputs
-    for input in &inputs {
-        args.push("-i".to_string());
-        args.push(input.clone());
+    // Check cancellation before blocking ffprobe call
+    if cancellation_token.load(Ordering::Relaxed) {
+        return Err(anyhow!("process cancelled by user"));
     }
 
-    // Add filter
-    args.push("-filter_complex".to_string());
-    args.push(filter);
+    let duration = crate::video::get_video_duration_precise(clip_path).context(
+        "Failed to determine clip duration. Cannot safely export without known duration.",
+    )?;
 
-    // Map output
-    args.push("-map".to_string());
-    args.push("[out]".to_string());
-    args.push("-map".to_string());
-    args.push("0:a?".to_string()); // Audio from main clip (optional)
+    let msg = format!("Detected clip duration: {:.3}s", duration);
+    if let Some(cb) = log_callback {
+        cb(ExportLogLevel::Info, msg.clone());
+    }
+    info!("{}", msg);
 
-    // Output settings
-    args.push("-c:v".to_string());
-    args.push("libx264".to_string());
-    args.push("-preset".to_string());
-    args.push("superfast".to_string());
+    // Speed ramps and the length policy of each profile; targets over a
+    // `fail` limit are skipped
+    let ramps = crate::timeline::load_ramps(clip_path);
+    let mut active: Vec<(&ExportTarget, Timeline)> = Vec::new();
+    let mut first_error = None;
+    for target in targets {
+        match Timeline::build(duration, &ramps, &target.profile.length) {
+            Ok(timeline) => {
+                if !timeline.is_identity(duration) {
+                    let msg = format!(
+                        "Output length for {}: {:.3}s",
+                        target.output_path,
+                        timeline.output_duration()
+                    );
+                    if let Some(cb) = log_callback {
+                        cb(ExportLogLevel::Info, msg.clone());
+                    }
+                    info!("{}", msg);
+                }
+                active.push((target, timeline));
+            }
+            Err(e) => {
+                let msg = format!("Skipping {}: {}", target.output_path, e);
+                if let Some(cb) = log_callback {
+                    cb(ExportLogLevel::Error, msg.clone());
+                }
+                error!("{}", msg);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if active.is_empty() {
+        return Err(first_error.unwrap_or_else(|| anyhow!("Nothing to export")));
+    }
 
-    args.push("-c:a".to_string());
-    args.push("aac".to_string());
+    let layer_sets: Vec<&[PlanoObject]> = active.iter().map(|(t, _)| t.layers).collect();
 
-    args.push("-b:a".to_string());
-    args.push("192k".to_string());
+    // Subject path for auto-reframed clip layers (edited sidecar wins)
+    let (needs_reframe, save_reframe) = reframe_requirements(&layer_sets);
+    let reframe = if !needs_reframe {
+        None
+    } else if let Some(path) = crate::reframe::load_sidecar(clip_path) {
+        Some(path)
+    } else {
+        let msg = format!("Analysing subject position: {}", clip_path);
+        if let Some(cb) = log_callback {
+            cb(ExportLogLevel::Info, msg.clone());
+        }
+        info!("{}", msg);
 
-    // 4. Limit output duration to the length of the main clip
-    // This prevents infinite loops if background video is looping
-    // CRITICAL: We MUST have a duration, otherwise the 10h black canvas will make the video 10h long
+        match crate::reframe::analyze(clip_path, cancellation_token.clone()).await {
+            Ok(path) => {
+                if save_reframe {
+                    if let Err(e) = crate::reframe::save_sidecar(clip_path, &path) {
+                        log::warn!("{}", e);
+                    }
+                }
+                Some(path)
+            }
+            Err(e) => {
+                if cancellation_token.load(Ordering::Relaxed) {
+                    return Err(e);
+                }
+                // Fall back to a centred crop rather than failing the export
+                let msg = format!("Reframe analysis failed, using centre crop: {}", e);
+                if let Some(cb) = log_callback {
+                    cb(ExportLogLevel::Warning, msg.clone());
+                }
+                log::warn!("{}", msg);
+                None
+            }
+        }
+    };
 
-    // Check cancellation before blocking ffprobe call
-    if cancellation_token.load(Ordering::Relaxed) {
-        return Err(anyhow!("process cancelled by user"));
+    // Compose each plano on its timeline and join intro/outro bumpers:
+    // (video label, audio label, output length) per target
+    let clip_has_audio = crate::video::has_audio_stream(clip_path);
+    let mut ctx = FilterContext::new(clip_path);
+    let mut outputs: Vec<(String, Option<String>, f64)> = Vec::new();
+    for (i, (target, timeline)) in active.iter().enumerate() {
+        let out = format!("out{}", i);
+        let options = RenderOptions::animated(duration)
+            .with_reframe(reframe.clone())
+            .with_scale(target.scale);
+        let (audio, length) = ctx.compose_timeline(
+            target.layers,
+            &out,
+            timeline,
+            duration,
+            clip_has_audio,
+            &options,
+        );
+
+        let intro = BumperInput::probe(target.intro, log_callback);
+        let outro = BumperInput::probe(target.outro, log_callback);
+        let bare = intro.is_none() && outro.is_none();
+        if bare && timeline.is_identity(duration) {
+            // Audio from main clip (optional)
+            outputs.push((format!("[{}]", out), Some("0:a?".to_string()), duration));
+            continue;
+        }
+
+        let video = ctx.trim_output(&out, length);
+        if bare {
+            outputs.push((
+                format!("[{}]", video),
+                audio.map(|a| format!("[{}]", a)),
+                length,
+            ));
+        } else {
+            let (v, a, total) = ctx.join_bumpers(
+                (video, audio, length),
+                intro.as_ref(),
+                outro.as_ref(),
+                options.canvas(),
+            );
+            outputs.push((format!("[{}]", v), Some(format!("[{}]", a)), total));
+        }
     }
 
-    let duration = crate::video::get_video_duration_precise(clip_path).context(
-        "Failed to determine clip duration. Cannot safely export without known duration.",
-    )?;
+    let (filter, inputs) = ctx.finish();
+    debug!("Export Filter: {}", filter);
 
-    let msg = format!("Detected clip duration: {:.3}s", duration);
-    if let Some(cb) = log_callback {
-        cb(ExportLogLevel::Info, msg.clone());
+    // Build FFmpeg command
+    let mut args: Vec<String> = Vec::new();
+
+    // Add all inputs
+    for input in &inputs {
+        args.push("-i".to_string());
+        args.push(input.clone());
     }
-    info!("{}", msg);
 
-    args.push("-t".to_string());
-    args.push(format!("{:.3}", duration));
+    // Add filter
+    args.push("-filter_complex".to_string());
+    args.push(filter);
 
-    args.push("-y".to_string());
-    args.push(output_path.to_string());
+    // Map each composed plano to its own output
+    let encode_targets: Vec<crate::encoding::EncodeTarget> = active
+        .iter()
+        .zip(&outputs)
+        .map(|((target, _), (video, audio, length))| {
+            let length = target.limit.map_or(*length, |limit| length.min(limit));
+            let mut args = vec!["-map".to_string(), video.clone()];
+            if let Some(audio) = audio {
+                args.push("-map".to_string());
+                args.push(audio.clone());
+            }
+            args.push("-t".to_string());
+            args.push(format!("{:.3}", length));
+            if let Some((width, height)) = target.output_size {
+                args.push("-s".to_string());
+                args.push(format!("{}x{}", width, height));
+            }
+            crate::encoding::EncodeTarget {
+                args,
+                profile: target.profile,
+                path: target.output_path.clone(),
+                duration: Some(length),
+            }
+        })
+        .collect();
 
-    let mut command = tokio::process::Command::new("ffmpeg");
     let cmd_str = format!("FFmpeg command args: {:?}", args);
     // Only log command debug if callback exists (to avoid spamming main log if not debug)
     if let Some(cb) = log_callback {
         cb(ExportLogLevel::Info, cmd_str.clone());
     }
-    command.args(&args);
-
-    // Run with cancellation support
-    let output = crate::video::run_command_with_cancellation(command, cancellation_token).await?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let msg = format!("FFmpeg export failed for {}: {}", clip_path, stderr);
+    // Run with cancellation support (two runs if any target is two-pass)
+    if let Err(e) =
+        crate::encoding::run_encode_targets(&args, &encode_targets, duration, cancellation_token)
+            .await
+    {
+        if e.to_string().contains("cancelled") {
+            return Err(e);
+        }
+        let msg = format!("FFmpeg export failed for {}: {}", clip_path, e);
         if let Some(cb) = log_callback {
             cb(ExportLogLevel::Error, msg.clone());
         }
         error!("{}", msg);
-        return Err(anyhow!("FFmpeg export failed: {}", stderr));
+        return Err(anyhow!("FFmpeg export failed: {}", e));
     }
 
-    let msg = format!("Successfully exported: {}", output_path);
-    if let Some(cb) = log_callback {
-        cb(ExportLogLevel::Success, msg.clone());
+    for (target, _) in &active {
+        let msg = format!("Successfully exported: {}", target.output_path);
+        if let Some(cb) = log_callback {
+            cb(ExportLogLevel::Success, msg.clone());
+        }
+        info!("{}", msg);
     }
-    info!("{}", msg);
-    Ok(())
+    Ok(active
+        .into_iter()
+        .map(|(target, _)| target.output_path.clone())
+        .collect())
 }
 
 /// Log level for export operations
@@ -889,7 +3333,6 @@ pub async fn export_clip(
 pub enum ExportLogLevel {
     Info,
     Success,
-    #[allow(dead_code)]
     Warning,
     Error,
 }
@@ -900,10 +3343,13 @@ pub type ExportLogCallback = Box<dyn Fn(ExportLogLevel, String) + Send + Sync>;
 /// Progress callback type for batch exports
 pub type ExportProgressCallback = Box<dyn Fn(usize, usize, &str) + Send + Sync>;
 
-/// Export all clips from multiple directories using a plano template
+/// Export all clips from multiple directories using one or more plano templates
+/// With several planos, each one writes into its own subfolder of `output_dir`.
+/// `default_profile` is used for planos that do not define their own
 pub async fn export_batch(
     clip_dirs: &[String],
-    plano: &[PlanoObject],
+    planos: &[LoadedPlano],
+    default_profile: &ExportProfile,
     output_dir: &str,
     progress_callback: Option<ExportProgressCallback>,
     log_callback: Option<ExportLogCallback>,
@@ -922,8 +3368,18 @@ pub async fn export_batch(
         }
     };
 
-    // Ensure output directory exists
-    fs::create_dir_all(output_dir)?;
+    // Ensure output directories exist (one subfolder per plano when exporting several)
+    let plano_dirs: Vec<String> = if planos.len() > 1 {
+        output_folder_names(planos)
+            .into_iter()
+            .map(|name| format!("{}/{}", output_dir, name))
+            .collect()
+    } else {
+        vec![output_dir.to_string()]
+    };
+    for dir in &plano_dirs {
+        fs::create_dir_all(dir)?;
+    }
     log(
         ExportLogLevel::Info,
         format!("Starting batch export to: {}", output_dir),
@@ -967,7 +3423,41 @@ pub async fn export_batch(
         // Fix: Use timestamp instead of original filename to prevent overwrites
         // Format: short_YYYYMMDD_HHMMSS_mmm.mp4
         let timestamp = Local::now().format("%Y%m%d_%H%M%S_%3f");
-        let output_path = format!("{}/short_{}.mp4", output_dir, timestamp);
+
+        // Planos specific to this clip (category mapping or variables)
+        let vars = crate::variables::load_clip_vars(&clip_path.to_string_lossy());
+        let clip_planos: Vec<Option<Plano>> = planos
+            .iter()
+            .map(|loaded| match plano_for_clip(loaded, &vars) {
+                Ok(plano) => plano,
+                Err(e) => {
+                    log(
+                        ExportLogLevel::Warning,
+                        format!("Using {} as is for {}: {:#}", loaded.path, file_name, e),
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        let targets: Vec<ExportTarget> = planos
+            .iter()
+            .zip(&clip_planos)
+            .zip(&plano_dirs)
+            .map(|((loaded, clip_plano), dir)| {
+                let plano = clip_plano.as_ref().unwrap_or(&loaded.plano);
+                ExportTarget {
+                    layers: &plano.layers,
+                    profile: plano.profile_or(default_profile),
+                    output_path: format!("{}/short_{}.mp4", dir, timestamp),
+                    intro: plano.intro.as_ref(),
+                    outro: plano.outro.as_ref(),
+                    output_size: None,
+                    scale: 1,
+                    limit: None,
+                }
+            })
+            .collect();
 
         if let Some(ref callback) = progress_callback {
             callback(i + 1, total, &file_name);
@@ -975,16 +3465,13 @@ pub async fn export_batch(
 
         match export_clip(
             clip_path.to_str().unwrap(),
-            plano,
-            &output_path,
+            &targets,
             cancellation_token.clone(),
             log_callback.as_ref(), // Pass log callback
         )
         .await
         {
-            Ok(_) => {
-                output_files.push(output_path);
-            }
+            Ok(written) => output_files.extend(written),
             Err(e) => {
                 if e.to_string().contains("cancelled") {
                     return Err(e);
@@ -999,6 +3486,35 @@ pub async fn export_batch(
     Ok(output_files)
 }
 
+/// Plano to use for a clip instead of `loaded.plano`, if any: the one mapped to
+/// the clip's category, or the same plano filled with the clip's variables
+fn plano_for_clip(loaded: &LoadedPlano, vars: &PlanoVars) -> Result<Option<Plano>> {
+    let mapped = crate::variables::category(vars).and_then(|c| loaded.plano.category_plano(c));
+    if let Some(path) = mapped {
+        return load_plano_with(path, vars).map(Some);
+    }
+    if loaded.plano.variables && !vars.is_empty() {
+        return load_plano_with(&loaded.path, vars).map(Some);
+    }
+    Ok(None)
+}
+
+/// Output subfolder name for each plano (file stem, made unique)
+fn output_folder_names(planos: &[LoadedPlano]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for loaded in planos {
+        let base = loaded.name();
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.contains(&name) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -1068,10 +3584,10 @@ mod tests {
         ]"#;
         let plano: Vec<PlanoObject> = serde_json::from_str(json).unwrap();
         match &plano[0] {
-            PlanoObject::Shader { effect, .. } => {
-                let ShaderEffect::Blur { intensity } = effect;
-                assert_eq!(*intensity, 25);
-            }
+            PlanoObject::Shader { effect, .. } => match effect {
+                ShaderEffect::Blur { intensity } => assert_eq!(*intensity, 25),
+                _ => panic!("Expected Blur"),
+            },
             _ => panic!("Expected Shader"),
         }
     }
@@ -1094,6 +3610,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_plano_document_with_profile() {
+        let json = r#"{
+            // Document form with encoder settings
+            "profile": {"codec": "x265", "max_file_size_mb": 50},
+            "layers": [
+                {"type": "clip", "position": {"x": 0, "y": 0, "width": "full", "height": "full"}}
+            ]
+        }"#;
+        let plano = parse_plano(json).unwrap();
+        assert_eq!(plano.layers.len(), 1);
+        let profile = plano.profile.as_ref().unwrap();
+        assert_eq!(profile.codec, crate::encoding::VideoCodec::X265);
+        assert_eq!(profile.max_file_size_mb, Some(50.0));
+
+        // Bare arrays still load, with no profile of their own
+        let legacy = parse_plano(r#"[{"type": "clip", "position": {}}]"#).unwrap();
+        assert!(legacy.profile.is_none());
+        let fallback = ExportProfile::default();
+        assert_eq!(legacy.profile_or(&fallback).preset, "superfast");
+    }
+
     #[test]
     fn test_remove_js_comments() {
         let input = r#"[
@@ -1124,11 +3662,712 @@ mod tests {
     #[test]
     fn test_build_ffmpeg_filter_basic() {
         let plano = create_default_plano();
-        let (filter, inputs) = build_ffmpeg_filter(&plano, "test.mp4");
+        let (filter, inputs) =
+            build_ffmpeg_filter(&plano, "test.mp4", &RenderOptions::animated(10.0));
         assert!(filter.contains("[out]"));
         assert_eq!(inputs.len(), 1);
     }
 
+    #[test]
+    fn test_compose_multi_shares_inputs() {
+        let plano = create_default_plano();
+        let timeline = Timeline::build(10.0, &[], &Default::default()).unwrap();
+        let mut ctx = FilterContext::new("test.mp4");
+        for out in ["out0", "out1"] {
+            ctx.compose_timeline(
+                &plano,
+                out,
+                &timeline,
+                10.0,
+                true,
+                &RenderOptions::animated(10.0),
+            );
+        }
+        let (filter, inputs) = ctx.finish();
+        assert!(filter.contains("[out0]"));
+        assert!(filter.contains("[out1]"));
+        assert!(!filter.contains("[out]"));
+        // The clip is decoded once and feeds both compositions
+        assert_eq!(inputs, vec!["test.mp4".to_string()]);
+    }
+
+    #[test]
+    fn test_output_folder_names_are_unique() {
+        let loaded = |path: &str| LoadedPlano {
+            path: path.to_string(),
+            plano: Plano::default(),
+        };
+        let planos = vec![
+            loaded("/a/tiktok.json"),
+            loaded("/b/tiktok.json"),
+            loaded("/a/shorts.json"),
+        ];
+        assert_eq!(
+            output_folder_names(&planos),
+            vec!["tiktok", "tiktok_2", "shorts"]
+        );
+    }
+
+    #[test]
+    fn test_shader_region_is_respected() {
+        let json = r#"[
+            {"type": "clip", "position": {}},
+            {
+                "type": "shader",
+                "effect": {"type": "pixelate", "block_size": 20},
+                "position": {"x": 100, "y": 1500, "width": 400, "height": 200}
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains("split"));
+        assert!(filter.contains("crop=400:200:100:1500,scale=20:10:flags=neighbor"));
+        assert!(filter.contains("overlay=100:1500"));
+
+        // A full-canvas shader is applied directly, without split/crop
+        let plano = create_default_plano();
+        let (filter, _) = build_ffmpeg_filter(&plano, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(!filter.contains("split"));
+        assert!(filter.contains("boxblur=20:20"));
+    }
+
+    #[test]
+    fn test_parse_shader_effects() {
+        let json = r#"[
+            {"type": "shader", "effect": {"type": "vignette"}, "position": {}},
+            {"type": "shader", "effect": {"type": "eq", "saturation": 1.4}, "position": {}},
+            {"type": "shader", "effect": {"type": "sharpen"}, "position": {}},
+            {"type": "shader", "effect": {"type": "grayscale"}, "position": {}},
+            {"type": "shader", "effect": {"type": "lut", "path": "missing.cube"}, "position": {}}
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains("vignette=angle="));
+        assert!(filter.contains("eq=brightness=0:contrast=1:saturation=1.4"));
+        assert!(filter.contains("unsharp=5:5:1:5:5:0"));
+        assert!(filter.contains("hue=s=0"));
+        // Missing LUT files are skipped instead of breaking the export
+        assert!(!filter.contains("lut3d"));
+    }
+
+    #[test]
+    fn test_layer_chromakey_effect() {
+        let json = r#"[
+            {
+                "type": "clip",
+                "position": {"x": 0, "y": 0, "width": 540, "height": 540},
+                "effects": [{"type": "chromakey", "similarity": 0.2}]
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains("setsar=1,format=rgba,colorkey=0x00FF00:0.2:0.05"));
+    }
+
+    #[test]
+    fn test_keyframes_compile_to_expressions() {
+        let json = r#"[
+            {
+                "type": "clip",
+                "position": {"x": 0, "y": "center", "width": 540, "height": 540},
+                "keyframes": [
+                    {"time": 0, "x": 0, "width": 540, "opacity": 0},
+                    {"time": "50%", "x": 540, "width": 1080, "opacity": 1, "easing": "ease_out"}
+                ]
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(4.0));
+        // Fitted at the largest size, then scaled per frame
+        assert!(filter.contains("scale=1080:540,setsar=1"));
+        assert!(filter.contains("scale=w='lt(t,0)*(540)+gte(t,0)*lt(t,2)*(540+(540)*"));
+        assert!(filter.contains(":h='540':eval=frame"));
+        assert!(filter.contains("geq=r='r(X,Y)'"));
+        assert!(filter.contains("a='alpha(X,Y)*clip(lt(T,0)*(0)+gte(T,0)*lt(T,2)*("));
+        assert!(filter.contains("overlay='lt(t,0)*(0)+gte(t,0)*lt(t,2)*(0+(540)*"));
+        assert!(filter.contains(":(H-h)/2["));
+    }
+
+    #[test]
+    fn test_keyframes_still_render() {
+        let json = r#"[
+            {
+                "type": "image",
+                "path": "Cargo.toml",
+                "position": {"x": 0, "y": 0, "width": 100, "height": 100},
+                "keyframes": [
+                    {"time": 0, "x": 0, "opacity": 0},
+                    {"time": 2, "x": 200, "opacity": 1}
+                ]
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.png", &RenderOptions::still(10.0, 1.0));
+        assert!(filter.contains("colorchannelmixer=aa=0.5"));
+        assert!(filter.contains("overlay=100:0"));
+        assert!(!filter.contains("eval=frame"));
+        assert!(!filter.contains("geq"));
+    }
+
+    #[test]
+    fn test_layer_timing_enable_and_fades() {
+        let json = r#"[
+            {"type": "clip", "position": {}},
+            {
+                "type": "image",
+                "path": "Cargo.toml",
+                "position": {"x": 0, "y": 0, "width": 300, "height": 100},
+                "start": -3,
+                "fade_in": 0.5
+            },
+            {
+                "type": "shader",
+                "effect": {"type": "grayscale"},
+                "position": {},
+                "end": "10%"
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(20.0));
+        assert!(filter.contains("loop=loop=-1:size=1:start=0,scale=300:100"));
+        assert!(filter.contains("format=rgba,fade=t=in:st=17:d=0.5:alpha=1"));
+        assert!(filter.contains("overlay=0:0:enable='gte(t,17)'"));
+        // A timed full-canvas shader goes through the region path
+        assert!(filter.contains("split"));
+        assert!(filter.contains(":enable='between(t,0,2)'"));
+
+        // Timing fields sit next to the other layer fields on disk
+        let saved = serde_json::to_string(&plano.layers).unwrap();
+        assert!(saved.contains("\"start\":-3.0"));
+        assert!(!saved.contains("fade_out"));
+        assert_eq!(parse_plano(&saved).unwrap().layers.len(), 3);
+    }
+
+    #[test]
+    fn test_layer_timing_follows_output_timeline() {
+        let json = r#"[
+            {"type": "clip", "position": {}, "reframe": "auto"},
+            {"type": "clip", "position": {"x": 0, "y": 0, "width": 300, "height": 300}},
+            {
+                "type": "image",
+                "path": "Cargo.toml",
+                "position": {"x": 0, "y": 0, "width": 300, "height": 100},
+                "start": -3
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let policy = crate::timeline::DurationPolicy {
+            max_duration: Some(60.0),
+            over_limit: crate::timeline::OverLimit::Speed,
+            max_speed: None,
+        };
+        // 90s played at 1.5x
+        let timeline = Timeline::build(90.0, &[], &policy).unwrap();
+        let path = ReframePath {
+            points: vec![
+                crate::reframe::ReframePoint {
+                    time: 0.0,
+                    x: 0.25,
+                    y: 0.5,
+                },
+                crate::reframe::ReframePoint {
+                    time: 45.0,
+                    x: 0.75,
+                    y: 0.5,
+                },
+            ],
+        };
+
+        let mut ctx = FilterContext::new("test.mp4");
+        let (audio, length) = ctx.compose_timeline(
+            &plano.layers,
+            "out0",
+            &timeline,
+            90.0,
+            true,
+            &RenderOptions::animated(90.0).with_reframe(Some(path)),
+        );
+        let (filter, _) = ctx.finish();
+        assert_eq!(length, 60.0);
+        assert_eq!(audio.as_deref(), Some("tl0a0"));
+
+        // The clip is cut first and shared by both clip layers
+        assert!(filter.contains("[0:v]trim=duration=90,setpts=(PTS-STARTPTS)/1.5[tl0v0]"));
+        assert!(filter.contains("[tl0v0]split=2[tl0c0][tl0c1]"));
+        assert!(filter.contains("[tl0c1]setpts=PTS-STARTPTS,"));
+        assert!(!filter.contains("[0:v]setpts"));
+        // "-3" is three seconds before the end of the output, the path is retimed
+        assert!(filter.contains("enable='gte(t,57)'"));
+        assert!(filter.contains("+gte(t,30)*(0.75)"));
+    }
+
+    #[test]
+    fn test_layer_timing_in_previews() {
+        let timing = LayerTiming {
+            start: Some(TimeValue::Seconds(2.0)),
+            end: Some(TimeValue::Seconds(-2.0)),
+            fade_in: 1.0,
+            fade_out: 0.0,
+        };
+        assert_eq!(timing.visibility_at(1.0, 10.0), 0.0);
+        assert_eq!(timing.visibility_at(2.5, 10.0), 0.5);
+        assert_eq!(timing.visibility_at(5.0, 10.0), 1.0);
+        assert_eq!(timing.visibility_at(9.0, 10.0), 0.0);
+
+        // Hidden layers are left out of still previews
+        let json = r#"[
+            {"type": "clip", "position": {}, "end": 1}
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.png", &RenderOptions::still(10.0, 5.0));
+        assert!(!filter.contains("[0:v]"));
+    }
+
+    #[test]
+    fn test_clip_reframe_auto() {
+        let json = r#"[
+            {
+                "type": "clip",
+                "position": {"x": 0, "y": 0, "width": 1080, "height": 1920},
+                "crop": {"x_from": 100, "x_to": 500},
+                "reframe": "auto"
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        assert_eq!(reframe_requirements(&[&plano.layers]), (true, false));
+
+        // Without a path the window is centred; `crop` is ignored
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains("setpts=PTS-STARTPTS,crop=w='min(iw,ih*0.5625)'"));
+        assert!(!filter.contains("crop=400:ih:100:0"));
+
+        let path = ReframePath {
+            points: vec![crate::reframe::ReframePoint {
+                time: 0.0,
+                x: 0.3,
+                y: 0.5,
+            }],
+        };
+        let options = RenderOptions::animated(10.0).with_reframe(Some(path));
+        let (filter, _) = build_ffmpeg_filter(&plano.layers, "test.mp4", &options);
+        assert!(filter.contains("x='clip((0.3)*iw-ow/2,0,iw-ow)'"));
+    }
+
+    #[test]
+    fn test_clip_source_and_offset() {
+        let json = r#"[
+            {"type": "clip", "position": {}},
+            {"type": "clip", "offset": 12.5, "position": {"width": 540}},
+            {"type": "clip", "source": "Cargo.toml", "offset": 3, "position": {"width": 540}},
+            {"type": "clip", "source": "missing.mp4", "position": {}}
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, inputs) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains("[0:v]trim=start=12.5,setpts=PTS-STARTPTS,"));
+        assert!(filter.contains("[1:v]trim=start=3,setpts=PTS-STARTPTS,"));
+        // Missing sources are skipped
+        assert_eq!(
+            inputs,
+            vec!["test.mp4".to_string(), "Cargo.toml".to_string()]
+        );
+
+        // Previews cannot offset the preview frame, but can seek other sources
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.png", &RenderOptions::still(10.0, 2.0));
+        assert!(!filter.contains("[0:v]trim"));
+        assert!(filter.contains("[1:v]trim=start=5,"));
+    }
+
+    #[test]
+    fn test_clip_zoom() {
+        let json = r#"[
+            {"type": "clip", "zoom": 0.7, "fit": "cover", "position": {"width": 1080, "height": 1400}},
+            {"type": "clip", "zoom": 0.01, "position": {}}
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(filter.contains(
+            "setpts=PTS-STARTPTS,crop=iw*0.7:ih*0.7,scale=1080:1400:force_original_aspect_ratio=increase"
+        ));
+        // Clamped to MIN_ZOOM
+        assert!(filter.contains("crop=iw*0.1:ih*0.1,"));
+        // The default is not written back
+        let saved = serde_json::to_value(&plano.layers[0]).unwrap();
+        assert_eq!(saved["zoom"], 0.7);
+        let saved = serde_json::to_value(&create_default_plano()[0]).unwrap();
+        assert!(saved.get("zoom").is_none());
+    }
+
+    #[test]
+    fn test_layout_planos() {
+        for layout in PlanoLayout::ALL {
+            let plano = create_layout_plano(layout);
+            assert!(!plano.is_empty());
+            let (filter, inputs) =
+                build_ffmpeg_filter(&plano, "test.mp4", &RenderOptions::animated(10.0));
+            assert!(filter.contains("[out]"));
+            assert_eq!(inputs.len(), 1);
+            // Presets are valid and their comments are translated
+            assert!(crate::validation::validate_plano(&Plano::from(plano.clone())).is_empty());
+            assert!(!serde_json::to_string(&plano)
+                .unwrap()
+                .contains("plano_comment_"));
+        }
+
+        // The letterbox title bar shows a title image when there is one
+        let letterbox = create_layout_plano(PlanoLayout::Letterbox);
+        assert!(letterbox.iter().any(|layer| matches!(
+            layer,
+            PlanoObject::Image { path, optional: true, .. } if path == "{{title_image|title.png}}"
+        )));
+    }
+
+    #[test]
+    fn test_plano_extends_and_include() {
+        let dir = std::env::temp_dir().join(format!("ytsm_compose_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        let write = |name: &str, json: &str| fs::write(dir.join(name), json).unwrap();
+
+        write(
+            "shared/base.json",
+            r#"{
+                "profile": { "crf": 20 },
+                "layers": [
+                    { "type": "clip", "id": "background", "position": {} },
+                    { "type": "clip", "id": "main", "position": {}, "zoom": 0.8 }
+                ]
+            }"#,
+        );
+        write(
+            "shared/watermark.json",
+            r#"[ { "type": "image", "id": "logo", "path": "logo.png", "position": {} } ]"#,
+        );
+        write(
+            "gaming.json",
+            r#"{
+                "extends": "shared/base.json",
+                "include": ["shared/watermark.json"],
+                "layers": [
+                    { "type": "clip", "id": "main", "position": {} },
+                    { "type": "shader", "effect": { "type": "grayscale" }, "position": {} }
+                ]
+            }"#,
+        );
+
+        let plano = load_plano(&dir.join("gaming.json").to_string_lossy()).unwrap();
+        let ids: Vec<Option<&str>> = plano.layers.iter().map(|l| l.id()).collect();
+        assert_eq!(
+            ids,
+            vec![Some("background"), Some("main"), None, Some("logo")]
+        );
+        // The override replaced the base layer, settings come from the base
+        assert!(matches!(plano.layers[1], PlanoObject::Clip { zoom, .. } if zoom == 1.0));
+        assert_eq!(plano.profile.as_ref().unwrap().crf, Some(20));
+        assert!(plano.extends.is_none() && plano.include.is_empty());
+        // Fragment assets are resolved against the fragment's own folder
+        match &plano.layers[3] {
+            PlanoObject::Image { path, .. } => {
+                assert!(Path::new(path).ends_with("shared/logo.png"))
+            }
+            other => panic!("unexpected layer {:?}", other),
+        }
+
+        write(
+            "shared/base.json",
+            r#"{ "extends": "../gaming.json", "layers": [] }"#,
+        );
+        let err = load_plano(&dir.join("gaming.json").to_string_lossy()).unwrap_err();
+        assert!(format!("{:#}", err).contains("itself"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_plano_for_clip() {
+        let dir = std::env::temp_dir().join(format!("ytsm_clip_plano_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("main.json"),
+            r#"{
+                "categories": { "funny": "meme.json" },
+                "layers": [ { "type": "clip", "position": {},
+                              "crop": { "x_from": "{{crop_x|0}}" }, "comment": "{{title}}" } ]
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("meme.json"),
+            r#"[ { "type": "clip", "position": {} } ]"#,
+        )
+        .unwrap();
+
+        let loaded = LoadedPlano::load(&dir.join("main.json").to_string_lossy()).unwrap();
+        assert!(loaded.plano.variables);
+        let crop_x = |plano: &Plano| match &plano.layers[0] {
+            PlanoObject::Clip { crop, .. } => crop.as_ref().and_then(|c| c.x_from),
+            _ => None,
+        };
+        assert_eq!(crop_x(&loaded.plano), Some(0));
+
+        // Without variables the loaded plano is used as is
+        assert!(plano_for_clip(&loaded, &PlanoVars::new())
+            .unwrap()
+            .is_none());
+
+        let vars: PlanoVars =
+            serde_json::from_str(r#"{ "crop_x": 480, "category": "Gaming" }"#).unwrap();
+        let plano = plano_for_clip(&loaded, &vars).unwrap().unwrap();
+        assert_eq!(crop_x(&plano), Some(480));
+
+        let vars: PlanoVars = serde_json::from_str(r#"{ "category": "Funny" }"#).unwrap();
+        let plano = plano_for_clip(&loaded, &vars).unwrap().unwrap();
+        assert!(!plano.variables && crop_x(&plano).is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_load_plano_without_variables() {
+        let dir = std::env::temp_dir().join(format!("ytsm_unset_vars_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("crop.json");
+        fs::write(
+            &path,
+            r#"[ { "type": "clip", "position": { "x": "{{crop_x}}", "width": "{{width}}" },
+                   "crop": { "x_from": "{{crop_x}}" }, "fit": "{{fit}}", "comment": "{{title}}" } ]"#,
+        )
+        .unwrap();
+
+        // Listing, previews and `plano validate` load planos with no clip variables
+        let plano = load_plano(&path.to_string_lossy()).unwrap();
+        assert!(plano.variables);
+        match &plano.layers[0] {
+            PlanoObject::Clip {
+                position,
+                crop,
+                fit,
+                comment,
+                ..
+            } => {
+                assert_eq!(position.x.resolve(OUTPUT_WIDTH, 100), 0);
+                assert!(crop.as_ref().is_some_and(|c| c.x_from.is_none()));
+                assert!(matches!(fit, Fit::Stretch));
+                assert!(comment.is_none());
+            }
+            other => panic!("unexpected layer {:?}", other),
+        }
+
+        let fields: Vec<&str> = plano.unset.iter().map(|u| u.field.as_str()).collect();
+        assert!(fields.contains(&"layers[0].position.x"));
+        assert!(fields.contains(&"layers[0].crop.x_from"));
+        let issues = crate::validation::validate_plano(&plano);
+        assert!(issues.iter().any(|i| i.field == "layers[0].crop.x_from"
+            && i.message == "variable `crop_x` has no value and no default"));
+
+        let vars: PlanoVars = serde_json::from_str(r#"{ "crop_x": 480 }"#).unwrap();
+        let plano = load_plano_with(&path.to_string_lossy(), &vars).unwrap();
+        match &plano.layers[0] {
+            PlanoObject::Clip { crop, .. } => {
+                assert_eq!(crop.as_ref().and_then(|c| c.x_from), Some(480))
+            }
+            other => panic!("unexpected layer {:?}", other),
+        }
+        assert!(plano.unset.iter().all(|u| u.name != "crop_x"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_example_clip_args() {
+        let args = example_clip_args("example.png", 7.5, "/tmp/source.mp4");
+        assert_eq!(&args[..2], ["-loop", "1"]);
+        assert!(args.join(" ").contains("-t 7.5 -vf"));
+        assert!(args.contains(&"yuv420p".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("/tmp/source.mp4"));
+    }
+
+    #[test]
+    fn test_scaled_canvas() {
+        let plano = parse_plano(
+            r#"[
+                {"type": "clip", "position": {"x": 0, "y": 400, "width": "full", "height": 1000},
+                 "corner_radius": 40, "border": {"width": 8},
+                 "keyframes": [{"time": 0, "x": 100}, {"time": 2, "x": 0}]},
+                {"type": "shader", "effect": {"type": "blur", "intensity": 20},
+                 "position": {"x": 0, "y": 0, "width": "full", "height": 400}}
+            ]"#,
+        )
+        .unwrap();
+        let options = RenderOptions::animated(10.0).with_scale(4);
+        assert_eq!(options.canvas(), (270, 480));
+
+        let mut ctx = FilterContext::new("clip.mp4");
+        ctx.compose(&plano.layers, "out", &options);
+        let (filter, _) = ctx.finish();
+        assert!(filter.starts_with("color=c=black:s=270x480:"));
+        assert!(filter.contains("scale=270:250"));
+        assert!(filter.contains(",0)-10);255*"));
+        assert!(filter.contains("ld(2)+2+0.5"));
+        assert!(filter.contains("boxblur=5:5"));
+        assert!(filter.contains("crop=270:100:0:0"));
+        assert!(filter.contains("*(25+(-25)*"));
+        assert!(!filter.contains("1080"));
+    }
+
+    #[test]
+    fn test_layer_style_border_radius_shadow() {
+        let json = r#"[
+            {
+                "type": "clip",
+                "position": {"x": 40, "y": 300, "width": 1000, "height": 800},
+                "corner_radius": 32,
+                "border": {"width": 8, "color": "0xFFCC00"},
+                "shadow": {"blur": 10}
+            }
+        ]"#;
+        let plano = parse_plano(json).unwrap();
+        let (filter, _) =
+            build_ffmpeg_filter(&plano.layers, "test.mp4", &RenderOptions::animated(10.0));
+        // Border ring drawn over the content, then the corners are cut out
+        assert!(filter.contains("color=c=0xFFCC00:s=1000x800:r=1:d=1,format=rgba"));
+        assert!(filter.contains("clip(ld(2)+8+0.5,0,1)"));
+        assert!(filter.contains("overlay=0:0:format=auto"));
+        assert!(filter.contains("]alphamerge[tmp"));
+        // The corner mask is multiplied with the layer's own alpha
+        assert!(filter.contains("alphaextract[rnda"));
+        assert!(filter.contains("blend=all_mode=multiply[rndc"));
+        // Shadow plate with room for the blur, moved by the offset
+        assert!(filter.contains("color=c=black:s=1040x840:r=1:d=1,format=rgba"));
+        assert!(filter.contains("gblur=sigma=5,colorchannelmixer=aa=0.5"));
+        assert!(filter.contains("overlay=32:292["));
+        assert!(filter.contains("overlay=40:300["));
+
+        // Unstyled layers keep the plain chain
+        let plano = create_default_plano();
+        let (filter, _) = build_ffmpeg_filter(&plano, "test.mp4", &RenderOptions::animated(10.0));
+        assert!(!filter.contains("alphamerge"));
+    }
+
+    #[test]
+    fn test_shifted_coord() {
+        assert_eq!(shifted_coord("100", 12, 40), "72");
+        assert_eq!(shifted_coord("(W-w)/2", 12, 40), "'(W-w)/2+12'");
+        assert_eq!(shifted_coord("'t*10'", 12, 40), "'t*10-28'");
+    }
+
+    #[test]
+    fn test_animated_image_detection() {
+        assert!(is_animated_image("sticker.gif"));
+        assert!(is_animated_image("frames/%03d.png"));
+        assert!(!is_animated_image("logo.jpg"));
+        // A still PNG without the animation chunk
+        assert!(!is_animated_image("example.png"));
+
+        assert_eq!(
+            sequence_frame_path("frames/f_%03d.png", 7),
+            "frames/f_007.png"
+        );
+        assert_eq!(sequence_frame_path("frames/%d.png", 12), "frames/12.png");
+        assert!(!is_image_sequence("100%.png"));
+    }
+
+    #[test]
+    fn test_animated_image_filters() {
+        let timing = LayerTiming {
+            start: Some(TimeValue::Seconds(4.0)),
+            ..Default::default()
+        };
+        let chain =
+            animated_image_frames(true, Some(12.0), &timing, &RenderOptions::animated(10.0));
+        assert_eq!(
+            chain,
+            "setpts=PTS-STARTPTS,setpts=N/(12*TB),loop=loop=-1:size=32767:start=0,setpts=PTS+4/TB,format=rgba,"
+        );
+
+        let still = animated_image_frames(
+            false,
+            None,
+            &LayerTiming::default(),
+            &RenderOptions::still(30.0, 0.0),
+        );
+        assert!(still.contains("tpad=stop_mode=clone"));
+        assert!(still.contains("trim=start=1,"));
+    }
+
+    #[test]
+    fn test_join_bumpers() {
+        let intro = Bumper {
+            path: "intro.mp4".to_string(),
+            crossfade: 0.5,
+        };
+        let outro = Bumper {
+            path: "outro.mp4".to_string(),
+            crossfade: 0.0,
+        };
+        let intro_input = BumperInput {
+            bumper: &intro,
+            duration: 2.0,
+            has_audio: true,
+        };
+        let outro_input = BumperInput {
+            bumper: &outro,
+            duration: 3.0,
+            has_audio: false,
+        };
+
+        let plano = create_default_plano();
+        let mut ctx = FilterContext::new("test.mp4");
+        let timeline = Timeline::build(10.0, &[], &Default::default()).unwrap();
+        let (audio, length) = ctx.compose_timeline(
+            &plano,
+            "out0",
+            &timeline,
+            10.0,
+            true,
+            &RenderOptions::animated(10.0),
+        );
+        let video = ctx.trim_output("out0", length);
+        let (v, a, total) = ctx.join_bumpers(
+            (video, audio, length),
+            Some(&intro_input),
+            Some(&outro_input),
+            (OUTPUT_WIDTH, OUTPUT_HEIGHT),
+        );
+        let (filter, inputs) = ctx.finish();
+
+        // 2 + 10 - 0.5 crossfade + 3
+        assert_eq!(total, 14.5);
+        assert_eq!(inputs, vec!["test.mp4", "intro.mp4", "outro.mp4"]);
+        assert!(filter.contains("[out0]trim=duration=10,setpts=PTS-STARTPTS[tl"));
+        assert!(filter.contains("]setsar=1,fps=60,format=yuv420p[mainv"));
+        assert!(filter.contains("xfade=transition=fade:duration=0.5:offset=1.5"));
+        assert!(filter.contains("acrossfade=d=0.5"));
+        // The outro has no audio: silence keeps the concat aligned
+        assert!(filter.contains("anullsrc=r=48000:cl=stereo,atrim=duration=3"));
+        assert!(filter.contains(&format!("concat=n=2:v=1:a=1[{}][{}]", v, a)));
+    }
+
+    #[test]
+    fn test_escape_filter_path() {
+        assert_eq!(
+            escape_filter_path("C:\\luts\\warm.cube"),
+            "'C\\:/luts/warm.cube'"
+        );
+    }
+
     #[test]
     fn test_create_default_plano() {
         let plano = create_default_plano();
@@ -0,0 +1,321 @@
+//! Jump cuts for YT ShortMaker
+//! Removes the pauses of an extracted clip with `silencedetect`
+//!
+//! The clip is re-encoded without the silent parts before any plano is
+//! applied. The moment's dialogue is re-timed to the cut clip and written next
+//! to it as subtitles ("clip.mp4" -> "clip.srt").
+
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::config::JumpCutConfig;
+use crate::encoding::ExportProfile;
+use crate::timeline::{Segment, Timeline};
+use crate::types::{DialoguePhrase, VideoMoment};
+
+/// Silences shorter than this after padding are left alone
+const MIN_CUT: f64 = 0.1;
+/// Kept parts shorter than this are dropped
+const MIN_KEEP: f64 = 0.05;
+
+/// A subtitle line with times in seconds from the start of the clip
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caption {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Subtitle file written next to a clip ("clip.mp4" -> "clip.srt")
+pub fn captions_path(clip_path: &str) -> PathBuf {
+    Path::new(clip_path).with_extension("srt")
+}
+
+/// Remove the silences of `clip_path` in place and write its re-timed captions
+/// Returns the seconds removed (0 when there was nothing to cut)
+pub async fn apply(
+    clip_path: &str,
+    moment: &VideoMoment,
+    settings: &JumpCutConfig,
+    profile: &ExportProfile,
+    cancellation_token: Arc<AtomicBool>,
+) -> Result<f64> {
+    let duration = crate::video::get_video_duration_precise(clip_path)?;
+    let ranges = if crate::video::has_audio_stream(clip_path) {
+        let silences = detect_silences(clip_path, settings, cancellation_token.clone()).await?;
+        keep_ranges(&silences, duration, settings.padding)
+    } else {
+        vec![(0.0, duration)]
+    };
+    let kept: f64 = ranges.iter().map(|(start, end)| end - start).sum();
+    let removed = duration - kept;
+
+    if removed > MIN_KEEP {
+        let timeline = Timeline {
+            segments: ranges
+                .iter()
+                .map(|&(start, end)| Segment {
+                    start,
+                    end,
+                    speed: 1.0,
+                })
+                .collect(),
+        };
+        let (filters, video, audio) = timeline.filters("0:v", Some("0:a"), "jc");
+        let mut args = vec![
+            "-hide_banner".to_string(),
+            "-loglevel".to_string(),
+            "error".to_string(),
+            "-i".to_string(),
+            clip_path.to_string(),
+            "-filter_complex".to_string(),
+            filters.join(";"),
+            "-map".to_string(),
+            format!("[{}]", video),
+        ];
+        if let Some(audio) = audio {
+            args.push("-map".to_string());
+            args.push(format!("[{}]", audio));
+        }
+
+        let temp_path = Path::new(clip_path).with_extension("jumpcut.mp4");
+        let temp = temp_path.to_string_lossy().to_string();
+        crate::encoding::run_encode(&args, profile, kept, &temp, cancellation_token)
+            .await
+            .context("ffmpeg jump cut failed")?;
+        std::fs::rename(&temp_path, clip_path)
+            .with_context(|| format!("Failed to replace {}", clip_path))?;
+    }
+
+    let clip_start = crate::video::parse_timestamp_to_seconds(&moment.start_time)? as f64;
+    let captions = retime_dialogue(&moment.dialogue, clip_start, &ranges);
+    if !captions.is_empty() {
+        let path = captions_path(clip_path);
+        std::fs::write(&path, to_srt(&captions))
+            .with_context(|| format!("Failed to write {:?}", path))?;
+    }
+
+    Ok(removed.max(0.0))
+}
+
+/// Find the silent parts of a clip as (start, end) pairs in seconds
+pub async fn detect_silences(
+    clip_path: &str,
+    settings: &JumpCutConfig,
+    cancellation_token: Arc<AtomicBool>,
+) -> Result<Vec<(f64, f64)>> {
+    let mut command = tokio::process::Command::new("ffmpeg");
+    command.args([
+        "-hide_banner",
+        "-nostats",
+        "-i",
+        clip_path,
+        "-vn",
+        "-af",
+        &format!(
+            "silencedetect=noise={}dB:d={}",
+            settings.threshold_db, settings.min_silence
+        ),
+        "-f",
+        "null",
+        "-",
+    ]);
+    let output = crate::video::run_command_with_cancellation(command, cancellation_token).await?;
+    let log = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(anyhow!("Silence detection failed: {}", log.trim()));
+    }
+    Ok(parse_silences(&log))
+}
+
+/// Read the `silence_start` / `silence_end` lines printed by `silencedetect`
+/// A silence still open at the end of the clip lasts until the end
+fn parse_silences(log: &str) -> Vec<(f64, f64)> {
+    let value_after = |line: &str, key: &str| -> Option<f64> {
+        let rest = &line[line.find(key)? + key.len()..];
+        rest.split_whitespace().next()?.parse().ok()
+    };
+
+    let mut silences = Vec::new();
+    let mut open = None;
+    for line in log.lines() {
+        if let Some(start) = value_after(line, "silence_start:") {
+            open = Some(start.max(0.0));
+        } else if let Some(end) = value_after(line, "silence_end:") {
+            if let Some(start) = open.take() {
+                silences.push((start, end));
+            }
+        }
+    }
+    if let Some(start) = open {
+        silences.push((start, f64::INFINITY));
+    }
+    silences
+}
+
+/// Parts of the clip to keep once the silences (minus `padding`) are cut
+/// A clip that is silent from start to end is kept whole
+pub fn keep_ranges(silences: &[(f64, f64)], duration: f64, padding: f64) -> Vec<(f64, f64)> {
+    let padding = padding.max(0.0);
+    let mut ranges = Vec::new();
+    let mut cursor = 0.0;
+    for &(start, end) in silences {
+        // No padding is needed before the first word or after the last one
+        let cut_start = if start <= MIN_KEEP {
+            0.0
+        } else {
+            start + padding
+        };
+        let cut_end = if end >= duration - MIN_KEEP {
+            duration
+        } else {
+            end - padding
+        };
+        let cut_start = cut_start.max(cursor);
+        if cut_end - cut_start < MIN_CUT {
+            continue;
+        }
+        if cut_start - cursor > MIN_KEEP {
+            ranges.push((cursor, cut_start));
+        }
+        cursor = cut_end;
+    }
+    if duration - cursor > MIN_KEEP {
+        ranges.push((cursor, duration));
+    }
+    if ranges.is_empty() {
+        ranges.push((0.0, duration));
+    }
+    ranges
+}
+
+/// Position of clip time `time` once only `ranges` are kept
+/// Times inside a cut land where the cut is
+pub fn remap_time(time: f64, ranges: &[(f64, f64)]) -> f64 {
+    let mut elapsed = 0.0;
+    for &(start, end) in ranges {
+        if time <= start {
+            return elapsed;
+        }
+        if time < end {
+            return elapsed + time - start;
+        }
+        elapsed += end - start;
+    }
+    elapsed
+}
+
+/// Move the dialogue of a moment to the time of the cut clip
+/// Dialogue times are whole seconds in the source video, so every line lasts
+/// at least one second; lines that fall entirely in a cut are dropped
+pub fn retime_dialogue(
+    dialogue: &[DialoguePhrase],
+    clip_start: f64,
+    ranges: &[(f64, f64)],
+) -> Vec<Caption> {
+    dialogue
+        .iter()
+        .filter_map(|phrase| {
+            let start = crate::video::parse_timestamp_to_seconds(&phrase.start_time).ok()? as f64;
+            let end = crate::video::parse_timestamp_to_seconds(&phrase.end_time).ok()? as f64;
+            let end = end.max(start + 1.0);
+            let caption = Caption {
+                start: remap_time(start - clip_start, ranges),
+                end: remap_time(end - clip_start, ranges),
+                text: phrase.phrase.trim().to_string(),
+            };
+            (caption.end - caption.start > MIN_KEEP && !caption.text.is_empty()).then_some(caption)
+        })
+        .collect()
+}
+
+/// Format captions as a SubRip (.srt) file
+pub fn to_srt(captions: &[Caption]) -> String {
+    let timestamp = |seconds: f64| {
+        let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+        format!(
+            "{:02}:{:02}:{:02},{:03}",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        )
+    };
+
+    captions
+        .iter()
+        .enumerate()
+        .map(|(i, caption)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                timestamp(caption.start),
+                timestamp(caption.end),
+                caption.text
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_silences() {
+        let log = "\
+[silencedetect @ 0x55d1] silence_start: 1.5
+[silencedetect @ 0x55d1] silence_end: 3.25 | silence_duration: 1.75
+size=N/A time=00:00:10.00 bitrate=N/A
+[silencedetect @ 0x55d1] silence_start: 8.2
+";
+        let silences = parse_silences(log);
+        assert_eq!(silences[0], (1.5, 3.25));
+        assert_eq!(silences[1].0, 8.2);
+        assert!(silences[1].1.is_infinite());
+    }
+
+    #[test]
+    fn test_keep_ranges() {
+        // Leading pause, a pause in the middle, a trailing pause and a short gap
+        let silences = vec![(0.0, 1.0), (4.0, 6.0), (7.0, 7.2), (9.0, f64::INFINITY)];
+        let ranges = keep_ranges(&silences, 10.0, 0.25);
+        assert_eq!(ranges, vec![(0.75, 4.25), (5.75, 9.25)]);
+
+        // Nothing but silence: keep the clip
+        assert_eq!(
+            keep_ranges(&[(0.0, f64::INFINITY)], 5.0, 0.1),
+            vec![(0.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn test_retime_dialogue() {
+        let ranges = vec![(0.0, 4.0), (6.0, 10.0)];
+        assert_eq!(remap_time(2.0, &ranges), 2.0);
+        assert_eq!(remap_time(5.0, &ranges), 4.0);
+        assert_eq!(remap_time(7.0, &ranges), 5.0);
+        assert_eq!(remap_time(12.0, &ranges), 8.0);
+
+        let phrase = |start: &str, end: &str, text: &str| DialoguePhrase {
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            phrase: text.to_string(),
+        };
+        // The clip starts at 00:01:00 in the source video
+        let dialogue = vec![
+            phrase("00:01:01", "00:01:03", "Hello"),
+            phrase("00:01:04", "00:01:05", ""),
+            phrase("00:01:07", "00:01:09", "World"),
+        ];
+        let captions = retime_dialogue(&dialogue, 60.0, &ranges);
+        assert_eq!(captions.len(), 2);
+        assert_eq!((captions[1].start, captions[1].end), (5.0, 7.0));
+
+        let srt = to_srt(&captions);
+        assert!(srt.starts_with("1\n00:00:01,000 --> 00:00:03,000\nHello\n\n2\n"));
+        assert!(srt.contains("00:00:05,000 --> 00:00:07,000\nWorld"));
+    }
+}
@@ -0,0 +1,311 @@
+//! Plano library for YT ShortMaker
+//! Built-in presets plus the planos the user saved into the config folder
+//!
+//! A preset is written to the library folder the first time it is used, so it
+//! can be edited like any other plano; later uses pick up that copy.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::AppConfig;
+use crate::exporter::{LoadedPlano, Plano, PlanoLayout};
+
+/// Where a library entry comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum LibrarySource {
+    /// Shipped with the app
+    Preset(PlanoLayout),
+    /// A plano file in the library folder
+    Saved(PathBuf),
+}
+
+/// A plano offered by the library
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    /// Display name (translated for presets, file stem for saved planos)
+    pub name: String,
+    pub source: LibrarySource,
+    pub plano: Plano,
+}
+
+/// Folder holding the user's saved planos (e.g. %LocalAppData%/yt-shortmaker/planos)
+pub fn library_dir() -> PathBuf {
+    AppConfig::get_config_dir().join("planos")
+}
+
+/// Presets first, then the saved planos sorted by name
+/// An installed preset is listed once, with the plano of its copy
+pub fn load_library() -> Vec<LibraryEntry> {
+    load_library_from(&library_dir())
+}
+
+fn load_library_from(dir: &Path) -> Vec<LibraryEntry> {
+    let mut entries: Vec<LibraryEntry> = PlanoLayout::ALL
+        .iter()
+        .map(|&layout| {
+            let copy = dir.join(layout.file_name());
+            let plano = if copy.exists() {
+                LoadedPlano::load(&copy.to_string_lossy())
+                    .map(|loaded| loaded.plano)
+                    .unwrap_or_else(|e| {
+                        log::warn!("Using the built-in {}: {:#}", layout.file_name(), e);
+                        Plano::from(crate::exporter::create_layout_plano(layout))
+                    })
+            } else {
+                Plano::from(crate::exporter::create_layout_plano(layout))
+            };
+            LibraryEntry {
+                name: rust_i18n::t!(layout.label_key()).to_string(),
+                source: LibrarySource::Preset(layout),
+                plano,
+            }
+        })
+        .collect();
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|read| read.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default();
+    files.retain(|path| {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json") || ext.eq_ignore_ascii_case("zip"))
+            && !path.ends_with(crate::schema::SCHEMA_FILE_NAME)
+            && !PlanoLayout::ALL
+                .iter()
+                .any(|layout| path.ends_with(layout.file_name()))
+    });
+    files.sort();
+
+    for path in files {
+        let path_str = path.to_string_lossy().to_string();
+        match LoadedPlano::load(&path_str) {
+            Ok(loaded) => entries.push(LibraryEntry {
+                name: loaded.name(),
+                source: LibrarySource::Saved(path),
+                plano: loaded.plano,
+            }),
+            Err(e) => log::warn!("Skipping library plano {}: {:#}", path_str, e),
+        }
+    }
+    entries
+}
+
+/// File to use for an entry: saved planos as they are, presets copied into
+/// the library first (an existing copy is kept, with the user's edits)
+pub fn entry_file(entry: &LibraryEntry) -> Result<PathBuf> {
+    match &entry.source {
+        LibrarySource::Saved(path) => Ok(path.clone()),
+        LibrarySource::Preset(layout) => install_preset_in(&library_dir(), *layout),
+    }
+}
+
+fn install_preset_in(dir: &Path, layout: PlanoLayout) -> Result<PathBuf> {
+    let path = dir.join(layout.file_name());
+    if !path.exists() {
+        fs::create_dir_all(dir)?;
+        let plano = Plano::from(crate::exporter::create_layout_plano(layout));
+        crate::exporter::save_plano(&path.to_string_lossy(), &plano)?;
+    }
+    Ok(path)
+}
+
+/// Copy a plano into the library under a name not used yet
+/// .zip bundles are copied as they are so their assets come along; other
+/// planos keep their document (comments, `extends`, placeholders) with
+/// relative paths pointed at the original folder
+pub fn save_to_library(loaded: &LoadedPlano) -> Result<PathBuf> {
+    save_to_library_in(&library_dir(), loaded)
+}
+
+fn save_to_library_in(dir: &Path, loaded: &LoadedPlano) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let source = Path::new(&loaded.path);
+    let is_zip = source
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let extension = if is_zip { "zip" } else { "json" };
+
+    let name = loaded.name();
+    let mut target = dir.join(format!("{}.{}", name, extension));
+    let mut n = 2;
+    while target.exists() {
+        target = dir.join(format!("{}_{}.{}", name, n, extension));
+        n += 1;
+    }
+
+    if is_zip {
+        fs::copy(source, &target)
+            .with_context(|| format!("Failed to copy {:?} to the library", source))?;
+    } else {
+        let file = crate::assets::plano_file(source)?;
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read plano file: {:?}", file))?;
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+        fs::write(&target, relocate_paths(&content, &dir)?)
+            .with_context(|| format!("Failed to write {:?}", target))?;
+    }
+    Ok(target)
+}
+
+/// Rewrite the relative paths of a plano document so they point into `dir`
+/// The text is edited in place, so comments and formatting are kept
+fn relocate_paths(content: &str, dir: &Path) -> Result<String> {
+    let document: Value = serde_json::from_str(&crate::exporter::remove_js_comments(content))
+        .context("Failed to parse plano JSON")?;
+    let mut paths = Vec::new();
+    collect_paths(&document, None, &mut paths);
+    paths.sort_unstable();
+    paths.dedup();
+
+    let mut content = content.to_string();
+    for path in paths {
+        let is_relative = Path::new(path).is_relative()
+            && !path.is_empty()
+            && !path.contains("://")
+            && !path.starts_with(['~', '$', '%'])
+            && !path.starts_with("{{");
+        if !is_relative {
+            continue;
+        }
+        let moved = dir.join(path).to_string_lossy().to_string();
+        let quoted = |s: &str| serde_json::to_string(s).unwrap_or_default();
+        content = content.replace(&quoted(path), &quoted(&moved));
+    }
+    Ok(content)
+}
+
+/// Strings of a plano document that are file paths: asset `path`s, clip
+/// `source`s and the planos of `extends`, `include` and `categories`
+fn collect_paths<'a>(value: &'a Value, key: Option<&str>, paths: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => {
+            if matches!(
+                key,
+                Some("path" | "source" | "extends" | "include" | "categories")
+            ) {
+                paths.push(s);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_paths(item, key, paths);
+            }
+        }
+        Value::Object(map) => {
+            for (name, item) in map {
+                let key = if key == Some("categories") {
+                    key
+                } else {
+                    Some(name.as_str())
+                };
+                collect_paths(item, key, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_presets_and_saved_planos() {
+        let dir = std::env::temp_dir().join(format!("ytsm_library_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        // Without a folder only the presets are listed
+        let entries = load_library_from(&dir);
+        assert_eq!(entries.len(), PlanoLayout::ALL.len());
+        assert!(entries
+            .iter()
+            .all(|e| matches!(e.source, LibrarySource::Preset(_))));
+
+        // Installing a preset keeps an edited copy
+        let path = install_preset_in(&dir, PlanoLayout::Podcast).unwrap();
+        fs::write(&path, "[]").unwrap();
+        assert_eq!(install_preset_in(&dir, PlanoLayout::Podcast).unwrap(), path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[]");
+
+        // Saving twice under the same name gets a suffix
+        let source_dir = dir.join("elsewhere");
+        fs::create_dir_all(&source_dir).unwrap();
+        let source = source_dir.join("gaming.json");
+        crate::exporter::save_plano(
+            &source.to_string_lossy(),
+            &Plano::from(crate::exporter::create_default_plano()),
+        )
+        .unwrap();
+        let loaded = LoadedPlano::load(&source.to_string_lossy()).unwrap();
+        let first = save_to_library_in(&dir, &loaded).unwrap();
+        let second = save_to_library_in(&dir, &loaded).unwrap();
+        assert!(first.ends_with("gaming.json"));
+        assert!(second.ends_with("gaming_2.json"));
+
+        let entries = load_library_from(&dir);
+        let saved: Vec<&str> = entries
+            .iter()
+            .filter(|e| matches!(e.source, LibrarySource::Saved(_)))
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(saved, vec!["gaming", "gaming_2"]);
+
+        // The installed preset is listed once, showing the edited copy
+        assert_eq!(entries.len(), PlanoLayout::ALL.len() + 2);
+        let podcast = entries
+            .iter()
+            .find(|e| e.source == LibrarySource::Preset(PlanoLayout::Podcast))
+            .unwrap();
+        assert!(podcast.plano.layers.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_saved_plano_keeps_its_document() {
+        let dir = std::env::temp_dir().join(format!("ytsm_library_doc_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let source_dir = dir.join("planos");
+        fs::create_dir_all(&source_dir).unwrap();
+        let source = source_dir.join("overlay.json");
+        fs::write(
+            &source,
+            r#"{
+  // Shared background
+  "extends": "base.json",
+  "categories": { "Funny": "meme.json" },
+  "layers": [
+    { "type": "image", "path": "titles/{{category}}.png", "opacity": "{{title_opacity|1}}",
+      "position": { "x": 0, "y": 0, "width": 100, "height": 100 } },
+    { "type": "image", "path": "{{logo|logo.png}}", "position": { "x": 0, "y": 0, "width": 10, "height": 10 } },
+    { "type": "image", "path": "/abs/logo.png", "position": { "x": 0, "y": 0, "width": 10, "height": 10 } }
+  ]
+}"#,
+        )
+        .unwrap();
+        fs::write(source_dir.join("base.json"), "[]").unwrap();
+        fs::write(source_dir.join("meme.json"), "[]").unwrap();
+
+        let loaded = LoadedPlano::load(&source.to_string_lossy()).unwrap();
+        let saved = save_to_library_in(&dir.join("library"), &loaded).unwrap();
+        let content = fs::read_to_string(&saved).unwrap();
+
+        let moved =
+            |path: &str| serde_json::to_string(&source_dir.join(path).to_string_lossy()).unwrap();
+        assert!(content.contains("// Shared background"));
+        assert!(content.contains(&format!("\"extends\": {}", moved("base.json"))));
+        assert!(content.contains(&format!("\"Funny\": {}", moved("meme.json"))));
+        assert!(content.contains(&moved("titles/{{category}}.png")));
+        assert!(content.contains("\"{{title_opacity|1}}\""));
+        assert!(content.contains("\"{{logo|logo.png}}\""));
+        assert!(content.contains("\"/abs/logo.png\""));
+
+        // The copy loads like the original
+        let copy = LoadedPlano::load(&saved.to_string_lossy()).unwrap();
+        assert_eq!(copy.plano.layers.len(), loaded.plano.layers.len());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,14 +2,28 @@
 //! A robust TUI tool to automate YouTube Shorts creation from long-form content
 //! using Google Gemini AI for intelligent content analysis.
 
+mod animation;
+mod assets;
 mod config;
+mod encoding;
 mod exporter;
 mod gemini;
+mod jumpcut;
+mod library;
+mod moments;
+mod nle;
+mod plano_editor;
+mod reframe;
+mod schema;
 mod security;
 mod setup;
 mod shorts;
+mod termimage;
+mod timeline;
 mod tui;
 mod types;
+mod validation;
+mod variables;
 mod video;
 
 use anyhow::{Context, Result};
@@ -88,6 +102,10 @@ async fn main() -> Result<()> {
 
 /// Handle CLI commands (preview, transform)
 async fn handle_cli_command(args: &[String]) -> Result<()> {
+    // `--plano <file>` replaces shorts_config for preview/transform/batch
+    let mut args = args.to_vec();
+    let plano_path = take_option(&mut args, "--plano");
+    let args = args.as_slice();
     let command = args[1].as_str();
 
     // Load config (minimal validation for CLI commands)
@@ -97,7 +115,7 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
         "preview" => {
             if args.len() < 3 {
                 eprintln!(
-                    "Usage: {} preview <video_path> [timestamp_seconds]",
+                    "Usage: {} preview <video_path> [timestamp_seconds] [--plano <file>]",
                     args[0]
                 );
                 eprintln!("\nExample:");
@@ -114,23 +132,78 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
 
             let output_image = format!("{}_preview.png", video_path.trim_end_matches(".mp4"));
 
+            let (plano, _) = cli_plano(&config, plano_path.as_deref())?;
+
             println!("🎬 Generating preview...");
             println!("   Input: {}", video_path);
             println!("   Timestamp: {:.2}s", timestamp);
-            println!(
-                "   Config: {} overlays",
-                config.shorts_config.overlays.len()
-            );
+            print_plano_source(&config, plano_path.as_deref(), &plano);
 
-            shorts::generate_preview(video_path, &output_image, &config.shorts_config, timestamp)?;
+            exporter::generate_preview_from_video_at(
+                video_path,
+                &plano.layers,
+                &output_image,
+                timestamp,
+            )?;
 
             println!("✅ Preview saved to: {}", output_image);
             Ok(())
         }
 
+        "preview-clip" => {
+            if args.len() < 3 {
+                eprintln!(
+                    "Usage: {} preview-clip <video_path> [seconds] [--plano <file>]",
+                    args[0]
+                );
+                eprintln!("\nExample:");
+                eprintln!("  {} preview-clip video.mp4", args[0]);
+                eprintln!("  {} preview-clip video.mp4 5  # First 5 seconds", args[0]);
+                std::process::exit(1);
+            }
+
+            let video_path = &args[2];
+            let seconds: f64 = args
+                .get(3)
+                .and_then(|s| s.parse().ok())
+                .filter(|s: &f64| *s > 0.0)
+                .unwrap_or(exporter::PREVIEW_CLIP_SECONDS);
+
+            let output_video = format!("{}_preview.mp4", video_path.trim_end_matches(".mp4"));
+
+            let (plano, _) = cli_plano(&config, plano_path.as_deref())?;
+
+            println!("🎬 Generating preview clip...");
+            println!("   Input: {}", video_path);
+            println!("   Length: {:.1}s", seconds);
+            print_plano_source(&config, plano_path.as_deref(), &plano);
+
+            let loaded = exporter::LoadedPlano {
+                path: plano_path.clone().unwrap_or_default(),
+                plano,
+            };
+            exporter::generate_preview_clip(
+                Some(video_path),
+                &loaded,
+                &output_video,
+                seconds,
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await?;
+
+            println!("✅ Preview clip saved to: {}", output_video);
+            if let Err(e) = open::that(&output_video) {
+                eprintln!("⚠️ Could not open the preview: {}", e);
+            }
+            Ok(())
+        }
+
         "transform" => {
             if args.len() < 3 {
-                eprintln!("Usage: {} transform <video_path> [output_path]", args[0]);
+                eprintln!(
+                    "Usage: {} transform <video_path> [output_path] [--plano <file>]",
+                    args[0]
+                );
                 eprintln!("\nExample:");
                 eprintln!("  {} transform video.mp4", args[0]);
                 eprintln!("  {} transform video.mp4 output_short.mp4", args[0]);
@@ -151,27 +224,27 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
                     )
                 });
 
+            let (plano, output_size) = cli_plano(&config, plano_path.as_deref())?;
+            let (width, height) =
+                output_size.unwrap_or((exporter::OUTPUT_WIDTH, exporter::OUTPUT_HEIGHT));
+
             println!("🎬 Transforming to YouTube Short...");
             println!("   Input: {}", video_path);
             println!("   Output: {}", output_path);
-            println!(
-                "   Resolution: {}x{}",
-                config.shorts_config.output_width, config.shorts_config.output_height
-            );
-            println!(
-                "   Background: {}",
-                config
-                    .shorts_config
-                    .background_video
-                    .as_ref()
-                    .unwrap_or(&"None".to_string())
-            );
-            println!("   Overlays: {}", config.shorts_config.overlays.len());
+            println!("   Resolution: {}x{}", width, height);
+            print_plano_source(&config, plano_path.as_deref(), &plano);
 
             let token = Arc::new(AtomicBool::new(false));
 
-            shorts::transform_to_short(video_path, &output_path, &config.shorts_config, token)
-                .await?;
+            shorts::transform_to_short(
+                video_path,
+                &output_path,
+                &plano,
+                &cli_profile(&config, plano_path.as_deref()),
+                output_size,
+                token,
+            )
+            .await?;
 
             println!("✅ Short saved to: {}", output_path);
             Ok(())
@@ -179,7 +252,10 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
 
         "batch" => {
             if args.len() < 3 {
-                eprintln!("Usage: {} batch <input_dir> [output_dir]", args[0]);
+                eprintln!(
+                    "Usage: {} batch <input_dir> [output_dir] [--plano <file>]",
+                    args[0]
+                );
                 eprintln!("\nExample:");
                 eprintln!("  {} batch ./clips", args[0]);
                 eprintln!("  {} batch ./clips ./shorts", args[0]);
@@ -192,16 +268,21 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| format!("{}_shorts", input_dir));
 
+            let (plano, output_size) = cli_plano(&config, plano_path.as_deref())?;
+
             println!("🎬 Batch transforming videos...");
             println!("   Input dir: {}", input_dir);
             println!("   Output dir: {}", output_dir);
+            print_plano_source(&config, plano_path.as_deref(), &plano);
 
             let token = Arc::new(AtomicBool::new(false));
 
             let results = shorts::transform_batch(
                 input_dir,
                 &output_dir,
-                &config.shorts_config,
+                &plano,
+                &cli_profile(&config, plano_path.as_deref()),
+                output_size,
                 Some(Box::new(|current, total, name| {
                     println!("   [{}/{}] Processing: {}", current, total, name);
                 })),
@@ -213,6 +294,190 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
             Ok(())
         }
 
+        "extract" => {
+            if args.len() < 4 {
+                eprintln!(
+                    "Usage: {} extract <moments_file> <url_or_video> [output_dir] [--plano <file>]",
+                    args[0]
+                );
+                eprintln!(
+                    "\nMoments: moments.json, a CSV (start,end,category,description) or a text"
+                );
+                eprintln!(
+                    "file with one \"start-end label\" per line, e.g. 1:10-1:40 Funny: the fall"
+                );
+                eprintln!("\nExample:");
+                eprintln!("  {} extract moments.json https://youtu.be/abc123", args[0]);
+                eprintln!(
+                    "  {} extract picks.txt stream.mp4 ./shorts --plano gaming.json",
+                    args[0]
+                );
+                std::process::exit(1);
+            }
+
+            let moments = moments::load_moments(&args[2])?;
+            let source = args[3].clone();
+            if !Path::new(&source).is_file() && !video::validate_media_url(&source) {
+                anyhow::bail!("Not a video file or URL: {}", source);
+            }
+            let mut config = config;
+            if let Some(dir) = args.get(4) {
+                config.default_output_dir = dir.clone();
+            }
+            config.ensure_output_dir()?;
+            moments::save_moments(&config.default_output_dir, &moments)?;
+
+            println!(
+                "🎬 Extracting {} moments...",
+                moments.iter().filter(|m| !m.rejected).count()
+            );
+            println!("   Source: {}", source);
+            println!("   Output dir: {}", config.default_output_dir);
+
+            let cache_id = extract_video_id(&source)
+                .unwrap_or_else(|| Local::now().format("import_%Y%m%d_%H%M%S").to_string());
+            let temp_dir = format!("{}/cache_{}", config.default_output_dir, cache_id);
+            fs::create_dir_all(&temp_dir)?;
+            let context = ProcessingContext {
+                url: source,
+                temp_json_path: format!("{}/import.json", temp_dir),
+                temp_dir,
+                custom_format: None,
+            };
+
+            // Print the progress messages the TUI would show
+            let (tx, mut rx) = tui::create_channel();
+            let printer = tokio::spawn(async move {
+                while let Some(msg) = rx.recv().await {
+                    match msg {
+                        AppMessage::Status(status) => println!("   {}", status),
+                        AppMessage::Log(LogLevel::Error, msg) | AppMessage::Error(msg) => {
+                            println!("   ❌ {}", msg)
+                        }
+                        AppMessage::Log(LogLevel::Warning, msg) => println!("   ⚠️ {}", msg),
+                        AppMessage::Log(_, msg) | AppMessage::Complete(msg) => {
+                            println!("   {}", msg)
+                        }
+                        _ => {}
+                    }
+                }
+            });
+            let token = Arc::new(AtomicBool::new(false));
+            let result = run_extraction(tx, config.clone(), context, moments, token.clone()).await;
+            printer.await.ok();
+            let (_, shorts_dir) = result?;
+            let shorts_dir = shorts_dir.unwrap_or_default();
+            println!("✅ Shorts saved to: {}", shorts_dir);
+
+            if let Some(path) = plano_path.as_deref() {
+                let plano = exporter::LoadedPlano::load(path)?;
+                let export_dir = format!("{}_export", shorts_dir);
+                println!("🎬 Exporting with plano {}...", path);
+                exporter::export_batch(
+                    &[shorts_dir],
+                    &[plano],
+                    &config.export_profile,
+                    &export_dir,
+                    Some(Box::new(|current, total, name| {
+                        println!("   [{}/{}] Exporting: {}", current, total, name);
+                    })),
+                    None,
+                    token,
+                )
+                .await?;
+                println!("✅ Exported shorts saved to: {}", export_dir);
+            }
+            Ok(())
+        }
+
+        "timeline" => {
+            if args.len() < 4 {
+                eprintln!(
+                    "Usage: {} timeline <moments_file> <video> [output_dir]",
+                    args[0]
+                );
+                eprintln!("\nWrites timeline.edl, timeline.fcpxml, timeline.xml (Premiere) and");
+                eprintln!("timeline.otio with a clip and a marker for every moment");
+                eprintln!("\nExample:");
+                eprintln!("  {} timeline moments.json stream.mp4 ./edit", args[0]);
+                std::process::exit(1);
+            }
+
+            let moments = moments::load_moments(&args[2])?;
+            let source = &args[3];
+            if !Path::new(source).is_file() {
+                anyhow::bail!("Video not found: {}", source);
+            }
+            let output_dir = args
+                .get(4)
+                .cloned()
+                .unwrap_or_else(|| config.default_output_dir.clone());
+
+            for path in nle::export_timelines(&output_dir, source, &moments)? {
+                println!("✅ {}", path.display());
+            }
+            Ok(())
+        }
+
+        "plano" => match args.get(2).map(|s| s.as_str()) {
+            Some("validate") if args.len() > 3 => {
+                let mut errors = 0;
+                for path in &args[3..] {
+                    let plano = match exporter::load_plano(path) {
+                        Ok(plano) => plano,
+                        Err(e) => {
+                            eprintln!("❌ {}: {:#}", path, e);
+                            errors += 1;
+                            continue;
+                        }
+                    };
+
+                    let issues = validation::validate_plano(&plano);
+                    if issues.is_empty() {
+                        println!("✅ {}: no problems found", path);
+                        continue;
+                    }
+                    println!("📋 {}:", path);
+                    for issue in &issues {
+                        let icon = match issue.severity {
+                            validation::Severity::Error => {
+                                errors += 1;
+                                "❌"
+                            }
+                            validation::Severity::Warning => "⚠️",
+                        };
+                        println!("   {} {}", icon, issue);
+                    }
+                }
+                if errors > 0 {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            Some("schema") => {
+                match args.get(3) {
+                    Some(path) => {
+                        let file = schema::write_plano_schema(std::path::Path::new(path))?;
+                        println!("✅ Schema saved to: {}", file.display());
+                        println!(
+                            "   Add \"$schema\": \"./{}\" to a plano document to use it",
+                            schema::SCHEMA_FILE_NAME
+                        );
+                    }
+                    None => println!("{}", schema::plano_schema_json()),
+                }
+                Ok(())
+            }
+            _ => {
+                eprintln!("Usage: {} plano validate <plano.json>...", args[0]);
+                eprintln!("       {} plano schema [output]", args[0]);
+                eprintln!("\nExample:");
+                eprintln!("  {} plano validate my_plano.json", args[0]);
+                eprintln!("  {} plano schema ./planos", args[0]);
+                std::process::exit(1);
+            }
+        },
+
         "help" | "--help" | "-h" => {
             print_help(&args[0]);
             Ok(())
@@ -226,6 +491,55 @@ async fn handle_cli_command(args: &[String]) -> Result<()> {
     }
 }
 
+/// Remove `name <value>` from the arguments and return the value
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let pos = args.iter().position(|a| a == name)?;
+    args.remove(pos);
+    (pos < args.len()).then(|| args.remove(pos))
+}
+
+/// Plano for the preview/transform/batch commands and the resolution to
+/// resize to: the `--plano` file, or shorts_config converted to a plano
+fn cli_plano(
+    config: &AppConfig,
+    plano_path: Option<&str>,
+) -> Result<(exporter::Plano, Option<(u32, u32)>)> {
+    match plano_path {
+        Some(path) => Ok((exporter::load_plano(path)?, None)),
+        None => Ok((
+            shorts::plano_from_config(&config.shorts_config),
+            shorts::output_size(&config.shorts_config),
+        )),
+    }
+}
+
+/// Encoder settings of a CLI command: the export profile with a plano, else
+/// the profile of `shorts_config`
+fn cli_profile(config: &AppConfig, plano_path: Option<&str>) -> encoding::ExportProfile {
+    match plano_path {
+        Some(_) => config.export_profile.clone(),
+        None => config.shorts_config.profile.clone(),
+    }
+}
+
+/// Print where the layout of a CLI command comes from
+fn print_plano_source(config: &AppConfig, plano_path: Option<&str>, plano: &exporter::Plano) {
+    match plano_path {
+        Some(path) => println!("   Plano: {} ({} layers)", path, plano.layers.len()),
+        None => {
+            println!(
+                "   Background: {}",
+                config
+                    .shorts_config
+                    .background_video
+                    .as_deref()
+                    .unwrap_or("None")
+            );
+            println!("   Overlays: {}", config.shorts_config.overlays.len());
+        }
+    }
+}
+
 /// Print help message
 fn print_help(program: &str) {
     println!("YT ShortMaker v{}", types::APP_VERSION);
@@ -239,6 +553,10 @@ fn print_help(program: &str) {
         "  {} preview <video> [time]    Generate preview image",
         program
     );
+    println!(
+        "  {} preview-clip <video> [s]  Render the first seconds as a small video",
+        program
+    );
     println!(
         "  {} transform <video> [out]   Transform single video to short",
         program
@@ -247,6 +565,22 @@ fn print_help(program: &str) {
         "  {} batch <dir> [out_dir]     Batch transform all videos in directory",
         program
     );
+    println!(
+        "  {} extract <moments> <src>   Extract moments from a file, without AI analysis",
+        program
+    );
+    println!(
+        "  {} timeline <moments> <video>  Write EDL, FCPXML, Premiere XML and OTIO timelines",
+        program
+    );
+    println!(
+        "  {} plano validate <file>     Check a plano for problems",
+        program
+    );
+    println!(
+        "  {} plano schema [output]     Print or save the plano JSON Schema",
+        program
+    );
     println!(
         "  {} help                      Show this help message",
         program
@@ -257,6 +591,10 @@ fn print_help(program: &str) {
         "  {} preview clip.mp4 2.5      Preview at 2.5 seconds",
         program
     );
+    println!(
+        "  {} preview-clip clip.mp4 5   Preview video of the first 5 seconds",
+        program
+    );
     println!(
         "  {} transform clip.mp4        Creates clip_short.mp4",
         program
@@ -265,12 +603,24 @@ fn print_help(program: &str) {
         "  {} batch ./clips ./shorts    Transform all clips to shorts",
         program
     );
+    println!(
+        "  {} transform clip.mp4 --plano gaming.json  Use a plano instead of shorts_config",
+        program
+    );
+    println!(
+        "  {} extract picks.txt stream.mp4 --plano gaming.json  Cut and export listed moments",
+        program
+    );
     println!();
     println!("CONFIGURATION:");
     println!("  Edit {:?} to configure:", AppConfig::get_config_path());
     println!("  - shorts_config.background_video   Background video path (looped)");
     println!("  - shorts_config.background_opacity Opacity (0.0-1.0, default 0.4)");
     println!("  - shorts_config.overlays           Array of image overlays with x,y positions");
+    println!(
+        "  preview, preview-clip, transform and batch accept --plano <file> to use a plano instead"
+    );
+    println!("  extract accepts --plano <file> to export the extracted clips with a plano");
 }
 
 /// Load config for CLI commands
@@ -382,6 +732,9 @@ async fn run_app(
     loop {
         // Render UI
         terminal.draw(|frame| tui::render(frame, &app))?;
+        if tui::draw_graphics(&mut app)? {
+            terminal.clear()?;
+        }
 
         // Handle messages from background tasks
         while let Ok(msg) = rx.try_recv() {
@@ -531,7 +884,10 @@ async fn run_app(
             AppScreen::UrlInput => {
                 if app.confirm_response.take().is_some() {
                     let input_url = app.input.trim().to_string();
-                    if video::validate_media_url(&input_url) {
+                    // Imported moments can be cut from a video on disk
+                    let local_video =
+                        app.imported_moments.is_some() && Path::new(&input_url).is_file();
+                    if local_video || video::validate_media_url(&input_url) {
                         url = input_url;
                         app.log(LogLevel::Success, format!("Valid URL: {}", url));
 
@@ -556,7 +912,22 @@ async fn run_app(
                         // Do NOT remove directory if it exists, to allow cache reuse
                         fs::create_dir_all(&temp_dir)?;
                         all_moments.clear();
-                        app.screen = AppScreen::FormatConfirm;
+
+                        if let Some(moments) = app.imported_moments.take() {
+                            // Skip the analysis and review the imported moments
+                            all_moments = moments;
+                            app.moments = all_moments.clone();
+                            let temp_json_path =
+                                format!("{}/temp.json", current_config.default_output_dir);
+                            save_session(&temp_json_path, &url, &all_moments, &temp_dir)?;
+                            moments::save_moments(
+                                &current_config.default_output_dir,
+                                &all_moments,
+                            )?;
+                            app.handle_message(AppMessage::RequestMomentReview);
+                        } else {
+                            app.screen = AppScreen::FormatConfirm;
+                        }
                     } else {
                         app.log(LogLevel::Error, "Invalid Media URL".to_string());
                         app.input.clear();
@@ -635,8 +1006,56 @@ async fn run_app(
                     });
                 }
             }
-            AppScreen::ShortsConfirm(_) => {
+            AppScreen::MomentReview => {
+                if app.review_video.is_none() {
+                    // Frames of the moments come from the low-res download, or from
+                    // the video itself when the moments were imported for a local file
+                    app.review_video = Some(if Path::new(&url).is_file() {
+                        url.clone()
+                    } else {
+                        format!("{}/low_res.mp4", temp_dir)
+                    });
+                    app.refresh_review_frames();
+                }
+                // One load at a time; moments highlighted meanwhile are loaded next
+                if !app.review_frames_running && std::mem::take(&mut app.review_frames_requested) {
+                    let moment = app.moments.get(app.review_index);
+                    if let Some((video, moment)) = app.review_video.clone().zip(moment) {
+                        let tx_clone = tx.clone();
+                        let (start, end) = (moment.start_time.clone(), moment.end_time.clone());
+                        let generation = app.export_preview_generation;
+                        app.review_frames_running = true;
+
+                        tokio::task::spawn_blocking(move || {
+                            let frames = tui::load_review_frames(&video, &start, &end);
+                            let _ = tx_clone.send(AppMessage::ReviewFrames(
+                                generation,
+                                frames.map_err(|e| format!("{:#}", e)),
+                            ));
+                        });
+                    }
+                }
+                // Saved once the edits pause, and always before leaving the review
+                let save_due = app.review_dirty.is_some_and(|edited| {
+                    edited.elapsed() >= tui::REVIEW_SAVE_DELAY || app.confirm_response.is_some()
+                });
+                if save_due {
+                    app.review_dirty = None;
+                    // Keep the edits if the session is resumed later
+                    all_moments = app.moments.clone();
+                    let current_config = app.config.clone().unwrap_or(config.clone());
+                    let temp_json_path = format!("{}/temp.json", current_config.default_output_dir);
+                    if let Err(e) = save_session(&temp_json_path, &url, &all_moments, &temp_dir) {
+                        app.log(LogLevel::Warning, format!("Failed to save session: {}", e));
+                    }
+                    if let Err(e) =
+                        moments::save_moments(&current_config.default_output_dir, &all_moments)
+                    {
+                        app.log(LogLevel::Warning, format!("Failed to save moments: {}", e));
+                    }
+                }
                 if let Some(response) = app.confirm_response.take() {
+                    app.review_video = None;
                     if response {
                         app.log(LogLevel::Info, "Generating shorts...".to_string());
 
@@ -648,7 +1067,13 @@ async fn run_app(
                         let temp_json_path_clone =
                             format!("{}/temp.json", config_clone.default_output_dir);
                         let custom_format_clone = custom_format.clone();
-                        let moments_clone = app.moments.clone();
+                        // Moments rejected in the review are not extracted
+                        let moments_clone: Vec<VideoMoment> = app
+                            .moments
+                            .iter()
+                            .filter(|m| !m.rejected)
+                            .cloned()
+                            .collect();
                         let cancellation_token = app.cancellation_token.clone();
 
                         // Reset token
@@ -699,6 +1124,114 @@ async fn run_app(
             AppScreen::Done => {
                 // Already handled by key press
             }
+            AppScreen::ExportPreview => {
+                // One frame at a time; requests made meanwhile are rendered next
+                if !app.export_preview_running && std::mem::take(&mut app.export_preview_requested)
+                {
+                    let tx_clone = tx.clone();
+                    let layers = app
+                        .selected_plano()
+                        .map(|p| p.plano.layers.clone())
+                        .unwrap_or_default();
+                    let video_path = app.export_preview_video_path.clone();
+                    // Unique name so image viewers open the new frame
+                    let output = std::env::temp_dir()
+                        .join(format!(
+                            "yt_shortmaker_preview_{}.png",
+                            exporter::unique_stamp()
+                        ))
+                        .to_string_lossy()
+                        .to_string();
+                    app.export_preview_running = true;
+
+                    tokio::task::spawn_blocking(move || {
+                        let result = match &video_path {
+                            Some(video) => {
+                                exporter::generate_preview_from_video(video, &layers, &output)
+                            }
+                            None => exporter::generate_preview_embedded(&layers, &output),
+                        };
+                        let _ = tx_clone.send(AppMessage::ExportPreview(
+                            result.map(|_| output).map_err(|e| format!("{:#}", e)),
+                        ));
+                    });
+                }
+                if std::mem::take(&mut app.export_preview_clip_requested) {
+                    let tx_clone = tx.clone();
+                    let loaded =
+                        app.selected_plano()
+                            .cloned()
+                            .unwrap_or_else(|| exporter::LoadedPlano {
+                                path: String::new(),
+                                plano: exporter::Plano::default(),
+                            });
+                    let video_path = app.export_preview_video_path.clone();
+                    let output = std::env::temp_dir()
+                        .join(format!(
+                            "yt_shortmaker_preview_{}.mp4",
+                            exporter::unique_stamp()
+                        ))
+                        .to_string_lossy()
+                        .to_string();
+
+                    tokio::spawn(async move {
+                        let result = exporter::generate_preview_clip(
+                            video_path.as_deref(),
+                            &loaded,
+                            &output,
+                            exporter::PREVIEW_CLIP_SECONDS,
+                            Arc::new(AtomicBool::new(false)),
+                        )
+                        .await;
+                        let message = match result {
+                            Ok(()) => {
+                                if let Err(e) = open::that(&output) {
+                                    log::warn!("Failed to open preview clip: {}", e);
+                                }
+                                AppMessage::Log(
+                                    LogLevel::Success,
+                                    rust_i18n::t!("export_preview_clip_ready", path = output)
+                                        .to_string(),
+                                )
+                            }
+                            Err(e) => AppMessage::Log(
+                                LogLevel::Error,
+                                rust_i18n::t!("export_preview_error", error = format!("{:#}", e))
+                                    .to_string(),
+                            ),
+                        };
+                        let _ = tx_clone.send(message);
+                    });
+                }
+            }
+            // One preview at a time; changes made meanwhile are rendered next
+            AppScreen::PlanoEditor
+                if !app.plano_editor_preview_running
+                    && std::mem::take(&mut app.plano_editor_preview_requested) =>
+            {
+                if let Some(editor) = &app.plano_editor {
+                    let tx_clone = tx.clone();
+                    let composed = editor.composed();
+                    let video_path = app.export_preview_video_path.clone();
+                    app.plano_editor_preview_running = true;
+
+                    tokio::task::spawn_blocking(move || {
+                        let output = plano_editor::preview_path();
+                        let output_str = output.to_string_lossy().to_string();
+                        let result = composed.and_then(|plano| match &video_path {
+                            Some(video) => exporter::generate_preview_from_video(
+                                video,
+                                &plano.layers,
+                                &output_str,
+                            ),
+                            None => exporter::generate_preview_embedded(&plano.layers, &output_str),
+                        });
+                        let _ = tx_clone.send(AppMessage::EditorPreview(
+                            result.map(|_| output_str).map_err(|e| format!("{:#}", e)),
+                        ));
+                    });
+                }
+            }
             AppScreen::ExportProcessing => {
                 if !export_processing_started {
                     export_processing_started = true;
@@ -706,7 +1239,9 @@ async fn run_app(
                     // Get export data from app state
                     let tx_clone = tx.clone();
                     let folders = app.export_clip_folders.clone();
-                    let plano = app.export_plano.clone();
+                    let planos = app.export_planos.clone();
+                    let default_profile =
+                        app.config.clone().unwrap_or(config.clone()).export_profile;
                     let output_dir = app.export_output_dir.clone().unwrap_or_default();
                     let cancellation_token = app.cancellation_token.clone();
 
@@ -727,7 +1262,8 @@ async fn run_app(
 
                         let result = exporter::export_batch(
                             &folders,
-                            &plano,
+                            &planos,
+                            &default_profile,
                             &output_dir,
                             Some(Box::new(move |current, total, name| {
                                 let progress = if total > 0 {
@@ -793,6 +1329,10 @@ fn load_config_with_fallback() -> Result<AppConfig> {
                     cookies_path: "./cookies.json".to_string(),
                     shorts_config: config::ShortsConfig::default(),
                     use_fast_model: true,
+                    extract_profile: config::default_extract_profile(),
+                    export_profile: encoding::ExportProfile::default(),
+                    jump_cut: config::JumpCutConfig::default(),
+                    export_timelines: false,
 
                     active_encryption_mode: security::EncryptionMode::Password,
                     active_password: None,
@@ -1074,21 +1614,8 @@ async fn run_processing(
         format!("Found {} total moments", all_moments.len()),
     ));
 
-    // Also create human-readable text file
-    let moments_txt = format!("{}/moments.txt", config.default_output_dir);
-    let mut txt_content = String::new();
-    txt_content.push_str("=== YouTube Shorts Moments ===\n\n");
-    for (i, moment) in all_moments.iter().enumerate() {
-        txt_content.push_str(&format!(
-            "{}. [{} - {}] ({})\n   {}\n\n",
-            i + 1,
-            moment.start_time,
-            moment.end_time,
-            moment.category,
-            moment.description
-        ));
-    }
-    fs::write(&moments_txt, &txt_content)?;
+    // Also keep them in the output folder (moments.json and a human-readable moments.txt)
+    moments::save_moments(&config.default_output_dir, &all_moments)?;
 
     if all_moments.is_empty() {
         let _ = tx.send(AppMessage::Log(
@@ -1104,7 +1631,7 @@ async fn run_processing(
     let generate_shorts = config.extract_shorts_when_finished_moments;
 
     if !generate_shorts {
-        let _ = tx.send(AppMessage::RequestShortsConfirm(all_moments.len()));
+        let _ = tx.send(AppMessage::RequestMomentReview);
         return Ok((all_moments, None));
     }
 
@@ -1132,7 +1659,12 @@ async fn run_extraction(
         "High-res download...".to_string(),
     ));
 
-    let source_high_res = format!("{}/high_res.mp4", temp_dir);
+    // A local video is cut directly, a URL is downloaded first
+    let source_high_res = if Path::new(&url).is_file() {
+        url.clone()
+    } else {
+        format!("{}/high_res.mp4", temp_dir)
+    };
     if !Path::new(&source_high_res).exists() {
         video::download_high_res(
             &url,
@@ -1157,8 +1689,14 @@ async fn run_extraction(
     let shorts_dir = format!("{}/shorts_{}", config.default_output_dir, shorts_session);
     fs::create_dir_all(&shorts_dir)?;
 
-    let total_clips = all_moments.len();
-    for (i, moment) in all_moments.iter().enumerate() {
+    // Moments rejected in the review are kept in the session but not cut
+    let accepted: Vec<VideoMoment> = all_moments
+        .iter()
+        .filter(|m| !m.rejected)
+        .cloned()
+        .collect();
+    let total_clips = accepted.len();
+    for (i, moment) in accepted.iter().enumerate() {
         if cancellation_token.load(Ordering::Relaxed) {
             let _ = tx.send(AppMessage::Status("Cancelled".to_string()));
             let _ = tx.send(AppMessage::Log(
@@ -1187,6 +1725,7 @@ async fn run_extraction(
             &moment.start_time,
             &moment.end_time,
             &output_path,
+            &config.extract_profile,
             cancellation_token.clone(),
         )
         .await
@@ -1200,6 +1739,70 @@ async fn run_extraction(
                 LogLevel::Success,
                 format!("Created: short_{}.mp4", i + 1),
             ));
+
+            // Moment fields for `{{category}}`-style plano variables
+            if let Err(e) = variables::save_moment_vars(&output_path, moment) {
+                let _ = tx.send(AppMessage::Log(
+                    LogLevel::Warning,
+                    format!("Failed to save moment info for clip {}: {}", i + 1, e),
+                ));
+            }
+
+            if config.jump_cut.enabled {
+                match jumpcut::apply(
+                    &output_path,
+                    moment,
+                    &config.jump_cut,
+                    &config.extract_profile,
+                    cancellation_token.clone(),
+                )
+                .await
+                {
+                    Ok(removed) => {
+                        let _ = tx.send(AppMessage::Log(
+                            LogLevel::Info,
+                            format!(
+                                "Removed {:.1}s of silence from short_{}.mp4",
+                                removed,
+                                i + 1
+                            ),
+                        ));
+                    }
+                    Err(e) => {
+                        let _ = tx.send(AppMessage::Log(
+                            LogLevel::Warning,
+                            format!("Jump cut failed for clip {}: {}", i + 1, e),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if config.export_timelines {
+        // The timelines point at the source, so a download is kept next to the shorts
+        let source = if source_high_res == url {
+            source_high_res.clone()
+        } else {
+            let kept = format!("{}/source.mp4", shorts_dir);
+            if fs::rename(&source_high_res, &kept).is_err() {
+                fs::copy(&source_high_res, &kept)?;
+            }
+            kept
+        };
+        match nle::export_timelines(&shorts_dir, &source, &accepted) {
+            Ok(_) => {
+                let _ = tx.send(AppMessage::Log(
+                    LogLevel::Success,
+                    "Timelines saved (EDL, FCPXML, Premiere XML, OTIO)".to_string(),
+                ));
+            }
+            Err(e) => {
+                let _ = tx.send(AppMessage::Log(
+                    LogLevel::Warning,
+                    format!("Failed to export timelines: {}", e),
+                ));
+            }
         }
     }
 
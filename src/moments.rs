@@ -0,0 +1,304 @@
+//! Moment files for YT ShortMaker
+//! Writes the moments found by the analysis and reads moments back from files
+//!
+//! Besides `moments.json` (and the session `temp.json`), moments can come from
+//! a CSV file (`start,end,category,description`) or a plain list typed by hand,
+//! one moment per line: `00:01:10-00:01:40 Funny: the fall`. The `moments.txt`
+//! written next to `moments.json` is read back the same way.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::{SessionState, VideoMoment};
+use crate::video::{format_seconds_to_timestamp, parse_timestamp_to_seconds};
+
+/// Category given to moments whose label has none
+const DEFAULT_CATEGORY: &str = "Other";
+
+/// Write `moments.json` and the human-readable `moments.txt` to `dir`
+pub fn save_moments(dir: &str, moments: &[VideoMoment]) -> Result<PathBuf> {
+    let json_path = Path::new(dir).join("moments.json");
+    fs::write(&json_path, serde_json::to_string_pretty(moments)?)
+        .with_context(|| format!("Failed to write {:?}", json_path))?;
+
+    let mut txt_content = String::new();
+    txt_content.push_str("=== YouTube Shorts Moments ===\n\n");
+    for (i, moment) in moments.iter().enumerate() {
+        txt_content.push_str(&format!(
+            "{}. [{} - {}] ({})\n   {}\n\n",
+            i + 1,
+            moment.start_time,
+            moment.end_time,
+            moment.category,
+            moment.description
+        ));
+    }
+    fs::write(Path::new(dir).join("moments.txt"), &txt_content)?;
+    Ok(json_path)
+}
+
+/// Read moments from a JSON, CSV or plain text file
+pub fn load_moments(path: &str) -> Result<Vec<VideoMoment>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read moments: {}", path))?;
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let moments = match extension.as_str() {
+        "json" => parse_json(&content)?,
+        "csv" => parse_csv(&content)?,
+        _ => parse_text(&content)?,
+    };
+    if moments.is_empty() {
+        bail!("No moments found in {}", path);
+    }
+    Ok(moments)
+}
+
+/// A `moments.json` array or a session file (`temp.json`)
+fn parse_json(content: &str) -> Result<Vec<VideoMoment>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MomentsFile {
+        List(Vec<VideoMoment>),
+        Session(SessionState),
+    }
+
+    let moments = match serde_json::from_str(content)? {
+        MomentsFile::List(moments) => moments,
+        MomentsFile::Session(session) => session.moments,
+    };
+    moments
+        .into_iter()
+        .enumerate()
+        .map(|(i, moment)| {
+            let (start_time, end_time) = parse_range(&moment.start_time, &moment.end_time)
+                .with_context(|| format!("Moment {}", i + 1))?;
+            Ok(VideoMoment {
+                start_time,
+                end_time,
+                ..moment
+            })
+        })
+        .collect()
+}
+
+/// `start,end,category,description` rows; a header row is skipped
+/// With three columns the last one is a label like in plain lists
+fn parse_csv(content: &str) -> Result<Vec<VideoMoment>> {
+    let mut moments = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if fields.len() < 2 || (moments.is_empty() && normalize_timestamp(&fields[0]).is_err()) {
+            if moments.is_empty() && number == 0 {
+                continue; // Header
+            }
+            bail!(
+                "Line {}: expected start,end,category,description",
+                number + 1
+            );
+        }
+
+        let (start_time, end_time) =
+            parse_range(&fields[0], &fields[1]).with_context(|| format!("Line {}", number + 1))?;
+        let (category, description) = match fields.len() {
+            2 => (DEFAULT_CATEGORY.to_string(), String::new()),
+            3 => split_label(&fields[2]),
+            _ => (fields[2].clone(), fields[3..].join(",")),
+        };
+        moments.push(moment(start_time, end_time, category, description));
+    }
+    Ok(moments)
+}
+
+/// Fields of a CSV line, with `"quoted, fields"` and `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// One moment per line: `start-end label`
+/// The `N. [start - end] (Category)` lines of `moments.txt` work too, with the
+/// description on the indented line below; other lines are ignored
+fn parse_text(content: &str) -> Result<Vec<VideoMoment>> {
+    let mut moments: Vec<VideoMoment> = Vec::new();
+    let mut awaiting_description = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if awaiting_description && line.starts_with(char::is_whitespace) {
+            // Description line of `moments.txt`
+            if let Some(last) = moments.last_mut() {
+                last.description = trimmed.to_string();
+            }
+            awaiting_description = false;
+            continue;
+        }
+        if let Some(parsed) = parse_text_line(trimmed) {
+            let parsed = parsed?;
+            awaiting_description = parsed.description.is_empty() && trimmed.contains("] (");
+            moments.push(parsed);
+        }
+    }
+    Ok(moments)
+}
+
+/// Moment on a line, None when the line does not start with a time range
+fn parse_text_line(line: &str) -> Option<Result<VideoMoment>> {
+    // Optional "12." numbering and [brackets] around the range
+    let line = match line.split_once(". ") {
+        Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => line,
+    };
+    let is_time = |c: char| c.is_ascii_digit() || c == ':' || c == '.';
+    let (start, rest) = line.trim_start_matches('[').split_once('-')?;
+    let start = start.trim();
+    if start.is_empty() || !start.chars().all(is_time) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (end, label) = rest.split_at(rest.find(|c: char| !is_time(c)).unwrap_or(rest.len()));
+    if end.is_empty() {
+        return None;
+    }
+    let label = label.trim().trim_start_matches(']').trim();
+
+    Some(parse_range(start, end).map(|(start_time, end_time)| {
+        let (category, description) = match label.strip_prefix('(') {
+            Some(rest) => match rest.split_once(')') {
+                Some((category, rest)) => (category.trim().to_string(), rest.trim().to_string()),
+                None => split_label(label),
+            },
+            None => split_label(label),
+        };
+        moment(start_time, end_time, category, description)
+    }))
+}
+
+/// "Category: description", or just a description
+fn split_label(label: &str) -> (String, String) {
+    match label.split_once(':') {
+        Some((category, description)) if !category.trim().is_empty() => {
+            (category.trim().to_string(), description.trim().to_string())
+        }
+        _ => (DEFAULT_CATEGORY.to_string(), label.trim().to_string()),
+    }
+}
+
+fn moment(
+    start_time: String,
+    end_time: String,
+    category: String,
+    description: String,
+) -> VideoMoment {
+    VideoMoment {
+        start_time,
+        end_time,
+        category,
+        description,
+        dialogue: Vec::new(),
+        rejected: false,
+    }
+}
+
+/// Both ends as HH:MM:SS, the start before the end
+fn parse_range(start: &str, end: &str) -> Result<(String, String)> {
+    let (start, end) = (normalize_timestamp(start)?, normalize_timestamp(end)?);
+    if parse_timestamp_to_seconds(&start)? >= parse_timestamp_to_seconds(&end)? {
+        bail!("The start ({}) must be before the end ({})", start, end);
+    }
+    Ok((start, end))
+}
+
+/// Seconds, MM:SS or HH:MM:SS (fractions of a second dropped) as HH:MM:SS
+fn normalize_timestamp(timestamp: &str) -> Result<String> {
+    let timestamp = timestamp.trim();
+    let invalid = || anyhow!("Invalid timestamp: {:?}", timestamp);
+    let parts: Vec<&str> = timestamp.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+
+    let mut seconds = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value = if i + 1 == parts.len() {
+            part.parse::<f64>()
+                .ok()
+                .filter(|v| *v >= 0.0)
+                .map(|v| v as u64)
+        } else {
+            part.parse::<u64>().ok()
+        };
+        seconds = seconds * 60 + value.ok_or_else(invalid)?;
+    }
+    Ok(format_seconds_to_timestamp(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_moment_lists() {
+        let text = "# Picked by hand\n\
+            1:10-1:40 Funny: the fall\n\
+            00:02:00 - 00:02:30.5 nice save\n\
+            0:03 -0:09 3 kills\n\
+            not a moment\n";
+        let moments = parse_text(text).unwrap();
+        assert_eq!(moments.len(), 3);
+        assert_eq!(moments[0].start_time, "00:01:10");
+        assert_eq!(moments[0].category, "Funny");
+        assert_eq!(moments[0].description, "the fall");
+        assert_eq!(moments[1].end_time, "00:02:30");
+        assert_eq!(moments[1].category, "Other");
+        assert_eq!(moments[2].description, "3 kills");
+        assert!(parse_text("0:40-0:10 backwards").is_err());
+
+        // moments.txt round trip
+        let dir = std::env::temp_dir().join(format!("ytsm_moments_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let json = save_moments(&dir.to_string_lossy(), &moments).unwrap();
+        let reloaded = parse_text(&fs::read_to_string(dir.join("moments.txt")).unwrap()).unwrap();
+        assert_eq!(reloaded.len(), 3);
+        assert_eq!(reloaded[0].category, "Funny");
+        assert_eq!(reloaded[0].description, "the fall");
+        assert_eq!(reloaded[1].start_time, "00:02:00");
+        let from_json = load_moments(&json.to_string_lossy()).unwrap();
+        assert_eq!(from_json[1].description, "nice save");
+        fs::remove_dir_all(&dir).unwrap();
+
+        let csv = "start,end,category,description\n\
+            70,100,Funny,\"the fall, again\"\n\
+            2:00,2:30,\"Epic: \"\"clutch\"\"\"\n";
+        let moments = parse_csv(csv).unwrap();
+        assert_eq!(moments[0].end_time, "00:01:40");
+        assert_eq!(moments[0].description, "the fall, again");
+        assert_eq!(moments[1].category, "Epic");
+        assert_eq!(moments[1].description, "\"clutch\"");
+    }
+}
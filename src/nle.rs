@@ -0,0 +1,569 @@
+//! NLE timelines for YT ShortMaker
+//! Exports moments as CMX3600 EDL, FCPXML, Premiere XML and OpenTimelineIO
+//!
+//! Every moment becomes a clip of the high-res source, placed back to back on
+//! the timeline, with a marker holding its category and description. Editors
+//! (DaVinci Resolve, Premiere, Final Cut) relink the clips to the source file.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::shorts::get_video_resolution;
+use crate::types::VideoMoment;
+use crate::video::{
+    get_frame_rate, get_video_duration_precise, has_audio_stream, parse_timestamp_to_seconds,
+};
+
+const TIMELINE_NAME: &str = "YT ShortMaker Moments";
+
+/// Timeline files written by `export_timelines`
+const TIMELINE_FILES: [&str; 4] = [
+    "timeline.edl",
+    "timeline.fcpxml",
+    "timeline.xml",
+    "timeline.otio",
+];
+
+/// The video every clip of the timeline comes from
+pub struct TimelineSource {
+    /// File name shown in the editor
+    pub name: String,
+    /// Absolute `file://` URL
+    pub url: String,
+    /// Frame rate as a fraction (30000/1001 for 29.97)
+    pub rate: (u32, u32),
+    pub width: u32,
+    pub height: u32,
+    /// Length in frames
+    pub duration: u64,
+    pub has_audio: bool,
+}
+
+impl TimelineSource {
+    /// Probe a video file; values ffprobe can't read fall back to 30 fps,
+    /// 1920x1080 and the end of the last moment
+    pub fn probe(path: &str, moments: &[VideoMoment]) -> Result<Self> {
+        let rate = get_frame_rate(path).unwrap_or((30, 1));
+        let (width, height) = get_video_resolution(path).unwrap_or((1920, 1080));
+        let seconds = match get_video_duration_precise(path) {
+            Ok(duration) => duration,
+            Err(_) => moments
+                .iter()
+                .map(|m| parse_timestamp_to_seconds(&m.end_time))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .max()
+                .unwrap_or(0) as f64,
+        };
+
+        Ok(Self {
+            name: Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string()),
+            url: file_url(path),
+            rate,
+            width,
+            height,
+            duration: (seconds * rate.0 as f64 / rate.1 as f64).round() as u64,
+            has_audio: has_audio_stream(path),
+        })
+    }
+
+    /// Frames in `seconds` of video
+    fn frames(&self, seconds: u64) -> u64 {
+        let (num, den) = (self.rate.0 as u64, self.rate.1 as u64);
+        (seconds * num + den / 2) / den
+    }
+
+    /// Frames per second of the timecode (30 for 29.97 non-drop)
+    fn timebase(&self) -> u64 {
+        (self.rate.0 as u64).div_ceil(self.rate.1 as u64)
+    }
+
+    fn ntsc(&self) -> bool {
+        self.rate.1 == 1001
+    }
+
+    fn fps(&self) -> f64 {
+        self.rate.0 as f64 / self.rate.1 as f64
+    }
+}
+
+/// A moment placed on the timeline, in frames
+struct Clip<'a> {
+    moment: &'a VideoMoment,
+    /// First frame in the source
+    start: u64,
+    duration: u64,
+    /// First frame on the timeline
+    offset: u64,
+}
+
+impl Clip<'_> {
+    fn name(&self, index: usize) -> String {
+        format!("{}. {}", index + 1, self.moment.category)
+    }
+}
+
+/// Moments rejected in the review are left out
+fn clips<'a>(source: &TimelineSource, moments: &'a [VideoMoment]) -> Result<Vec<Clip<'a>>> {
+    let mut offset = 0;
+    moments
+        .iter()
+        .filter(|moment| !moment.rejected)
+        .map(|moment| {
+            let start = source.frames(parse_timestamp_to_seconds(&moment.start_time)?);
+            let end = source.frames(parse_timestamp_to_seconds(&moment.end_time)?);
+            let clip = Clip {
+                moment,
+                start,
+                duration: end.saturating_sub(start),
+                offset,
+            };
+            offset += clip.duration;
+            Ok(clip)
+        })
+        .collect()
+}
+
+/// Write the EDL, FCPXML, Premiere XML and OTIO timelines of `moments` to `dir`
+pub fn export_timelines(dir: &str, source: &str, moments: &[VideoMoment]) -> Result<Vec<PathBuf>> {
+    let source = TimelineSource::probe(source, moments)?;
+    let clips = clips(&source, moments)?;
+    let contents = [
+        edl(&source, &clips),
+        fcpxml(&source, &clips),
+        premiere_xml(&source, &clips),
+        serde_json::to_string_pretty(&otio(&source, &clips))?,
+    ];
+
+    fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (file, content) in TIMELINE_FILES.iter().zip(contents) {
+        let path = Path::new(dir).join(file);
+        fs::write(&path, content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Marker colour for a category (Avid locator and OTIO names)
+fn marker_color(category: &str) -> &'static str {
+    match category.to_lowercase().as_str() {
+        "funny" => "YELLOW",
+        "interesting" => "BLUE",
+        "incredible play" => "GREEN",
+        "cinematic" => "MAGENTA",
+        _ => "CYAN",
+    }
+}
+
+/// HH:MM:SS:FF non-drop timecode
+fn timecode(frames: u64, timebase: u64) -> String {
+    let seconds = frames / timebase;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60,
+        frames % timebase
+    )
+}
+
+/// Single-line text for EDL comments
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// CMX3600 EDL, the record timecode starting at 01:00:00:00
+/// Markers are `* LOC:` locators, read by Resolve and Avid
+fn edl(source: &TimelineSource, clips: &[Clip]) -> String {
+    let timebase = source.timebase();
+    let record_start = 3600 * timebase;
+    let channels = if source.has_audio { "AA/V" } else { "V" };
+
+    let mut edl = format!("TITLE: {}\nFCM: NON-DROP FRAME\n\n", TIMELINE_NAME);
+    for (i, clip) in clips.iter().enumerate() {
+        let record_in = record_start + clip.offset;
+        edl.push_str(&format!(
+            "{:03}  AX       {:<5} C        {} {} {} {}\n",
+            i + 1,
+            channels,
+            timecode(clip.start, timebase),
+            timecode(clip.start + clip.duration, timebase),
+            timecode(record_in, timebase),
+            timecode(record_in + clip.duration, timebase)
+        ));
+        edl.push_str(&format!("* FROM CLIP NAME: {}\n", source.name));
+        edl.push_str(&format!(
+            "* LOC: {} {:<7} {}: {}\n\n",
+            timecode(record_in, timebase),
+            marker_color(&clip.moment.category),
+            one_line(&clip.moment.category),
+            one_line(&clip.moment.description)
+        ));
+    }
+    edl
+}
+
+/// Text safe for XML content and attributes
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// FCPXML rational time of `frames`, e.g. "1001/30000s"
+fn rational_time(source: &TimelineSource, frames: u64) -> String {
+    if frames == 0 {
+        return "0s".to_string();
+    }
+    let (num, den) = (frames * source.rate.1 as u64, source.rate.0 as u64);
+    let divisor = gcd(num, den);
+    if divisor == den {
+        format!("{}s", num / divisor)
+    } else {
+        format!("{}/{}s", num / divisor, den / divisor)
+    }
+}
+
+/// FCPXML 1.9 project, also read by DaVinci Resolve
+fn fcpxml(source: &TimelineSource, clips: &[Clip]) -> String {
+    let time = |frames| rational_time(source, frames);
+    let total: u64 = clips.iter().map(|c| c.duration).sum();
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE fcpxml>\n<fcpxml version=\"1.9\">\n",
+    );
+    xml.push_str("  <resources>\n");
+    xml.push_str(&format!(
+        "    <format id=\"r1\" frameDuration=\"{}/{}s\" width=\"{}\" height=\"{}\"/>\n",
+        source.rate.1, source.rate.0, source.width, source.height
+    ));
+    xml.push_str(&format!(
+        "    <asset id=\"r2\" name=\"{}\" start=\"0s\" duration=\"{}\" hasVideo=\"1\" hasAudio=\"{}\" format=\"r1\">\n",
+        xml_escape(&source.name),
+        time(source.duration),
+        source.has_audio as u8
+    ));
+    xml.push_str(&format!(
+        "      <media-rep kind=\"original-media\" src=\"{}\"/>\n    </asset>\n  </resources>\n",
+        xml_escape(&source.url)
+    ));
+    xml.push_str(&format!(
+        "  <library>\n    <event name=\"{0}\">\n      <project name=\"{0}\">\n",
+        TIMELINE_NAME
+    ));
+    xml.push_str(&format!(
+        "        <sequence format=\"r1\" duration=\"{}\" tcStart=\"0s\" tcFormat=\"NDF\">\n          <spine>\n",
+        time(total)
+    ));
+    for (i, clip) in clips.iter().enumerate() {
+        xml.push_str(&format!(
+            "            <asset-clip ref=\"r2\" name=\"{}\" offset=\"{}\" start=\"{}\" duration=\"{}\" format=\"r1\">\n",
+            xml_escape(&clip.name(i)),
+            time(clip.offset),
+            time(clip.start),
+            time(clip.duration)
+        ));
+        // Marker times are in the clip's source time
+        xml.push_str(&format!(
+            "              <marker start=\"{}\" duration=\"{}\" value=\"{}\" note=\"{}\"/>\n            </asset-clip>\n",
+            time(clip.start),
+            time(clip.duration),
+            xml_escape(&clip.moment.category),
+            xml_escape(&clip.moment.description)
+        ));
+    }
+    xml.push_str("          </spine>\n        </sequence>\n      </project>\n    </event>\n  </library>\n</fcpxml>\n");
+    xml
+}
+
+/// `<rate>` element of Premiere XML
+fn xml_rate(source: &TimelineSource) -> String {
+    format!(
+        "<rate><timebase>{}</timebase><ntsc>{}</ntsc></rate>",
+        source.timebase(),
+        if source.ntsc() { "TRUE" } else { "FALSE" }
+    )
+}
+
+/// Premiere XML (xmeml 4), the format of Premiere's "Import" and "Export > Final Cut Pro XML"
+/// Markers go on the sequence, at the start of each clip
+fn premiere_xml(source: &TimelineSource, clips: &[Clip]) -> String {
+    let rate = xml_rate(source);
+    let total: u64 = clips.iter().map(|c| c.duration).sum();
+    let file = format!(
+        "<file id=\"file-1\"><name>{}</name><pathurl>{}</pathurl>{}<duration>{}</duration>\
+         <media><video><samplecharacteristics>{}<width>{}</width><height>{}</height></samplecharacteristics></video>{}</media></file>",
+        xml_escape(&source.name),
+        xml_escape(&source.url),
+        rate,
+        source.duration,
+        rate,
+        source.width,
+        source.height,
+        if source.has_audio {
+            "<audio><channelcount>2</channelcount></audio>"
+        } else {
+            ""
+        }
+    );
+
+    let track = |kind: &str| {
+        let mut track = String::from("        <track>\n");
+        for (i, clip) in clips.iter().enumerate() {
+            track.push_str(&format!(
+                "          <clipitem id=\"clipitem-{}{}\"><name>{}</name><duration>{}</duration>{}\
+                 <start>{}</start><end>{}</end><in>{}</in><out>{}</out>{}",
+                kind,
+                i + 1,
+                xml_escape(&clip.name(i)),
+                source.duration,
+                rate,
+                clip.offset,
+                clip.offset + clip.duration,
+                clip.start,
+                clip.start + clip.duration,
+                // The full file description only once, then references to it
+                if i == 0 && kind == "v" {
+                    file.clone()
+                } else {
+                    "<file id=\"file-1\"/>".to_string()
+                }
+            ));
+            if kind == "a" {
+                track.push_str(
+                    "<sourcetrack><mediatype>audio</mediatype><trackindex>1</trackindex></sourcetrack>",
+                );
+            }
+            track.push_str("</clipitem>\n");
+        }
+        track.push_str("        </track>\n");
+        track
+    };
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE xmeml>\n<xmeml version=\"4\">\n",
+    );
+    xml.push_str(&format!(
+        "  <sequence id=\"sequence-1\">\n    <name>{}</name>\n    <duration>{}</duration>\n    {}\n",
+        TIMELINE_NAME, total, rate
+    ));
+    xml.push_str("    <media>\n      <video>\n");
+    xml.push_str(&format!(
+        "        <format><samplecharacteristics>{}<width>{}</width><height>{}</height></samplecharacteristics></format>\n",
+        rate, source.width, source.height
+    ));
+    xml.push_str(&track("v"));
+    xml.push_str("      </video>\n");
+    if source.has_audio {
+        xml.push_str("      <audio>\n");
+        xml.push_str(&track("a"));
+        xml.push_str("      </audio>\n");
+    }
+    xml.push_str("    </media>\n");
+    for clip in clips {
+        xml.push_str(&format!(
+            "    <marker><name>{}</name><comment>{}</comment><in>{}</in><out>{}</out></marker>\n",
+            xml_escape(&clip.moment.category),
+            xml_escape(&clip.moment.description),
+            clip.offset,
+            clip.offset + clip.duration
+        ));
+    }
+    xml.push_str("  </sequence>\n</xmeml>\n");
+    xml
+}
+
+/// OpenTimelineIO `.otio` document
+fn otio(source: &TimelineSource, clips: &[Clip]) -> Value {
+    let fps = source.fps();
+    let time = |frames: u64| json!({ "OTIO_SCHEMA": "RationalTime.1", "rate": fps, "value": frames as f64 });
+    let range = |start: u64, duration: u64| {
+        json!({
+            "OTIO_SCHEMA": "TimeRange.1",
+            "start_time": time(start),
+            "duration": time(duration),
+        })
+    };
+
+    let track = |kind: &str, with_markers: bool| {
+        let children: Vec<Value> = clips
+            .iter()
+            .enumerate()
+            .map(|(i, clip)| {
+                let markers = if with_markers {
+                    vec![json!({
+                        "OTIO_SCHEMA": "Marker.2",
+                        "name": clip.moment.category,
+                        "color": marker_color(&clip.moment.category),
+                        "marked_range": range(clip.start, clip.duration),
+                        "comment": clip.moment.description,
+                        "metadata": {},
+                    })]
+                } else {
+                    Vec::new()
+                };
+                json!({
+                    "OTIO_SCHEMA": "Clip.1",
+                    "name": clip.name(i),
+                    "source_range": range(clip.start, clip.duration),
+                    "media_reference": {
+                        "OTIO_SCHEMA": "ExternalReference.1",
+                        "name": source.name,
+                        "target_url": source.url,
+                        "available_range": range(0, source.duration),
+                        "metadata": {},
+                    },
+                    "effects": [],
+                    "markers": markers,
+                    "metadata": {},
+                })
+            })
+            .collect();
+        json!({
+            "OTIO_SCHEMA": "Track.1",
+            "name": kind,
+            "kind": kind,
+            "children": children,
+            "effects": [],
+            "markers": [],
+            "source_range": null,
+            "metadata": {},
+        })
+    };
+
+    let mut tracks = vec![track("Video", true)];
+    if source.has_audio {
+        tracks.push(track("Audio", false));
+    }
+    json!({
+        "OTIO_SCHEMA": "Timeline.1",
+        "name": TIMELINE_NAME,
+        "global_start_time": null,
+        "metadata": {},
+        "tracks": {
+            "OTIO_SCHEMA": "Stack.1",
+            "name": "tracks",
+            "children": tracks,
+            "effects": [],
+            "markers": [],
+            "source_range": null,
+            "metadata": {},
+        },
+    })
+}
+
+/// Absolute `file://` URL of a path, percent-encoded
+fn file_url(path: &str) -> String {
+    let absolute = fs::canonicalize(path).unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| PathBuf::from(path))
+    });
+    let mut path = absolute.to_string_lossy().replace('\\', "/");
+    // Windows verbatim prefix added by canonicalize
+    if let Some(stripped) = path.strip_prefix("//?/") {
+        path = stripped.to_string();
+    }
+    if !path.starts_with('/') {
+        path.insert(0, '/');
+    }
+
+    let mut url = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_formats() {
+        let moment = |start: &str, end: &str, category: &str, description: &str| VideoMoment {
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            description: description.to_string(),
+            dialogue: Vec::new(),
+            rejected: false,
+        };
+        let moments = vec![
+            moment("00:01:10", "00:01:40", "Funny", "the fall"),
+            VideoMoment {
+                rejected: true,
+                ..moment("00:01:45", "00:01:55", "Other", "rejected in the review")
+            },
+            moment(
+                "00:02:00",
+                "00:02:10",
+                "Incredible Play",
+                "3 kills & a <clutch>",
+            ),
+        ];
+        let source = TimelineSource {
+            name: "high res.mp4".to_string(),
+            url: "file:///videos/high%20res.mp4".to_string(),
+            rate: (30000, 1001),
+            width: 1920,
+            height: 1080,
+            duration: 9000,
+            has_audio: true,
+        };
+        let clips = clips(&source, &moments).unwrap();
+        assert_eq!(clips[0].start, 2098); // 70s at 29.97
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[1].offset, clips[0].duration);
+
+        let edl = edl(&source, &clips);
+        assert!(edl.contains(
+            "001  AX       AA/V  C        00:01:09:28 00:01:39:27 01:00:00:00 01:00:29:29\n"
+        ));
+        assert!(edl.contains("* LOC: 01:00:00:00 YELLOW  Funny: the fall\n"));
+        assert!(edl.contains("* LOC: 01:00:29:29 GREEN   Incredible Play: 3 kills & a <clutch>\n"));
+
+        let fcpxml = fcpxml(&source, &clips);
+        assert!(fcpxml.contains("frameDuration=\"1001/30000s\""));
+        assert!(fcpxml.contains("<marker start=\"1050049/15000s\""));
+        assert!(fcpxml.contains("note=\"3 kills &amp; a &lt;clutch&gt;\""));
+
+        let xml = premiere_xml(&source, &clips);
+        assert!(xml.contains("<timebase>30</timebase><ntsc>TRUE</ntsc>"));
+        assert!(xml.contains("<pathurl>file:///videos/high%20res.mp4</pathurl>"));
+        assert_eq!(xml.matches("<file id=\"file-1\">").count(), 1);
+        assert!(xml.contains("<marker><name>Funny</name><comment>the fall</comment><in>0</in>"));
+
+        let otio = otio(&source, &clips);
+        let video = &otio["tracks"]["children"][0];
+        let marker = &video["children"][1]["markers"][0];
+        assert_eq!(marker["name"], "Incredible Play");
+        assert_eq!(marker["comment"], "3 kills & a <clutch>");
+        assert_eq!(
+            video["children"][0]["source_range"]["start_time"]["value"],
+            2098.0
+        );
+        assert_eq!(otio["tracks"]["children"][1]["kind"], "Audio");
+    }
+}
@@ -0,0 +1,479 @@
+//! Plano editor for YT ShortMaker
+//! Edits the layers of a plano file field by field (used by the TUI editor screen)
+//!
+//! The editor works on the document as written in the file, not on the loaded
+//! plano: layers inherited with `extends`/`include` stay in their own files and
+//! asset paths are kept as the user wrote them.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exporter::{
+    parse_plano, save_plano, Crop, Fit, Plano, PlanoObject, PositionValue, SizeValue,
+};
+
+/// A value of a layer that can be edited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorField {
+    X,
+    Y,
+    Width,
+    Height,
+    Fit,
+    Opacity,
+    CropXFrom,
+    CropXTo,
+    CropYFrom,
+    CropYTo,
+}
+
+impl EditorField {
+    /// Name of the field as written in plano JSON
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorField::X => "x",
+            EditorField::Y => "y",
+            EditorField::Width => "width",
+            EditorField::Height => "height",
+            EditorField::Fit => "fit",
+            EditorField::Opacity => "opacity",
+            EditorField::CropXFrom => "crop.x_from",
+            EditorField::CropXTo => "crop.x_to",
+            EditorField::CropYFrom => "crop.y_from",
+            EditorField::CropYTo => "crop.y_to",
+        }
+    }
+
+    /// Fields of a layer, in display order
+    pub fn for_layer(layer: &PlanoObject) -> Vec<EditorField> {
+        use EditorField::*;
+        let mut fields = vec![X, Y, Width, Height];
+        match layer {
+            PlanoObject::Clip { .. } => {
+                fields.extend([Fit, CropXFrom, CropXTo, CropYFrom, CropYTo]);
+            }
+            PlanoObject::Image { .. } => fields.push(Opacity),
+            PlanoObject::Video { .. } => fields.extend([Fit, Opacity]),
+            PlanoObject::Shader { .. } => {}
+        }
+        fields
+    }
+
+    /// Current value of the field as text ("" for an unset crop edge)
+    pub fn value(self, layer: &PlanoObject) -> String {
+        let position = layer.position();
+        match self {
+            EditorField::X => position_text(&position.x),
+            EditorField::Y => position_text(&position.y),
+            EditorField::Width => size_text(&position.width),
+            EditorField::Height => size_text(&position.height),
+            EditorField::Fit => match layer {
+                PlanoObject::Clip { fit, .. } | PlanoObject::Video { fit, .. } => fit_text(fit),
+                _ => String::new(),
+            },
+            EditorField::Opacity => match layer {
+                PlanoObject::Image { opacity, .. } | PlanoObject::Video { opacity, .. } => {
+                    opacity.to_string()
+                }
+                _ => String::new(),
+            },
+            EditorField::CropXFrom
+            | EditorField::CropXTo
+            | EditorField::CropYFrom
+            | EditorField::CropYTo => match layer {
+                PlanoObject::Clip {
+                    crop: Some(crop), ..
+                } => crop_edge(crop, self)
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            },
+        }
+    }
+
+    /// Set the field from text typed by the user
+    pub fn set(self, layer: &mut PlanoObject, input: &str) -> Result<()> {
+        let input = input.trim();
+        match self {
+            EditorField::X | EditorField::Y => {
+                let value = parse_position(input)?;
+                let position = position_mut(layer);
+                match self {
+                    EditorField::X => position.x = value,
+                    _ => position.y = value,
+                }
+            }
+            EditorField::Width | EditorField::Height => {
+                let value = parse_size(input)?;
+                let position = position_mut(layer);
+                match self {
+                    EditorField::Width => position.width = value,
+                    _ => position.height = value,
+                }
+            }
+            EditorField::Fit => {
+                let value = match input.to_lowercase().as_str() {
+                    "cover" => Fit::Cover,
+                    "contain" => Fit::Contain,
+                    "stretch" => Fit::Stretch,
+                    _ => bail!("Fit must be cover, contain or stretch"),
+                };
+                match layer {
+                    PlanoObject::Clip { fit, .. } | PlanoObject::Video { fit, .. } => *fit = value,
+                    _ => bail!("This layer has no fit"),
+                }
+            }
+            EditorField::Opacity => {
+                let value: f32 = input
+                    .parse()
+                    .ok()
+                    .filter(|v| (0.0..=1.0).contains(v))
+                    .context("Opacity must be a number from 0.0 to 1.0")?;
+                match layer {
+                    PlanoObject::Image { opacity, .. } | PlanoObject::Video { opacity, .. } => {
+                        *opacity = value
+                    }
+                    _ => bail!("This layer has no opacity"),
+                }
+            }
+            EditorField::CropXFrom
+            | EditorField::CropXTo
+            | EditorField::CropYFrom
+            | EditorField::CropYTo => {
+                let value = match input {
+                    "" => None,
+                    _ => Some(
+                        input
+                            .parse::<i32>()
+                            .context("Crop values are whole pixels")?,
+                    ),
+                };
+                let PlanoObject::Clip { crop, .. } = layer else {
+                    bail!("Only clip layers can be cropped");
+                };
+                let edges = crop.get_or_insert_with(Crop::default);
+                match self {
+                    EditorField::CropXFrom => edges.x_from = value,
+                    EditorField::CropXTo => edges.x_to = value,
+                    EditorField::CropYFrom => edges.y_from = value,
+                    _ => edges.y_to = value,
+                }
+                if !edges.is_specified() {
+                    *crop = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Next value for fields with a fixed set of choices (Enter cycles them)
+    pub fn next_choice(self, layer: &PlanoObject) -> Option<String> {
+        if self != EditorField::Fit {
+            return None;
+        }
+        let next = match self.value(layer).as_str() {
+            "cover" => "contain",
+            "contain" => "stretch",
+            _ => "cover",
+        };
+        Some(next.to_string())
+    }
+}
+
+fn position_text(value: &PositionValue) -> String {
+    match value {
+        PositionValue::Pixels(px) => px.to_string(),
+        PositionValue::Keyword(kw) => kw.clone(),
+    }
+}
+
+fn size_text(value: &SizeValue) -> String {
+    match value {
+        SizeValue::Pixels(px) => px.to_string(),
+        SizeValue::Keyword(kw) => kw.clone(),
+    }
+}
+
+fn fit_text(fit: &Fit) -> String {
+    match fit {
+        Fit::Cover => "cover",
+        Fit::Contain => "contain",
+        Fit::Stretch => "stretch",
+    }
+    .to_string()
+}
+
+fn crop_edge(crop: &Crop, field: EditorField) -> Option<i32> {
+    match field {
+        EditorField::CropXFrom => crop.x_from,
+        EditorField::CropXTo => crop.x_to,
+        EditorField::CropYFrom => crop.y_from,
+        _ => crop.y_to,
+    }
+}
+
+/// Whether text is a percentage such as "50%"
+fn is_percent(input: &str) -> bool {
+    input
+        .strip_suffix('%')
+        .is_some_and(|pct| pct.trim().parse::<f32>().is_ok())
+}
+
+fn parse_position(input: &str) -> Result<PositionValue> {
+    if let Ok(px) = input.parse() {
+        Ok(PositionValue::Pixels(px))
+    } else if input.eq_ignore_ascii_case("center") || is_percent(input) {
+        Ok(PositionValue::Keyword(input.to_lowercase()))
+    } else {
+        bail!("Position must be pixels, \"center\" or a percentage")
+    }
+}
+
+fn parse_size(input: &str) -> Result<SizeValue> {
+    if let Ok(px) = input.parse() {
+        Ok(SizeValue::Pixels(px))
+    } else if input.eq_ignore_ascii_case("full") || is_percent(input) {
+        Ok(SizeValue::Keyword(input.to_lowercase()))
+    } else {
+        bail!("Size must be pixels, \"full\" or a percentage")
+    }
+}
+
+fn position_mut(layer: &mut PlanoObject) -> &mut crate::exporter::Position {
+    match layer {
+        PlanoObject::Clip { position, .. }
+        | PlanoObject::Image { position, .. }
+        | PlanoObject::Shader { position, .. }
+        | PlanoObject::Video { position, .. } => position,
+    }
+}
+
+/// A full-canvas layer of the given type ("clip", "image", "video" or "shader")
+/// Image and video layers need the path of their file
+pub fn new_layer(kind: &str, path: Option<&str>) -> Result<PlanoObject> {
+    let mut value = serde_json::json!({ "type": kind, "position": {} });
+    if let Some(path) = path {
+        value["path"] = path.into();
+    }
+    if kind == "shader" {
+        value["effect"] = serde_json::json!({ "type": "blur" });
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// A plano file open for editing
+pub struct PlanoEditor {
+    /// Path the plano was selected with (file, bundle folder)
+    pub path: String,
+    /// JSON file written on save
+    file: PathBuf,
+    /// The document as written in the file
+    pub plano: Plano,
+    /// The file has `//` comments, which saving drops
+    pub has_comments: bool,
+    /// Changed since it was opened or last saved
+    pub modified: bool,
+    /// Highlighted layer
+    pub layer_index: usize,
+    /// Highlighted field of the layer
+    pub field_index: usize,
+}
+
+impl PlanoEditor {
+    /// Open a plano file or bundle folder
+    /// Zipped bundles and planos with `{{name}}` placeholders are edited as text
+    pub fn open(path: &str) -> Result<Self> {
+        let is_zip = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if is_zip {
+            bail!("Zipped planos can't be edited here, extract the bundle first");
+        }
+        let file = crate::assets::plano_file(Path::new(path))?;
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read plano file: {}", path))?;
+        if crate::variables::has_placeholders(&content) {
+            bail!("This plano uses {{{{variables}}}}, edit it as text instead");
+        }
+        let plano =
+            parse_plano(&content).with_context(|| format!("Failed to parse plano: {}", path))?;
+        let has_comments = crate::exporter::remove_js_comments(&content) != content;
+
+        Ok(Self {
+            path: path.to_string(),
+            file,
+            plano,
+            has_comments,
+            modified: false,
+            layer_index: 0,
+            field_index: 0,
+        })
+    }
+
+    /// Highlighted layer, if the plano has any
+    pub fn layer(&self) -> Option<&PlanoObject> {
+        self.plano.layers.get(self.layer_index)
+    }
+
+    /// Editable fields of the highlighted layer
+    pub fn fields(&self) -> Vec<EditorField> {
+        self.layer().map(EditorField::for_layer).unwrap_or_default()
+    }
+
+    /// Highlighted field, if any
+    pub fn field(&self) -> Option<EditorField> {
+        self.fields().get(self.field_index).copied()
+    }
+
+    /// Set the highlighted field of the highlighted layer
+    pub fn set_field(&mut self, input: &str) -> Result<()> {
+        let (Some(field), Some(layer)) =
+            (self.field(), self.plano.layers.get_mut(self.layer_index))
+        else {
+            return Ok(());
+        };
+        field.set(layer, input)
+    }
+
+    /// Highlight another layer
+    pub fn select_layer(&mut self, index: usize) {
+        self.layer_index = index.min(self.plano.layers.len().saturating_sub(1));
+        self.field_index = self.field_index.min(self.fields().len().saturating_sub(1));
+    }
+
+    /// Move the highlighted layer towards the back (`forward` false) or front
+    /// Returns false when it is already at that end
+    pub fn move_layer(&mut self, forward: bool) -> bool {
+        let index = self.layer_index;
+        let target = match forward {
+            true if index + 1 < self.plano.layers.len() => index + 1,
+            false if index > 0 && index < self.plano.layers.len() => index - 1,
+            _ => return false,
+        };
+        self.plano.layers.swap(index, target);
+        self.layer_index = target;
+        true
+    }
+
+    /// Add a layer in front of the highlighted one and highlight it
+    pub fn add_layer(&mut self, layer: PlanoObject) {
+        let index = match self.plano.layers.is_empty() {
+            true => 0,
+            false => self.layer_index + 1,
+        };
+        self.plano.layers.insert(index, layer);
+        self.field_index = 0;
+        self.select_layer(index);
+    }
+
+    /// Remove the highlighted layer
+    pub fn remove_layer(&mut self) -> bool {
+        if self.layer_index >= self.plano.layers.len() {
+            return false;
+        }
+        self.plano.layers.remove(self.layer_index);
+        self.select_layer(self.layer_index);
+        true
+    }
+
+    /// Write the document back to its file
+    pub fn save(&mut self) -> Result<()> {
+        save_plano(&self.file.to_string_lossy(), &self.plano)?;
+        self.modified = false;
+        self.has_comments = false;
+        Ok(())
+    }
+
+    /// The edited plano as it would load once saved (`extends`, `include`
+    /// and paths resolved), for previews
+    pub fn composed(&self) -> Result<Plano> {
+        crate::exporter::compose_plano_document(self.plano.clone(), &self.file)
+    }
+}
+
+/// Image the editor preview is rendered to, reused for every change
+pub fn preview_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "yt_shortmaker_editor_preview_{}.png",
+        std::process::id()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plano_editor() {
+        let dir = std::env::temp_dir().join(format!("ytsm_editor_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plano.json");
+        fs::write(
+            &path,
+            r#"{ "extends": "base.json", "layers": [
+                // Main clip
+                { "type": "clip", "position": { "x": 0, "y": "center", "width": "full", "height": 960 } }
+            ] }"#,
+        )
+        .unwrap();
+
+        fs::write(
+            dir.join("base.json"),
+            r#"[{ "type": "shader", "effect": { "type": "grayscale" },
+                  "position": { "x": 0, "y": 0, "width": "full", "height": "full" } }]"#,
+        )
+        .unwrap();
+
+        let mut editor = PlanoEditor::open(&path.to_string_lossy()).unwrap();
+        assert_eq!(EditorField::Y.value(editor.layer().unwrap()), "center");
+        // Saving would drop the "// Main clip" comment
+        assert!(editor.has_comments);
+        assert!(!editor.modified);
+
+        // Height is the 4th field of a clip, crop.x_from the 6th
+        editor.field_index = 3;
+        editor.set_field("50%").unwrap();
+        assert!(editor.set_field("tall").is_err());
+        editor.field_index = 5;
+        editor.set_field("120").unwrap();
+        editor.set_field("").unwrap();
+        assert!(matches!(
+            editor.layer(),
+            Some(PlanoObject::Clip { crop: None, .. })
+        ));
+
+        editor.add_layer(new_layer("image", Some("logo.png")).unwrap());
+        assert_eq!(editor.layer_index, 1);
+        editor.field_index = 4;
+        assert_eq!(editor.field(), Some(EditorField::Opacity));
+        assert!(editor.set_field("1.5").is_err());
+        editor.set_field("0.5").unwrap();
+        assert!(editor.move_layer(false));
+        assert!(!editor.move_layer(false));
+
+        // Previews use the edits before they are saved
+        let composed = editor.composed().unwrap();
+        assert_eq!(composed.layers.len(), 3);
+        assert!(
+            matches!(&composed.layers[1], PlanoObject::Image { path, .. } if path != "logo.png")
+        );
+        assert!(fs::read_to_string(&path).unwrap().contains("// Main clip"));
+
+        editor.save().unwrap();
+        assert!(!editor.has_comments);
+
+        // The base plano is not merged in and paths are kept as written
+        let saved = parse_plano(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.extends.as_deref(), Some("base.json"));
+        assert!(matches!(
+            &saved.layers[0],
+            PlanoObject::Image { path, opacity, .. } if path == "logo.png" && *opacity == 0.5
+        ));
+        assert_eq!(EditorField::Height.value(&saved.layers[1]), "50%");
+
+        assert!(editor.remove_layer());
+        assert_eq!(editor.plano.layers.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,396 @@
+//! Automatic reframing for YT ShortMaker
+//! Follows the subject of a clip with a smoothed crop path
+//!
+//! The clip is analysed on the CPU from small grayscale frames: the centre of
+//! the moving pixels between two frames is taken as the subject position.
+//! The raw positions are smoothed so the virtual camera pans calmly.
+
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::animation::{Easing, Track};
+
+/// Size of the frames used for analysis (positions are normalised, so the
+/// aspect ratio does not need to match the clip)
+const ANALYSIS_WIDTH: usize = 96;
+const ANALYSIS_HEIGHT: usize = 64;
+/// Frames analysed per second of clip
+const ANALYSIS_FPS: f64 = 4.0;
+/// Pixel difference below this is treated as noise
+const MOTION_THRESHOLD: u8 = 16;
+/// Minimum share of moving pixels to trust a sample
+const MIN_MOTION_SHARE: f64 = 0.002;
+/// Width of the smoothing window in seconds
+const SMOOTHING_SECONDS: f64 = 2.0;
+/// Spacing of the points kept in the path, in seconds
+const POINT_SPACING: f64 = 0.5;
+/// Seconds analysed around the frame of a still preview
+const STILL_WINDOW_SECONDS: f64 = 2.0 * SMOOTHING_SECONDS;
+
+/// How a clip layer chooses its crop window
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReframeMode {
+    /// Use `crop` as given
+    #[default]
+    Off,
+    /// Follow the subject automatically
+    Auto,
+}
+
+/// Subject position at a point in time, normalised to 0.0 - 1.0
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReframePoint {
+    pub time: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Smoothed subject path for one clip
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReframePath {
+    pub points: Vec<ReframePoint>,
+}
+
+impl ReframePath {
+    fn track(&self, pick: impl Fn(&ReframePoint) -> f64) -> Track {
+        Track::new(
+            self.points
+                .iter()
+                .map(|p| (p.time, pick(p), Easing::Linear))
+                .collect(),
+        )
+    }
+
+    /// The path on another time base; points mapped to None are dropped
+    pub fn retime(&self, map: impl Fn(f64) -> Option<f64>) -> Self {
+        Self {
+            points: self
+                .points
+                .iter()
+                .filter_map(|p| map(p.time).map(|time| ReframePoint { time, ..*p }))
+                .collect(),
+        }
+    }
+
+    /// Crop filter following the path, keeping the aspect ratio `aspect` (w / h)
+    /// With `still_at`, the window is fixed at the position for that time
+    pub fn crop_filter(&self, aspect: f64, still_at: Option<f64>) -> String {
+        let x_track = self.track(|p| p.x);
+        let y_track = self.track(|p| p.y);
+        let centre = |track: &Track| match still_at {
+            _ if track.is_empty() => "0.5".to_string(),
+            Some(time) => format!("{:.4}", track.value_at(time)),
+            None => track.to_expr("t"),
+        };
+
+        format!(
+            "crop=w='min(iw,ih*{a})':h='min(ih,iw/{a})':x='clip(({cx})*iw-ow/2,0,iw-ow)':y='clip(({cy})*ih-oh/2,0,ih-oh)'",
+            a = format!("{:.4}", aspect),
+            cx = centre(&x_track),
+            cy = centre(&y_track),
+        )
+    }
+}
+
+/// Sidecar file holding the path of a clip ("clip.mp4" -> "clip.reframe.json")
+/// Edit it by hand to tweak the framing; it is reused on the next export
+pub fn sidecar_path(clip_path: &str) -> PathBuf {
+    Path::new(clip_path).with_extension("reframe.json")
+}
+
+/// Load the sidecar path of a clip, if there is one
+pub fn load_sidecar(clip_path: &str) -> Option<ReframePath> {
+    let path = sidecar_path(clip_path);
+    let content = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(reframe) => Some(reframe),
+        Err(e) => {
+            log::warn!("Ignoring invalid reframe file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Write the path next to the clip
+pub fn save_sidecar(clip_path: &str, reframe: &ReframePath) -> Result<()> {
+    let path = sidecar_path(clip_path);
+    let content = serde_json::to_string_pretty(reframe)?;
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// ffmpeg arguments writing the analysis frames; `window` limits them to
+/// `(start, seconds)` of the clip
+fn analysis_args(clip_path: &str, window: Option<(f64, f64)>) -> Vec<String> {
+    let mut args = vec!["-v".to_string(), "error".to_string()];
+    if let Some((start, seconds)) = window {
+        args.extend([
+            "-ss".to_string(),
+            format!("{:.3}", start),
+            "-t".to_string(),
+            format!("{:.3}", seconds),
+        ]);
+    }
+    args.extend([
+        "-i".to_string(),
+        clip_path.to_string(),
+        "-an".to_string(),
+        "-vf".to_string(),
+        format!(
+            "fps={},scale={}:{},format=gray",
+            ANALYSIS_FPS, ANALYSIS_WIDTH, ANALYSIS_HEIGHT
+        ),
+        "-f".to_string(),
+        "rawvideo".to_string(),
+        "-".to_string(),
+    ]);
+    args
+}
+
+/// Analyse a clip and build its subject path
+pub async fn analyze(clip_path: &str, cancellation_token: Arc<AtomicBool>) -> Result<ReframePath> {
+    let mut command = tokio::process::Command::new("ffmpeg");
+    command.args(analysis_args(clip_path, None));
+    let output = crate::video::run_command_with_cancellation(command, cancellation_token).await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Reframe analysis failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(path_from_frames(&output.stdout))
+}
+
+/// Path of the few seconds around `time` only, enough to place the crop of a
+/// still preview (blocking)
+///
+/// Not meant for the sidecar: export would take it for the path of the whole clip.
+pub fn analyze_around_blocking(clip_path: &str, time: f64) -> Result<ReframePath> {
+    let start = (time - STILL_WINDOW_SECONDS / 2.0).max(0.0);
+    let output = std::process::Command::new("ffmpeg")
+        .args(analysis_args(
+            clip_path,
+            Some((start, STILL_WINDOW_SECONDS)),
+        ))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to execute ffmpeg for reframe analysis")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Reframe analysis failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(path_from_frames(&output.stdout).retime(|t| Some(t + start)))
+}
+
+/// Build a path from raw grayscale frames of ANALYSIS_WIDTH x ANALYSIS_HEIGHT
+fn path_from_frames(raw: &[u8]) -> ReframePath {
+    let frame_size = ANALYSIS_WIDTH * ANALYSIS_HEIGHT;
+    let frames: Vec<&[u8]> = raw.chunks_exact(frame_size).collect();
+
+    let mut samples = vec![None];
+    for pair in frames.windows(2) {
+        samples.push(motion_centroid(
+            pair[0],
+            pair[1],
+            ANALYSIS_WIDTH,
+            ANALYSIS_HEIGHT,
+        ));
+    }
+    if frames.is_empty() {
+        samples.clear();
+    }
+
+    smooth_path(&samples, ANALYSIS_FPS)
+}
+
+/// Centre of the pixels that changed between two frames (normalised)
+/// Returns None when there is too little motion to tell
+fn motion_centroid(prev: &[u8], cur: &[u8], width: usize, height: usize) -> Option<(f64, f64)> {
+    let mut total = 0.0;
+    let mut sum_x = 0.0;
+    let mut sum_y = 0.0;
+    let mut moving = 0usize;
+
+    for (i, (a, b)) in prev.iter().zip(cur).enumerate() {
+        let diff = a.abs_diff(*b);
+        if diff < MOTION_THRESHOLD {
+            continue;
+        }
+        let weight = diff as f64;
+        total += weight;
+        sum_x += weight * ((i % width) as f64 + 0.5);
+        sum_y += weight * ((i / width) as f64 + 0.5);
+        moving += 1;
+    }
+
+    if (moving as f64) < MIN_MOTION_SHARE * (width * height) as f64 {
+        return None;
+    }
+    Some((sum_x / total / width as f64, sum_y / total / height as f64))
+}
+
+/// Fill gaps, smooth with a moving average and thin out the samples
+fn smooth_path(samples: &[Option<(f64, f64)>], fps: f64) -> ReframePath {
+    // Hold the last known position through still frames (the first one before it)
+    let first = samples
+        .iter()
+        .flatten()
+        .next()
+        .copied()
+        .unwrap_or((0.5, 0.5));
+    let mut last = first;
+    let filled: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|s| {
+            if let Some(pos) = s {
+                last = *pos;
+            }
+            last
+        })
+        .collect();
+
+    let radius = ((SMOOTHING_SECONDS * fps) / 2.0).round().max(1.0) as usize;
+    let step = ((POINT_SPACING * fps).round() as usize).max(1);
+
+    let mut points = Vec::new();
+    for i in (0..filled.len()).step_by(step) {
+        let from = i.saturating_sub(radius);
+        let to = (i + radius + 1).min(filled.len());
+        let window = &filled[from..to];
+        let n = window.len() as f64;
+        let x = window.iter().map(|p| p.0).sum::<f64>() / n;
+        let y = window.iter().map(|p| p.1).sum::<f64>() / n;
+        points.push(ReframePoint {
+            time: i as f64 / fps,
+            x: round4(x),
+            y: round4(y),
+        });
+    }
+
+    ReframePath { points }
+}
+
+fn round4(v: f64) -> f64 {
+    (v * 10000.0).round() / 10000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_motion_centroid() {
+        let width = 10;
+        let height = 4;
+        let prev = vec![0u8; width * height];
+        let mut cur = prev.clone();
+        // Movement in the right half, second row
+        for x in 6..10 {
+            cur[width + x] = 200;
+        }
+        let (x, y) = motion_centroid(&prev, &cur, width, height).unwrap();
+        assert!((x - 0.8).abs() < 1e-9);
+        assert!((y - 0.375).abs() < 1e-9);
+
+        // Nothing moved
+        assert!(motion_centroid(&prev, &prev, width, height).is_none());
+    }
+
+    #[test]
+    fn test_smooth_path_holds_and_smooths() {
+        let samples = vec![
+            None,
+            Some((0.2, 0.5)),
+            None,
+            None,
+            Some((0.8, 0.5)),
+            None,
+            None,
+            None,
+        ];
+        let path = smooth_path(&samples, 4.0);
+        // One point every half second
+        assert_eq!(path.points.len(), 4);
+        assert_eq!(path.points[0].time, 0.0);
+        assert_eq!(path.points[1].time, 0.5);
+        // Smoothed values stay between the raw extremes and move right
+        assert!(path.points[0].x >= 0.2 && path.points[0].x < path.points[3].x);
+        assert!(path.points[3].x <= 0.8);
+    }
+
+    #[test]
+    fn test_analysis_args_window() {
+        let args = analysis_args("clip.mp4", None);
+        assert!(!args.contains(&"-ss".to_string()));
+
+        let args = analysis_args("clip.mp4", Some((3.0, STILL_WINDOW_SECONDS))).join(" ");
+        assert!(args.starts_with("-v error -ss 3.000 -t 4.000 -i clip.mp4"));
+    }
+
+    #[test]
+    fn test_crop_filter() {
+        let path = ReframePath {
+            points: vec![
+                ReframePoint {
+                    time: 0.0,
+                    x: 0.25,
+                    y: 0.5,
+                },
+                ReframePoint {
+                    time: 2.0,
+                    x: 0.75,
+                    y: 0.5,
+                },
+            ],
+        };
+        let filter = path.crop_filter(0.5625, None);
+        assert!(filter.starts_with("crop=w='min(iw,ih*0.5625)':h='min(ih,iw/0.5625)'"));
+        assert!(filter.contains(
+            "x='clip((lt(t,0)*(0.25)+gte(t,0)*lt(t,2)*(0.25+(0.5)*(t-0)/2)+gte(t,2)*(0.75))*iw-ow/2,0,iw-ow)'"
+        ));
+        assert!(filter.contains("y='clip((0.5)*ih-oh/2,0,ih-oh)'"));
+
+        let still = path.crop_filter(0.5625, Some(1.0));
+        assert!(still.contains("x='clip((0.5000)*iw-ow/2,0,iw-ow)'"));
+
+        // No data: centre crop
+        let empty = ReframePath::default().crop_filter(1.0, None);
+        assert!(empty.contains("x='clip((0.5)*iw-ow/2"));
+    }
+
+    #[test]
+    fn test_crop_filter_long_clip() {
+        // 90 seconds of points, as many as a long moment gets
+        let points = (0..=180)
+            .map(|i| ReframePoint {
+                time: i as f64 * POINT_SPACING,
+                x: if i % 2 == 0 { 0.3 } else { 0.7 },
+                y: 0.5,
+            })
+            .collect();
+        let filter = ReframePath { points }.crop_filter(0.5625, None);
+        assert!(filter.contains("+gte(t,89.5)*lt(t,90)*(0.7+(-0.4)*(t-89.5)/0.5)"));
+
+        // FFmpeg refuses expressions nested about 100 levels deep
+        let mut depth: i32 = 0;
+        let mut max_depth = 0;
+        for c in filter.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            max_depth = max_depth.max(depth);
+        }
+        assert_eq!(depth, 0);
+        assert!(max_depth < 10, "nested {} levels", max_depth);
+    }
+}
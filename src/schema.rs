@@ -0,0 +1,350 @@
+//! JSON Schema of the plano format for YT ShortMaker
+//! Generated from the plano types so it never drifts from what the exporter reads
+//!
+//! Editors such as VS Code pick the schema up from a `"$schema"` key at the top
+//! of a plano document and offer autocompletion, hover docs and typo warnings.
+
+use anyhow::{Context, Result};
+use schemars::gen::SchemaSettings;
+use schemars::schema::{
+    InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec, StringValidation,
+    SubschemaValidation,
+};
+use schemars::visit::{visit_schema_object, Visitor};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::exporter::{Plano, PlanoObject};
+use crate::variables::{whole_placeholder, Unset};
+
+/// File name used when the schema is written next to planos
+pub const SCHEMA_FILE_NAME: &str = "plano.schema.json";
+
+/// Schema of a plano file: a document object or a bare array of layers
+pub fn plano_schema() -> RootSchema {
+    let mut root = generated_schema();
+    DenyUnknownFields.visit_root_schema(&mut root);
+    AllowPlaceholders.visit_root_schema(&mut root);
+    root
+}
+
+/// The schema as derived from the plano types, before the editor tweaks
+fn generated_schema() -> RootSchema {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    let document = gen.subschema_for::<Plano>();
+    let layers = gen.subschema_for::<Vec<PlanoObject>>();
+
+    let mut schema = SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![document, layers]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    let metadata = schema.metadata();
+    metadata.title = Some("YT ShortMaker plano".to_string());
+    metadata.description =
+        Some("Layers of a short, back to front, with optional export settings".to_string());
+
+    RootSchema {
+        meta_schema: gen.settings().meta_schema.clone(),
+        schema,
+        definitions: gen.take_definitions(),
+    }
+}
+
+/// The schema as pretty-printed JSON
+pub fn plano_schema_json() -> String {
+    // Serializing a schema built from plain data cannot fail
+    serde_json::to_string_pretty(&plano_schema()).unwrap_or_default()
+}
+
+/// Write the schema to `path`, or to `plano.schema.json` inside it when it is a folder
+/// Returns the file written
+pub fn write_plano_schema(path: &Path) -> Result<PathBuf> {
+    let file = if path.is_dir() {
+        path.join(SCHEMA_FILE_NAME)
+    } else {
+        path.to_path_buf()
+    };
+    fs::write(&file, plano_schema_json())
+        .with_context(|| format!("Failed to write schema to {:?}", file))?;
+    Ok(file)
+}
+
+/// Replace the `{{name}}` placeholders of a plano document that are still
+/// there after substitution (no clip variable, no default) with a value their
+/// field accepts, so the plano loads without the variables of a clip
+///
+/// The field's default is used when it has one, else `null`, `0`, `false` or
+/// an empty text, in that order. Returns where each placeholder was.
+pub fn fill_placeholders(document: &mut Value) -> Vec<Unset> {
+    static SCHEMA: OnceLock<RootSchema> = OnceLock::new();
+    let root = SCHEMA.get_or_init(generated_schema);
+    let schema = Schema::Object(root.schema.clone());
+    // Bare arrays are reported as `layers` too, like validation does
+    let field = if document.is_array() { "layers" } else { "" };
+
+    let mut unset = Vec::new();
+    fill(document, &schema, &root.definitions, field, &mut unset);
+    unset
+}
+
+fn fill(
+    value: &mut Value,
+    schema: &Schema,
+    definitions: &schemars::Map<String, Schema>,
+    field: &str,
+    unset: &mut Vec<Unset>,
+) {
+    let mut objects = Vec::new();
+    alternatives(schema, definitions, &mut objects);
+
+    match value {
+        Value::String(text) => {
+            if let Some(name) = whole_placeholder(text) {
+                unset.push(Unset {
+                    field: field.to_string(),
+                    name: name.trim().to_string(),
+                });
+                *value = neutral_value(&objects);
+            }
+        }
+        Value::Array(items) => {
+            let item_schema = objects.iter().find_map(|o| match o.array.as_ref()?.items {
+                Some(SingleOrVec::Single(ref item)) => Some(item.as_ref()),
+                _ => None,
+            });
+            let Some(item_schema) = item_schema else {
+                return;
+            };
+            for (i, item) in items.iter_mut().enumerate() {
+                let field = format!("{}[{}]", field, i);
+                fill(item, item_schema, definitions, &field, unset);
+            }
+        }
+        Value::Object(map) => {
+            // Layers are tagged with `type`: only that variant's fields apply
+            let tag = map.get("type").cloned();
+            for (key, item) in map.iter_mut() {
+                let Some(property) = property_schema(&objects, key, tag.as_ref()) else {
+                    continue;
+                };
+                let field = match field {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", field, key),
+                };
+                fill(item, property, definitions, &field, unset);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Every schema object a value of `schema` may have to match: references are
+/// followed and `allOf`/`anyOf`/`oneOf` alternatives are listed
+fn alternatives<'a>(
+    schema: &'a Schema,
+    definitions: &'a schemars::Map<String, Schema>,
+    out: &mut Vec<&'a SchemaObject>,
+) {
+    let Schema::Object(object) = schema else {
+        return;
+    };
+    out.push(object);
+    let referenced = object
+        .reference
+        .as_deref()
+        .and_then(|r| r.strip_prefix("#/definitions/"))
+        .and_then(|name| definitions.get(name));
+    if let Some(referenced) = referenced {
+        alternatives(referenced, definitions, out);
+    }
+    if let Some(subschemas) = &object.subschemas {
+        let lists = [&subschemas.all_of, &subschemas.any_of, &subschemas.one_of];
+        for schema in lists.into_iter().flatten().flatten() {
+            alternatives(schema, definitions, out);
+        }
+    }
+}
+
+/// Schema of the `key` field of an object, skipping variants of another `type`
+fn property_schema<'a>(
+    objects: &[&'a SchemaObject],
+    key: &str,
+    tag: Option<&Value>,
+) -> Option<&'a Schema> {
+    objects.iter().find_map(|o| {
+        let object = o.object.as_ref()?;
+        let other_variant = match (object.properties.get("type"), tag) {
+            (Some(Schema::Object(kind)), Some(tag)) => kind
+                .enum_values
+                .as_ref()
+                .is_some_and(|values| !values.contains(tag)),
+            _ => false,
+        };
+        if other_variant {
+            return None;
+        }
+        object
+            .properties
+            .get(key)
+            .or(object.additional_properties.as_deref())
+    })
+}
+
+/// Value standing in for a placeholder in a field matching `objects`
+fn neutral_value(objects: &[&SchemaObject]) -> Value {
+    let default = objects
+        .iter()
+        .find_map(|o| o.metadata.as_ref()?.default.clone());
+    if let Some(default) = default {
+        return default;
+    }
+
+    let accepts = |kind: InstanceType| {
+        objects.iter().any(|o| match &o.instance_type {
+            Some(SingleOrVec::Single(t)) => **t == kind,
+            Some(SingleOrVec::Vec(types)) => types.contains(&kind),
+            None => false,
+        })
+    };
+    let first_enum_value = objects
+        .iter()
+        .find_map(|o| o.enum_values.as_ref()?.first().cloned());
+    if accepts(InstanceType::Null) {
+        Value::Null
+    } else if accepts(InstanceType::Integer) || accepts(InstanceType::Number) {
+        Value::from(0)
+    } else if accepts(InstanceType::Boolean) {
+        Value::Bool(false)
+    } else if let Some(value) = first_enum_value {
+        value
+    } else {
+        Value::String(String::new())
+    }
+}
+
+/// Flags keys the exporter would silently ignore (usually typos)
+/// serde accepts unknown fields, so the derived schema would allow them too
+#[derive(Debug, Clone)]
+struct DenyUnknownFields;
+
+impl Visitor for DenyUnknownFields {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        visit_schema_object(self, schema);
+        if let Some(object) = schema.object.as_mut() {
+            if !object.properties.is_empty() && object.additional_properties.is_none() {
+                object.additional_properties = Some(Box::new(Schema::Bool(false)));
+            }
+        }
+    }
+}
+
+/// Lets numeric fields hold a `{{variable}}` placeholder instead of a number
+/// (replaced with the clip's value when the plano is loaded)
+#[derive(Debug, Clone)]
+struct AllowPlaceholders;
+
+impl Visitor for AllowPlaceholders {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        visit_schema_object(self, schema);
+        let numeric = |t: &InstanceType| matches!(t, InstanceType::Integer | InstanceType::Number);
+        let is_numeric = match &schema.instance_type {
+            Some(SingleOrVec::Single(t)) => numeric(t),
+            Some(SingleOrVec::Vec(types)) => types.iter().any(numeric),
+            None => false,
+        };
+        if !is_numeric {
+            return;
+        }
+
+        let placeholder = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"^\{\{.+\}\}$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let metadata = schema.metadata.take();
+        let value = std::mem::take(schema);
+        *schema = SchemaObject {
+            metadata,
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![value.into(), placeholder.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plano_schema() {
+        let schema: Value = serde_json::from_str(&plano_schema_json()).unwrap();
+        assert_eq!(schema["anyOf"].as_array().unwrap().len(), 2);
+
+        let definitions = &schema["definitions"];
+        for name in ["Plano", "PlanoObject", "Position", "Crop", "ShaderEffect"] {
+            assert!(definitions[name].is_object(), "missing {}", name);
+        }
+        let document = &definitions["Plano"];
+        assert!(document["properties"]["$schema"].is_object());
+        assert!(document["properties"]["layers"].is_object());
+        assert_eq!(document["additionalProperties"], Value::Bool(false));
+
+        // The `$schema` key is accepted and kept when saving
+        let plano =
+            crate::exporter::parse_plano(r#"{ "$schema": "./plano.schema.json", "layers": [] }"#)
+                .unwrap();
+        assert_eq!(plano.schema.as_deref(), Some("./plano.schema.json"));
+        let saved = serde_json::to_value(&plano).unwrap();
+        assert_eq!(saved["$schema"], "./plano.schema.json");
+    }
+
+    #[test]
+    fn test_numbers_accept_placeholders() {
+        let schema: Value = serde_json::from_str(&plano_schema_json()).unwrap();
+        let x_from = &schema["definitions"]["Crop"]["properties"]["x_from"];
+        let options = x_from["anyOf"].as_array().unwrap();
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[1]["type"], "string");
+        assert_eq!(options[1]["pattern"], r"^\{\{.+\}\}$");
+        // The description stays on the field, not inside the alternatives
+        assert!(x_from["description"].is_string());
+        assert!(options[0]["description"].is_null());
+    }
+
+    #[test]
+    fn test_default_planos_match_schema() {
+        // Every key written by the built-in planos must be known to the schema
+        let schema: Value = serde_json::from_str(&plano_schema_json()).unwrap();
+        let variants = schema["definitions"]["PlanoObject"]["oneOf"]
+            .as_array()
+            .unwrap();
+        let layers = serde_json::to_value(crate::exporter::create_default_plano()).unwrap();
+
+        for layer in layers.as_array().unwrap() {
+            let kind = layer["type"].as_str().unwrap();
+            let variant = variants
+                .iter()
+                .find(|v| v["properties"]["type"]["enum"][0] == kind)
+                .unwrap();
+            for key in layer.as_object().unwrap().keys() {
+                assert!(
+                    variant["properties"][key.as_str()].is_object(),
+                    "{}.{} missing from schema",
+                    kind,
+                    key
+                );
+            }
+        }
+    }
+}
@@ -1,41 +1,25 @@
 //! Shorts transformation module for YT ShortMaker
 //! Converts extracted clips to YouTube Shorts format (9:16) with layered composition
+//!
+//! The `transform`, `batch` and `preview` commands render through the plano
+//! exporter. Without `--plano`, the `shorts_config` from settings.json is
+//! turned into an equivalent plano first.
 
 use anyhow::{anyhow, Context, Result};
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 use crate::config::{ImageOverlay, ShortsConfig};
-
-/// Get video duration as float (seconds with decimals)
-#[allow(dead_code)]
-pub fn get_video_duration_float(file_path: &str) -> Result<f64> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "error",
-            "-show_entries",
-            "format=duration",
-            "-of",
-            "default=noprint_wrappers=1:nokey=1",
-            file_path,
-        ])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .output()
-        .context("Failed to run ffprobe")?;
-
-    let duration_str = String::from_utf8_lossy(&output.stdout);
-    let duration: f64 = duration_str
-        .trim()
-        .parse()
-        .context("Failed to parse duration")?;
-
-    Ok(duration)
-}
-
-/// Get video resolution (width, height)
-#[allow(dead_code)]
+use crate::encoding::ExportProfile;
+use crate::exporter::{
+    ExportTarget, Fit, LayerStyle, LayerTiming, Plano, PlanoObject, Position, PositionValue,
+    ShaderEffect, SizeValue, OUTPUT_HEIGHT, OUTPUT_WIDTH,
+};
+use crate::reframe::ReframeMode;
+
+/// Get video (or image) resolution (width, height)
 pub fn get_video_resolution(file_path: &str) -> Result<(u32, u32)> {
     let output = Command::new("ffprobe")
         .args([
@@ -67,291 +51,171 @@ pub fn get_video_resolution(file_path: &str) -> Result<(u32, u32)> {
     Ok((width, height))
 }
 
-/// Build the FFmpeg filter_complex string for layered video composition
-/// Uses a simplified approach that works with any input resolution
-fn build_filter_complex(
-    config: &ShortsConfig,
-    has_background: bool,
-    overlay_count: usize,
-) -> String {
-    let w = config.output_width;
-    let h = config.output_height;
-    let blur = config.base_blur;
-    let main_h = config.main_video_height;
-    let opacity = config.background_opacity;
-
-    let mut filters = Vec::new();
-
-    // Layer 1: Base video (blurred background) - scale to fill, then crop, then blur
-    // Use 'scale' with fill mode and 'crop' to center
-    filters.push(format!(
-        "[0:v]scale={}:{}:force_original_aspect_ratio=increase,\
-         crop={}:{},\
-         boxblur={}:{}[base]",
-        w, h, w, h, blur, blur
+/// Build the plano equivalent to a `ShortsConfig`
+///
+/// Layers: the clip stretched and blurred, the background video, the zoomed
+/// main clip and the image overlays. Config coordinates are in output pixels
+/// and are scaled onto the 1080x1920 plano canvas.
+pub fn plano_from_config(config: &ShortsConfig) -> Plano {
+    let w = config.output_width.max(1);
+    let h = config.output_height.max(1);
+    let sx = |v: f64| (v * OUTPUT_WIDTH as f64 / w as f64).round();
+    let sy = |v: f64| (v * OUTPUT_HEIGHT as f64 / h as f64).round();
+    let full = || Position {
+        x: PositionValue::Pixels(0),
+        y: PositionValue::Pixels(0),
+        width: SizeValue::Keyword("full".to_string()),
+        height: SizeValue::Keyword("full".to_string()),
+    };
+
+    let mut layers = Vec::new();
+
+    // Layer 1: the clip filling the canvas, blurred
+    layers.push(clip_layer(
+        full(),
+        1.0,
+        vec![ShaderEffect::Blur {
+            intensity: config.base_blur,
+        }],
+        "Blurred background",
     ));
 
-    let mut current_layer = "base".to_string();
-
-    // Layer 2: Background video with transparency (if provided)
-    if has_background {
-        // Loop the background video, scale to fill, crop, and apply transparency
-        filters.push(format!(
-            "[1:v]loop=-1:size=32767,setpts=N/FRAME_RATE/TB,\
-             scale={}:{}:force_original_aspect_ratio=increase,\
-             crop={}:{},\
-             format=rgba,colorchannelmixer=aa={}[bg]",
-            w, h, w, h, opacity
-        ));
-        filters.push(format!("[{}][bg]overlay=0:0[layer1]", current_layer));
-        current_layer = "layer1".to_string();
+    // Layer 2: background video with transparency
+    if let Some(path) = &config.background_video {
+        layers.push(PlanoObject::Video {
+            id: None,
+            path: path.clone(),
+            position: full(),
+            loop_video: true,
+            keep_last_frame: false,
+            opacity: config.background_opacity,
+            fit: Fit::Cover,
+            effects: Vec::new(),
+            keyframes: Vec::new(),
+            style: LayerStyle::default(),
+            timing: LayerTiming::default(),
+            comment: Some("Background video".to_string()),
+        });
     }
 
-    // Layer 3: Main video with zoom (crop center) and positioned with offset
-    let main_input = if has_background { 2 } else { 1 };
-    let zoom = config.main_video_zoom.clamp(0.3, 1.0);
-    let y_offset = config.main_video_y_offset;
-
-    // Calculate base Y position (centered) then apply offset
-    let base_y = ((h - main_h) / 2) as i32;
-    let final_y = (base_y + y_offset).max(0) as u32;
-
-    // Zoom strategy:
-    // 1) Scale video to be larger than target (to enable cropping)
-    // 2) Crop center portion based on zoom level
-    // zoom=0.7 means show 70% of original, so we scale up by 1/0.7 ≈ 1.43x first
-    // then crop to exactly our target dimensions
-    let scale_factor = 1.0 / zoom;
-
-    // We need the final video to be exactly w x main_h after cropping
-    // So we scale it up enough that crop will work
-    // Scale to make sure both width AND height are large enough for crop
-    filters.push(format!(
-        "[{}:v]scale=w={}:h={}:force_original_aspect_ratio=increase,crop={}:{}[main]",
-        main_input,
-        (w as f32 * scale_factor) as u32,
-        (main_h as f32 * scale_factor) as u32,
-        w,
-        main_h
+    // Layer 3: main clip, centred and moved by the offset
+    let main_h = config.main_video_height.min(h);
+    let y = ((h - main_h) as i32 / 2 + config.main_video_y_offset).max(0);
+    layers.push(clip_layer(
+        Position {
+            x: PositionValue::Pixels(0),
+            y: PositionValue::Pixels(sy(y as f64) as i32),
+            width: SizeValue::Keyword("full".to_string()),
+            height: SizeValue::Pixels(sy(main_h as f64) as u32),
+        },
+        config.main_video_zoom.clamp(0.3, 1.0) as f64,
+        Vec::new(),
+        "Main video",
     ));
 
-    // Overlay main video at calculated position
-    filters.push(format!(
-        "[{}][main]overlay=0:{}[layer2]",
-        current_layer, final_y
-    ));
-    current_layer = "layer2".to_string();
-
-    // Layer 4: Image overlays
-    let overlay_start_input = if has_background { 3 } else { 2 };
-    for i in 0..overlay_count {
-        let input_idx = overlay_start_input + i;
-        let next_layer = if i == overlay_count - 1 {
-            "out".to_string()
-        } else {
-            format!("layer{}", 3 + i)
+    // Layer 4: image overlays
+    for overlay in &config.overlays {
+        let Some((width, height)) = overlay_size(overlay) else {
+            log::warn!("Skipping overlay, cannot read: {}", overlay.path);
+            continue;
         };
-
-        // Apply scale to overlay if dimensions specified (handled via placeholder)
-        filters.push(format!(
-            "[{}][{}:v]overlay=OVERLAY_X_{}:OVERLAY_Y_{}[{}]",
-            current_layer, input_idx, i, i, next_layer
-        ));
-        current_layer = next_layer;
+        layers.push(PlanoObject::Image {
+            id: None,
+            path: overlay.path.clone(),
+            position: Position {
+                x: PositionValue::Pixels(sx(overlay.x as f64) as i32),
+                y: PositionValue::Pixels(sy(overlay.y as f64) as i32),
+                width: SizeValue::Pixels(sx(width as f64) as u32),
+                height: SizeValue::Pixels(sy(height as f64) as u32),
+            },
+            opacity: 1.0,
+            loop_animation: true,
+            fps: None,
+            optional: false,
+            effects: Vec::new(),
+            keyframes: Vec::new(),
+            style: LayerStyle::default(),
+            timing: LayerTiming::default(),
+            comment: None,
+        });
     }
 
-    // If no overlays, rename layer2 to out
-    if overlay_count == 0 {
-        let last_filter = filters.pop().unwrap();
-        filters.push(last_filter.replace("[layer2]", "[out]"));
-    }
-
-    filters.join(";")
+    Plano::from(layers)
 }
 
-/// Transform a video clip to YouTube Shorts format
-pub async fn transform_to_short(
-    input_video: &str,
-    output_path: &str,
-    config: &ShortsConfig,
-    cancellation_token: std::sync::Arc<std::sync::atomic::AtomicBool>,
-) -> Result<()> {
-    if !Path::new(input_video).exists() {
-        return Err(anyhow!("Input video not found: {}", input_video));
-    }
-
-    let has_background = config
-        .background_video
-        .as_ref()
-        .map(|p| Path::new(p).exists())
-        .unwrap_or(false);
-
-    // Validate overlays
-    let valid_overlays: Vec<&ImageOverlay> = config
-        .overlays
-        .iter()
-        .filter(|o| Path::new(&o.path).exists())
-        .collect();
-
-    // Build filter complex
-    let mut filter = build_filter_complex(config, has_background, valid_overlays.len());
-
-    // Replace overlay position placeholders with actual values
-    for (i, overlay) in valid_overlays.iter().enumerate() {
-        filter = filter.replace(&format!("OVERLAY_X_{}", i), &overlay.x.to_string());
-        filter = filter.replace(&format!("OVERLAY_Y_{}", i), &overlay.y.to_string());
-    }
-
-    // Build FFmpeg command
-    let mut args: Vec<String> = vec!["-i".to_string(), input_video.to_string()];
-
-    // Add background video input if exists
-    if has_background {
-        if let Some(ref bg_path) = config.background_video {
-            args.push("-i".to_string());
-            args.push(bg_path.clone());
-        }
-    }
-
-    // Add main video again for the centered layer
-    args.push("-i".to_string());
-    args.push(input_video.to_string());
-
-    // Add overlay images
-    for overlay in &valid_overlays {
-        args.push("-i".to_string());
-        args.push(overlay.path.clone());
+/// Clip layer filling `position` with the cover fit
+fn clip_layer(
+    position: Position,
+    zoom: f64,
+    effects: Vec<ShaderEffect>,
+    comment: &str,
+) -> PlanoObject {
+    PlanoObject::Clip {
+        id: None,
+        source: None,
+        offset: 0.0,
+        position,
+        crop: None,
+        zoom,
+        fit: Fit::Cover,
+        reframe: ReframeMode::Off,
+        save_reframe: false,
+        effects,
+        keyframes: Vec::new(),
+        style: LayerStyle::default(),
+        timing: LayerTiming::default(),
+        comment: Some(comment.to_string()),
     }
+}
 
-    // Add filter complex
-    args.push("-filter_complex".to_string());
-    args.push(filter);
-
-    // Map output
-    args.push("-map".to_string());
-    args.push("[out]".to_string());
-    args.push("-map".to_string());
-    args.push("0:a?".to_string()); // Audio from main video (optional)
-
-    // Output settings
-    args.push("-c:v".to_string());
-    args.push("libx264".to_string());
-    args.push("-preset".to_string());
-    args.push("medium".to_string());
-    args.push("-crf".to_string());
-    args.push("23".to_string());
-
-    args.push("-c:a".to_string());
-    args.push("aac".to_string());
-    args.push("-b:a".to_string());
-    args.push("192k".to_string());
-    args.push("-y".to_string());
-    args.push(output_path.to_string());
-
-    let mut command = tokio::process::Command::new("ffmpeg");
-    command.args(&args);
-
-    // Use the cancellation helper from video module
-    let output = crate::video::run_command_with_cancellation(command, cancellation_token).await?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("FFmpeg transformation failed: {}", stderr));
+/// Size of an overlay in output pixels
+/// A missing width or height follows the image's aspect ratio
+fn overlay_size(overlay: &ImageOverlay) -> Option<(u32, u32)> {
+    if let (Some(width), Some(height)) = (overlay.width, overlay.height) {
+        return Some((width, height));
     }
+    let (native_w, native_h) = get_video_resolution(&overlay.path).ok()?;
+    let native_w = native_w.max(1) as f64;
+    let native_h = native_h.max(1) as f64;
+    Some(match (overlay.width, overlay.height) {
+        (Some(width), None) => (width, (width as f64 * native_h / native_w).round() as u32),
+        (None, Some(height)) => ((height as f64 * native_w / native_h).round() as u32, height),
+        _ => (native_w as u32, native_h as u32),
+    })
+}
 
-    Ok(())
+/// Output resolution to resize the canvas to, when it differs from 1080x1920
+pub fn output_size(config: &ShortsConfig) -> Option<(u32, u32)> {
+    let size = (config.output_width, config.output_height);
+    (size != (OUTPUT_WIDTH, OUTPUT_HEIGHT) && size.0 > 0 && size.1 > 0).then_some(size)
 }
 
-/// Generate a single-frame preview image to visualize the composition
-pub fn generate_preview(
+/// Transform a video clip to YouTube Shorts format
+pub async fn transform_to_short(
     input_video: &str,
-    output_image: &str,
-    config: &ShortsConfig,
-    timestamp_secs: f64,
+    output_path: &str,
+    plano: &Plano,
+    profile: &ExportProfile,
+    output_size: Option<(u32, u32)>,
+    cancellation_token: Arc<AtomicBool>,
 ) -> Result<()> {
     if !Path::new(input_video).exists() {
         return Err(anyhow!("Input video not found: {}", input_video));
     }
 
-    let has_background = config
-        .background_video
-        .as_ref()
-        .map(|p| Path::new(p).exists())
-        .unwrap_or(false);
-
-    // Validate overlays
-    let valid_overlays: Vec<&ImageOverlay> = config
-        .overlays
-        .iter()
-        .filter(|o| Path::new(&o.path).exists())
-        .collect();
-
-    // Build filter complex
-    let mut filter = build_filter_complex(config, has_background, valid_overlays.len());
-
-    // Replace overlay position placeholders
-    for (i, overlay) in valid_overlays.iter().enumerate() {
-        filter = filter.replace(&format!("OVERLAY_X_{}", i), &overlay.x.to_string());
-        filter = filter.replace(&format!("OVERLAY_Y_{}", i), &overlay.y.to_string());
-    }
-
-    // Build FFmpeg command for single frame
-    let timestamp = format!("{:.3}", timestamp_secs);
-
-    let mut args: Vec<String> = vec![
-        "-ss".to_string(),
-        timestamp.clone(),
-        "-i".to_string(),
-        input_video.to_string(),
-    ];
-
-    // Add background video input if exists
-    if has_background {
-        if let Some(ref bg_path) = config.background_video {
-            args.push("-ss".to_string());
-            args.push(timestamp.clone());
-            args.push("-i".to_string());
-            args.push(bg_path.clone());
-        }
-    }
-
-    // Add main video again for centered layer
-    args.push("-ss".to_string());
-    args.push(timestamp);
-    args.push("-i".to_string());
-    args.push(input_video.to_string());
-
-    // Add overlay images
-    for overlay in &valid_overlays {
-        args.push("-i".to_string());
-        args.push(overlay.path.clone());
-    }
-
-    // Add filter complex
-    args.push("-filter_complex".to_string());
-    args.push(filter);
-
-    // Map output and take single frame
-    args.push("-map".to_string());
-    args.push("[out]".to_string());
-    args.push("-frames:v".to_string());
-    args.push("1".to_string());
-
-    args.push("-y".to_string());
-    args.push(output_image.to_string());
-
-    let output = Command::new("ffmpeg")
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .context("Failed to execute ffmpeg for preview")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("FFmpeg preview failed: {}", stderr));
-    }
-
+    let target = ExportTarget {
+        layers: &plano.layers,
+        profile: plano.profile_or(profile),
+        output_path: output_path.to_string(),
+        intro: plano.intro.as_ref(),
+        outro: plano.outro.as_ref(),
+        output_size,
+        scale: 1,
+        limit: None,
+    };
+    crate::exporter::export_clip(input_video, &[target], cancellation_token, None)
+        .await
+        .context("FFmpeg transformation failed")?;
     Ok(())
 }
 
@@ -361,9 +225,11 @@ pub type ProgressCallback = Box<dyn Fn(usize, usize, &str) + Send>;
 pub async fn transform_batch(
     input_dir: &str,
     output_dir: &str,
-    config: &ShortsConfig,
+    plano: &Plano,
+    profile: &ExportProfile,
+    output_size: Option<(u32, u32)>,
     progress_callback: Option<ProgressCallback>,
-    cancellation_token: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    cancellation_token: Arc<AtomicBool>,
 ) -> Result<Vec<String>> {
     use std::fs;
     use std::sync::atomic::Ordering;
@@ -401,7 +267,9 @@ pub async fn transform_batch(
         match transform_to_short(
             input_path.to_str().unwrap(),
             &output_path,
-            config,
+            plano,
+            profile,
+            output_size,
             cancellation_token.clone(),
         )
         .await
@@ -425,30 +293,69 @@ pub async fn transform_batch(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ShortsConfig;
-
-    #[test]
-    fn test_build_filter_no_background() {
-        let config = ShortsConfig::default();
-        let filter = build_filter_complex(&config, false, 0);
-        assert!(filter.contains("[base]"));
-        assert!(filter.contains("[main]"));
-        assert!(filter.contains("[out]"));
-    }
 
     #[test]
-    fn test_build_filter_with_background() {
+    fn test_plano_from_default_config() {
         let config = ShortsConfig::default();
-        let filter = build_filter_complex(&config, true, 0);
-        assert!(filter.contains("[bg]"));
-        assert!(filter.contains("colorchannelmixer"));
+        let plano = plano_from_config(&config);
+        assert_eq!(plano.layers.len(), 2);
+        assert_eq!(output_size(&config), None);
+
+        // Same graph as the old compositor: blurred cover fill, then the
+        // zoomed main clip 150px above centre
+        let (filter, _) = crate::exporter::build_ffmpeg_filter(
+            &plano.layers,
+            "clip.mp4",
+            &crate::exporter::RenderOptions::animated(10.0),
+        );
+        assert!(filter.contains(
+            "scale=1080:1920:force_original_aspect_ratio=increase,crop=1080:1920,boxblur=20:20"
+        ));
+        assert!(filter.contains(
+            "crop=iw*0.7:ih*0.7,scale=1080:1400:force_original_aspect_ratio=increase,crop=1080:1400"
+        ));
+        assert!(filter.contains("overlay=0:110"));
     }
 
     #[test]
-    fn test_build_filter_with_overlays() {
-        let config = ShortsConfig::default();
-        let filter = build_filter_complex(&config, false, 2);
-        assert!(filter.contains("OVERLAY_X_0"));
-        assert!(filter.contains("OVERLAY_X_1"));
+    fn test_plano_from_config_scales_to_canvas() {
+        let config = ShortsConfig {
+            background_video: Some("bg.mp4".to_string()),
+            background_opacity: 0.3,
+            output_width: 720,
+            output_height: 1280,
+            main_video_height: 960,
+            main_video_y_offset: 0,
+            overlays: vec![ImageOverlay {
+                path: "logo.png".to_string(),
+                x: 40,
+                y: 100,
+                width: Some(200),
+                height: Some(100),
+            }],
+            ..ShortsConfig::default()
+        };
+        let plano = plano_from_config(&config);
+        assert_eq!(output_size(&config), Some((720, 1280)));
+        assert_eq!(plano.layers.len(), 4);
+        assert!(matches!(
+            &plano.layers[1],
+            PlanoObject::Video { opacity, .. } if *opacity == 0.3
+        ));
+        match &plano.layers[2] {
+            PlanoObject::Clip { position, .. } => {
+                assert!(matches!(position.y, PositionValue::Pixels(240)));
+                assert!(matches!(position.height, SizeValue::Pixels(1440)));
+            }
+            other => panic!("unexpected layer {:?}", other),
+        }
+        match &plano.layers[3] {
+            PlanoObject::Image { position, .. } => {
+                assert!(matches!(position.x, PositionValue::Pixels(60)));
+                assert!(matches!(position.width, SizeValue::Pixels(300)));
+                assert!(matches!(position.height, SizeValue::Pixels(150)));
+            }
+            other => panic!("unexpected layer {:?}", other),
+        }
     }
 }
@@ -0,0 +1,518 @@
+//! In-terminal images for YT ShortMaker
+//! Shows preview frames inside the TUI, so layouts can be checked over SSH
+//!
+//! Terminals speaking the kitty graphics protocol or sixel get the real
+//! pixels; any other truecolor terminal gets unicode half blocks (two pixels
+//! per cell). Kitty and sixel output bypasses the ratatui buffer: the cells
+//! under the image are skipped and the escape sequence is written after the
+//! frame is drawn.
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use std::process::{Command, Stdio};
+
+/// Cell size assumed when the terminal does not report pixel sizes
+const FALLBACK_CELL_SIZE: (u32, u32) = (8, 16);
+/// Kitty payloads are sent in chunks of at most this many bytes
+const KITTY_CHUNK: usize = 4096;
+/// Levels per channel of the sixel palette (6x6x6 colour cube)
+const SIXEL_LEVELS: u32 = 6;
+
+/// How images are drawn in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    HalfBlock,
+}
+
+impl GraphicsProtocol {
+    /// Pick the protocol from the environment
+    /// `YTSM_GRAPHICS=kitty|sixel|halfblock` forces one
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(forced) = var("YTSM_GRAPHICS").and_then(|v| Self::parse(&v)) {
+            return forced;
+        }
+        let term = var("TERM").unwrap_or_default().to_lowercase();
+        let program = var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+
+        if term.contains("kitty")
+            || term.contains("ghostty")
+            || var("KITTY_WINDOW_ID").is_some()
+            || program == "wezterm"
+            || program == "ghostty"
+        {
+            GraphicsProtocol::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.starts_with("contour")
+        {
+            GraphicsProtocol::Sixel
+        } else {
+            GraphicsProtocol::HalfBlock
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "kitty" => Some(GraphicsProtocol::Kitty),
+            "sixel" => Some(GraphicsProtocol::Sixel),
+            "halfblock" | "half-block" | "blocks" => Some(GraphicsProtocol::HalfBlock),
+            _ => None,
+        }
+    }
+
+    /// Next protocol, to try another one from the UI
+    pub fn next(self) -> Self {
+        match self {
+            GraphicsProtocol::Kitty => GraphicsProtocol::Sixel,
+            GraphicsProtocol::Sixel => GraphicsProtocol::HalfBlock,
+            GraphicsProtocol::HalfBlock => GraphicsProtocol::Kitty,
+        }
+    }
+
+    /// Name shown in the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            GraphicsProtocol::Kitty => "kitty",
+            GraphicsProtocol::Sixel => "sixel",
+            GraphicsProtocol::HalfBlock => "half-block",
+        }
+    }
+
+    /// Whether the image is written outside the ratatui buffer
+    pub fn is_graphics(&self) -> bool {
+        !matches!(self, GraphicsProtocol::HalfBlock)
+    }
+
+    /// Size of a cell in image pixels
+    pub fn cell_size(&self) -> (u32, u32) {
+        match self {
+            GraphicsProtocol::HalfBlock => (1, 2),
+            _ => terminal_cell_size(),
+        }
+    }
+}
+
+/// Pixel size of a terminal cell, as reported by the terminal
+fn terminal_cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            size.width as u32 / size.columns as u32,
+            size.height as u32 / size.rows as u32,
+        ),
+        _ => FALLBACK_CELL_SIZE,
+    }
+}
+
+/// 8-bit RGB image
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbImage {
+    pub width: u32,
+    pub height: u32,
+    /// Rows of `width` RGB triplets
+    pub pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    /// Black image
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 3) as usize],
+        }
+    }
+
+    /// Decode an image file with FFmpeg, scaled to `width`x`height`
+    pub fn load(path: &str, width: u32, height: u32) -> Result<Self> {
+        Self::decode(&["-i", path], path, width, height)
+    }
+
+    /// Decode the frame of a video at `seconds`, scaled to `width`x`height`
+    pub fn load_frame(video: &str, seconds: f64, width: u32, height: u32) -> Result<Self> {
+        let seek = format!("{:.3}", seconds.max(0.0));
+        Self::decode(&["-ss", &seek, "-i", video], video, width, height)
+    }
+
+    /// Decode the first frame of `input` (FFmpeg input options and `-i`)
+    fn decode(input: &[&str], path: &str, width: u32, height: u32) -> Result<Self> {
+        let output = Command::new("ffmpeg")
+            .args(["-v", "error"])
+            .args(input)
+            .args([
+                "-frames:v",
+                "1",
+                "-vf",
+                &format!("scale={}:{}", width, height),
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgb24",
+                "-",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .context("Failed to execute ffmpeg to decode image")?;
+        let expected = (width * height * 3) as usize;
+        if !output.status.success() || output.stdout.len() < expected {
+            return Err(anyhow!(
+                "Failed to decode {}: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels: output.stdout[..expected].to_vec(),
+        })
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Set a pixel; coordinates outside the image are ignored
+    fn set(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+        self.pixels[i..i + 3].copy_from_slice(&color);
+    }
+
+    /// Copy `other` into this image with its top-left corner at (x, y)
+    pub fn paste(&mut self, other: &RgbImage, x: i64, y: i64) {
+        for oy in 0..other.height {
+            for ox in 0..other.width {
+                self.set(x + ox as i64, y + oy as i64, other.pixel(ox, oy));
+            }
+        }
+    }
+
+    /// Nearest-neighbour resize
+    pub fn resized(&self, width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut out = Self::new(width, height);
+        for y in 0..height {
+            let sy = (y * self.height / height).min(self.height.saturating_sub(1));
+            for x in 0..width {
+                let sx = (x * self.width / width).min(self.width.saturating_sub(1));
+                out.set(x as i64, y as i64, self.pixel(sx, sy));
+            }
+        }
+        out
+    }
+
+    /// Outline of a rectangle, `thickness` pixels wide, clipped to the image
+    pub fn draw_box(&mut self, x: i64, y: i64, w: i64, h: i64, thickness: i64, color: [u8; 3]) {
+        for t in 0..thickness.min(w).min(h) {
+            for px in x..x + w {
+                self.set(px, y + t, color);
+                self.set(px, y + h - 1 - t, color);
+            }
+            for py in y..y + h {
+                self.set(x + t, py, color);
+                self.set(x + w - 1 - t, py, color);
+            }
+        }
+    }
+
+    /// A number in a 3x5 pixel font on a black backdrop, `scale` times larger
+    pub fn draw_number(&mut self, x: i64, y: i64, number: usize, scale: i64, color: [u8; 3]) {
+        let digits: Vec<usize> = number
+            .to_string()
+            .bytes()
+            .map(|b| (b - b'0') as usize)
+            .collect();
+        let width = (digits.len() as i64 * 4 + 1) * scale;
+        for py in y..y + 7 * scale {
+            for px in x..x + width {
+                self.set(px, py, [0, 0, 0]);
+            }
+        }
+        for (n, digit) in digits.iter().enumerate() {
+            let glyph = DIGITS[*digit];
+            for row in 0..5 {
+                for col in 0..3 {
+                    if glyph & (1 << (14 - (row * 3 + col))) == 0 {
+                        continue;
+                    }
+                    let gx = x + (1 + n as i64 * 4 + col) * scale;
+                    let gy = y + (1 + row) * scale;
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set(gx + dx, gy + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 3x5 glyphs of the digits, row by row from the top bit
+const DIGITS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_001_001_001,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+
+/// Largest number of cells (columns, rows) that shows the whole image at its
+/// aspect ratio inside `area` cells
+pub fn fit_cells(image: (u32, u32), area: (u16, u16), cell: (u32, u32)) -> (u16, u16) {
+    let (iw, ih) = (image.0.max(1) as f64, image.1.max(1) as f64);
+    let max_w = area.0 as f64 * cell.0 as f64;
+    let max_h = area.1 as f64 * cell.1 as f64;
+    let scale = (max_w / iw).min(max_h / ih);
+    let cols = ((iw * scale / cell.0 as f64).floor() as u16).clamp(1, area.0.max(1));
+    let rows = ((ih * scale / cell.1 as f64).floor() as u16).clamp(1, area.1.max(1));
+    (cols, rows)
+}
+
+/// Draw the image with half blocks, filling `area`
+pub fn render_half_blocks(image: &RgbImage, area: Rect, buf: &mut Buffer) {
+    let scaled = image.resized(area.width as u32, area.height as u32 * 2);
+    for row in 0..area.height {
+        for col in 0..area.width {
+            let [tr, tg, tb] = scaled.pixel(col as u32, row as u32 * 2);
+            let [br, bg, bb] = scaled.pixel(col as u32, row as u32 * 2 + 1);
+            if let Some(cell) = buf.cell_mut((area.x + col, area.y + row)) {
+                cell.set_char('▀')
+                    .set_fg(Color::Rgb(tr, tg, tb))
+                    .set_bg(Color::Rgb(br, bg, bb));
+            }
+        }
+    }
+}
+
+/// Mark the cells of `area` so ratatui leaves the image drawn there alone
+pub fn skip_cells(area: Rect, buf: &mut Buffer) {
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.set_skip(true);
+            }
+        }
+    }
+}
+
+/// Escape sequence drawing the image over `cols`x`rows` cells at the cursor
+pub fn encode(protocol: GraphicsProtocol, image: &RgbImage, cols: u16, rows: u16) -> String {
+    match protocol {
+        GraphicsProtocol::Kitty => kitty_sequence(image, cols, rows),
+        GraphicsProtocol::Sixel => {
+            let (cw, ch) = protocol.cell_size();
+            sixel_sequence(&image.resized(cols as u32 * cw, rows as u32 * ch))
+        }
+        GraphicsProtocol::HalfBlock => String::new(),
+    }
+}
+
+/// Escape sequence removing images drawn with `protocol`, if it needs one
+/// (sixel pixels are replaced when ratatui redraws the cells)
+pub fn clear_sequence(protocol: GraphicsProtocol) -> &'static str {
+    match protocol {
+        GraphicsProtocol::Kitty => "\x1b_Ga=d,d=A,q=2\x1b\\",
+        _ => "",
+    }
+}
+
+/// Kitty graphics: raw RGB, scaled by the terminal to the cells, cursor kept
+/// in place and replies suppressed (they would arrive as key presses)
+fn kitty_sequence(image: &RgbImage, cols: u16, rows: u16) -> String {
+    let data = base64::engine::general_purpose::STANDARD.encode(&image.pixels);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::from(clear_sequence(GraphicsProtocol::Kitty));
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},C=1,q=2,m={};{}\x1b\\",
+                image.width, image.height, cols, rows, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+/// Sixel graphics with a fixed 216-colour palette
+fn sixel_sequence(image: &RgbImage) -> String {
+    let levels = SIXEL_LEVELS;
+    let quantize = |v: u8| (v as u32 * (levels - 1) + 127) / 255;
+    let (w, h) = (image.width, image.height);
+    let indices: Vec<u32> = image
+        .pixels
+        .chunks(3)
+        .map(|p| (quantize(p[0]) * levels + quantize(p[1])) * levels + quantize(p[2]))
+        .collect();
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", w, h);
+    let palette = levels * levels * levels;
+    for i in 0..palette {
+        let percent = |level: u32| level * 100 / (levels - 1);
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            i,
+            percent(i / (levels * levels)),
+            percent(i / levels % levels),
+            percent(i % levels)
+        ));
+    }
+
+    for band in (0..h).step_by(6) {
+        let band_rows = (band..(band + 6).min(h)).collect::<Vec<u32>>();
+        let mut used = vec![false; palette as usize];
+        for &y in &band_rows {
+            for x in 0..w {
+                used[indices[(y * w + x) as usize] as usize] = true;
+            }
+        }
+
+        let mut first = true;
+        for color in (0..palette).filter(|c| used[*c as usize]) {
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            out.push_str(&format!("#{}", color));
+
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..w {
+                let bits = band_rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &y)| indices[(y * w + x) as usize] == color)
+                    .fold(0u8, |bits, (dy, _)| bits | (1 << dy));
+                let ch = (63 + bits) as char;
+                run = match run {
+                    Some((c, n)) if c == ch => Some((c, n + 1)),
+                    Some(previous) => {
+                        push_sixel_run(&mut out, previous);
+                        Some((ch, 1))
+                    }
+                    None => Some((ch, 1)),
+                };
+            }
+            if let Some(last) = run {
+                push_sixel_run(&mut out, last);
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_sixel_run(out: &mut String, (ch, count): (char, usize)) {
+    if count > 3 {
+        out.push_str(&format!("!{}{}", count, ch));
+    } else {
+        out.extend(std::iter::repeat_n(ch, count));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_protocol() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "xterm-kitty")])),
+            GraphicsProtocol::Kitty
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "foot")])),
+            GraphicsProtocol::Sixel
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "xterm-256color")])),
+            GraphicsProtocol::HalfBlock
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[
+                ("TERM", "xterm-kitty"),
+                ("YTSM_GRAPHICS", "halfblock")
+            ])),
+            GraphicsProtocol::HalfBlock
+        );
+    }
+
+    #[test]
+    fn test_fit_cells() {
+        // 9:16 image in 40x20 cells of 8x16 px: height bound
+        assert_eq!(fit_cells((270, 480), (40, 20), (8, 16)), (22, 20));
+        // Half blocks: two square pixels per cell
+        assert_eq!(fit_cells((270, 480), (40, 20), (1, 2)), (22, 20));
+        assert_eq!(fit_cells((480, 270), (40, 40), (1, 2)), (40, 11));
+    }
+
+    #[test]
+    fn test_draw_box_and_number() {
+        let mut image = RgbImage::new(20, 20);
+        image.draw_box(-5, 2, 10, 5, 1, [255, 0, 0]);
+        assert_eq!(image.pixel(4, 2), [255, 0, 0]);
+        assert_eq!(image.pixel(4, 6), [255, 0, 0]);
+        assert_eq!(image.pixel(2, 4), [0, 0, 0]);
+
+        image.draw_number(10, 10, 1, 1, [0, 255, 0]);
+        // Top of the "1" glyph is its middle column
+        assert_eq!(image.pixel(12, 11), [0, 255, 0]);
+        assert_eq!(image.pixel(11, 11), [0, 0, 0]);
+
+        // Pasting clips to the image
+        let mut tile = RgbImage::new(4, 4);
+        tile.draw_box(0, 0, 4, 4, 2, [0, 0, 255]);
+        image.paste(&tile, 18, 0);
+        assert_eq!(image.pixel(19, 1), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_encoders() {
+        let mut image = RgbImage::new(4, 8);
+        image.draw_box(0, 0, 4, 8, 1, [255, 255, 255]);
+
+        let sixel = sixel_sequence(&image);
+        assert!(sixel.starts_with("\x1bPq\"1;1;4;8"));
+        assert!(sixel.ends_with("-\x1b\\"));
+        // Two bands of six rows, black and white in each
+        assert_eq!(sixel.matches('-').count(), 2);
+        assert!(sixel.contains("#0") && sixel.contains("#215"));
+
+        let large = RgbImage::new(64, 64);
+        let kitty = kitty_sequence(&large, 10, 5);
+        assert!(kitty.contains("a=T,f=24,s=64,v=64,c=10,r=5,C=1,q=2,m=1;"));
+        assert!(kitty.ends_with("\x1b\\"));
+        assert_eq!(kitty.matches("m=0;").count(), 1);
+    }
+}
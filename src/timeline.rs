@@ -0,0 +1,477 @@
+//! Output timeline for YT ShortMaker
+//! Speed ramps and the maximum duration policy of an export
+//!
+//! A clip is played as a list of segments, each with its own speed. Speed
+//! ramps come from a sidecar file next to the clip ("clip.speed.json"); the
+//! export profile decides what happens when the result is still too long.
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::animation::{format_number, TimeValue};
+
+/// Speed-up used by `over_limit: "speed"` when `max_speed` is not set
+pub const DEFAULT_MAX_SPEED: f64 = 1.5;
+/// Slowest and fastest speed accepted in a ramp
+const MIN_RAMP_SPEED: f64 = 0.25;
+const MAX_RAMP_SPEED: f64 = 4.0;
+/// Length differences below this are rounding noise
+const EPSILON: f64 = 0.001;
+
+/// What to do with a clip longer than `max_duration`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OverLimit {
+    /// Cut the end of the clip
+    #[default]
+    Trim,
+    /// Speed the whole clip up (up to `max_speed`), then cut what is left
+    Speed,
+    /// Skip the export with an error
+    Fail,
+}
+
+fn is_default_over_limit(value: &OverLimit) -> bool {
+    *value == OverLimit::Trim
+}
+
+/// Maximum duration policy, part of an export profile
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct DurationPolicy {
+    /// Longest allowed output in seconds (e.g. 60 for Shorts). No limit if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<f64>,
+    /// What to do when the clip is longer: trim, speed or fail
+    #[serde(default, skip_serializing_if = "is_default_over_limit")]
+    pub over_limit: OverLimit,
+    /// Fastest uniform speed-up for `over_limit: "speed"` (default 1.5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_speed: Option<f64>,
+}
+
+/// A sub-range of the clip played at another speed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpeedRamp {
+    /// Start of the range (seconds, "50%" or negative from the end)
+    pub start: TimeValue,
+    /// End of the range
+    pub end: TimeValue,
+    /// Playback speed (2.0 = twice as fast, 0.5 = slow motion)
+    pub speed: f64,
+}
+
+/// Content of a speed sidecar file
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SpeedRamps {
+    #[serde(default)]
+    pub ramps: Vec<SpeedRamp>,
+}
+
+/// Sidecar file holding the speed ramps of a clip ("clip.mp4" -> "clip.speed.json")
+pub fn sidecar_path(clip_path: &str) -> PathBuf {
+    Path::new(clip_path).with_extension("speed.json")
+}
+
+/// Load the speed ramps of a clip, empty if there are none
+pub fn load_ramps(clip_path: &str) -> Vec<SpeedRamp> {
+    let path = sidecar_path(clip_path);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<SpeedRamps>(&content) {
+        Ok(file) => file.ramps,
+        Err(e) => {
+            log::warn!("Ignoring invalid speed file {:?}: {}", path, e);
+            Vec::new()
+        }
+    }
+}
+
+/// A range of the source clip and the speed it is played at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub speed: f64,
+}
+
+impl Segment {
+    /// Length of the segment in the output
+    pub fn output_length(&self) -> f64 {
+        (self.end - self.start) / self.speed
+    }
+}
+
+/// The parts of a clip that end up in the output, in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    pub segments: Vec<Segment>,
+}
+
+impl Timeline {
+    /// Build the timeline of a clip of `duration` seconds
+    ///
+    /// Ramps are applied first; the policy then trims or speeds up the result.
+    /// Fails when the policy is `fail` and the clip is too long.
+    pub fn build(duration: f64, ramps: &[SpeedRamp], policy: &DurationPolicy) -> Result<Self> {
+        let mut timeline = Self::with_ramps(duration, ramps);
+
+        let Some(max) = policy.max_duration.filter(|m| *m > 0.0) else {
+            return Ok(timeline);
+        };
+        let length = timeline.output_duration();
+        if length <= max + EPSILON {
+            return Ok(timeline);
+        }
+
+        match policy.over_limit {
+            OverLimit::Trim => {}
+            OverLimit::Speed => {
+                let max_speed = policy.max_speed.unwrap_or(DEFAULT_MAX_SPEED).max(1.0);
+                let factor = (length / max).min(max_speed);
+                for segment in &mut timeline.segments {
+                    segment.speed *= factor;
+                }
+            }
+            OverLimit::Fail => {
+                return Err(anyhow!(
+                    "Output would last {:.1}s, over the {}s limit",
+                    length,
+                    format_number(max)
+                ));
+            }
+        }
+        timeline.trim_to(max);
+        Ok(timeline)
+    }
+
+    /// Split the clip at the ramps; overlapping ramps keep the earlier one
+    fn with_ramps(duration: f64, ramps: &[SpeedRamp]) -> Self {
+        let mut ranges: Vec<(f64, f64, f64)> = ramps
+            .iter()
+            .filter(|r| r.speed > 0.0)
+            .map(|r| {
+                (
+                    r.start.resolve(duration).clamp(0.0, duration),
+                    r.end.resolve(duration).clamp(0.0, duration),
+                    r.speed.clamp(MIN_RAMP_SPEED, MAX_RAMP_SPEED),
+                )
+            })
+            .filter(|(start, end, _)| end - start > EPSILON)
+            .collect();
+        ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut segments = Vec::new();
+        let mut cursor = 0.0;
+        for (start, end, speed) in ranges {
+            let start = start.max(cursor);
+            if end - start <= EPSILON {
+                continue;
+            }
+            if start - cursor > EPSILON {
+                segments.push(Segment {
+                    start: cursor,
+                    end: start,
+                    speed: 1.0,
+                });
+            }
+            segments.push(Segment { start, end, speed });
+            cursor = end;
+        }
+        if duration - cursor > EPSILON || segments.is_empty() {
+            segments.push(Segment {
+                start: cursor,
+                end: duration,
+                speed: 1.0,
+            });
+        }
+        Self { segments }
+    }
+
+    /// Drop whatever plays after `max` seconds of output
+    fn trim_to(&mut self, max: f64) {
+        let mut elapsed = 0.0;
+        let mut kept = Vec::new();
+        for segment in &self.segments {
+            let remaining = max - elapsed;
+            if remaining <= EPSILON {
+                break;
+            }
+            let length = segment.output_length();
+            if length <= remaining {
+                kept.push(*segment);
+                elapsed += length;
+            } else {
+                kept.push(Segment {
+                    end: segment.start + remaining * segment.speed,
+                    ..*segment
+                });
+                break;
+            }
+        }
+        self.segments = kept;
+    }
+
+    /// Length of the output in seconds
+    pub fn output_duration(&self) -> f64 {
+        self.segments.iter().map(Segment::output_length).sum()
+    }
+
+    /// Output time of the source time `time`, None if that part is cut
+    pub fn output_time(&self, time: f64) -> Option<f64> {
+        let mut elapsed = 0.0;
+        for segment in &self.segments {
+            if time >= segment.start - EPSILON && time <= segment.end + EPSILON {
+                return Some(elapsed + (time - segment.start).max(0.0) / segment.speed);
+            }
+            elapsed += segment.output_length();
+        }
+        None
+    }
+
+    /// Whether the clip plays untouched from 0 to `duration`
+    pub fn is_identity(&self, duration: f64) -> bool {
+        matches!(
+            self.segments.as_slice(),
+            [s] if s.start <= EPSILON && (s.end - duration).abs() <= EPSILON && s.speed == 1.0
+        )
+    }
+
+    /// Filter chains that cut `[video]` (and `[audio]`) into the timeline
+    ///
+    /// Labels are given and returned without brackets; new labels start with
+    /// `prefix`. Returns the filters and the resulting video and audio labels.
+    pub fn filters(
+        &self,
+        video: &str,
+        audio: Option<&str>,
+        prefix: &str,
+    ) -> (Vec<String>, String, Option<String>) {
+        let mut filters = Vec::new();
+        let n = self.segments.len();
+
+        // A label can only be read once: split the streams per segment
+        let sources = |label: &str, split: &str, kind: &str, filters: &mut Vec<String>| {
+            if n == 1 {
+                return vec![label.to_string()];
+            }
+            let parts: Vec<String> = (0..n).map(|i| format!("{}{}{}", prefix, kind, i)).collect();
+            filters.push(format!(
+                "[{}]{}={}{}",
+                label,
+                split,
+                n,
+                parts.iter().map(|p| format!("[{}]", p)).collect::<String>()
+            ));
+            parts
+        };
+
+        let video_sources = sources(video, "split", "sv", &mut filters);
+        let audio_sources = audio.map(|a| sources(a, "asplit", "sa", &mut filters));
+
+        let mut video_parts = Vec::new();
+        let mut audio_parts = Vec::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            let v = format!("{}v{}", prefix, i);
+            filters.push(format!(
+                "[{}]{},{}[{}]",
+                video_sources[i],
+                segment.trim_args("trim"),
+                segment.setpts(),
+                v
+            ));
+            video_parts.push(v);
+
+            if let Some(ref sources) = audio_sources {
+                let a = format!("{}a{}", prefix, i);
+                let mut chain = format!(
+                    "[{}]{},asetpts=PTS-STARTPTS",
+                    sources[i],
+                    segment.trim_args("atrim")
+                );
+                for tempo in atempo_factors(segment.speed) {
+                    chain.push_str(&format!(",atempo={}", format_number(tempo)));
+                }
+                filters.push(format!("{}[{}]", chain, a));
+                audio_parts.push(a);
+            }
+        }
+
+        if n == 1 {
+            return (filters, video_parts.remove(0), audio_parts.pop());
+        }
+
+        let v = format!("{}v", prefix);
+        if audio_sources.is_some() {
+            let a = format!("{}a", prefix);
+            let pads: String = video_parts
+                .iter()
+                .zip(&audio_parts)
+                .map(|(v, a)| format!("[{}][{}]", v, a))
+                .collect();
+            filters.push(format!("{}concat=n={}:v=1:a=1[{}][{}]", pads, n, v, a));
+            (filters, v, Some(a))
+        } else {
+            let pads: String = video_parts.iter().map(|v| format!("[{}]", v)).collect();
+            filters.push(format!("{}concat=n={}:v=1:a=0[{}]", pads, n, v));
+            (filters, v, None)
+        }
+    }
+}
+
+impl Segment {
+    fn trim_args(&self, filter: &str) -> String {
+        if self.start <= EPSILON {
+            format!("{}=duration={}", filter, format_number(self.end))
+        } else {
+            format!(
+                "{}=start={}:end={}",
+                filter,
+                format_number(self.start),
+                format_number(self.end)
+            )
+        }
+    }
+
+    fn setpts(&self) -> String {
+        if self.speed == 1.0 {
+            "setpts=PTS-STARTPTS".to_string()
+        } else {
+            format!("setpts=(PTS-STARTPTS)/{}", format_number(self.speed))
+        }
+    }
+}
+
+/// `atempo` factors for a speed, each within the 0.5 - 2.0 range of the filter
+/// (pitch is preserved, unlike resampling)
+fn atempo_factors(speed: f64) -> Vec<f64> {
+    let mut factors = Vec::new();
+    let mut rest = speed;
+    while rest > 2.0 + EPSILON {
+        factors.push(2.0);
+        rest /= 2.0;
+    }
+    while rest < 0.5 - EPSILON {
+        factors.push(0.5);
+        rest /= 0.5;
+    }
+    if (rest - 1.0).abs() > EPSILON {
+        factors.push(rest);
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max: f64, over_limit: OverLimit) -> DurationPolicy {
+        DurationPolicy {
+            max_duration: Some(max),
+            over_limit,
+            max_speed: None,
+        }
+    }
+
+    fn ramp(start: f64, end: f64, speed: f64) -> SpeedRamp {
+        SpeedRamp {
+            start: TimeValue::Seconds(start),
+            end: TimeValue::Seconds(end),
+            speed,
+        }
+    }
+
+    #[test]
+    fn test_policy_trim_speed_fail() {
+        let none = Timeline::build(90.0, &[], &DurationPolicy::default()).unwrap();
+        assert!(none.is_identity(90.0));
+
+        let trimmed = Timeline::build(90.0, &[], &policy(60.0, OverLimit::Trim)).unwrap();
+        assert_eq!(trimmed.segments.len(), 1);
+        assert!((trimmed.output_duration() - 60.0).abs() < 1e-9);
+        assert!(!trimmed.is_identity(90.0));
+
+        // 70s needs 1.1666x, within the default 1.5x
+        let sped = Timeline::build(70.0, &[], &policy(60.0, OverLimit::Speed)).unwrap();
+        assert_eq!(sped.segments[0].end, 70.0);
+        assert!((sped.output_duration() - 60.0).abs() < 1e-9);
+
+        // 120s would need 2x: capped at 1.5x, then trimmed
+        let capped = Timeline::build(120.0, &[], &policy(60.0, OverLimit::Speed)).unwrap();
+        assert_eq!(capped.segments[0].speed, 1.5);
+        assert!((capped.segments[0].end - 90.0).abs() < 1e-9);
+
+        assert!(Timeline::build(61.0, &[], &policy(60.0, OverLimit::Fail)).is_err());
+        assert!(Timeline::build(60.0, &[], &policy(60.0, OverLimit::Fail)).is_ok());
+    }
+
+    #[test]
+    fn test_speed_ramps() {
+        let ramps = vec![ramp(10.0, 20.0, 2.0), ramp(15.0, 25.0, 4.0)];
+        let timeline = Timeline::build(30.0, &ramps, &DurationPolicy::default()).unwrap();
+        let ranges: Vec<(f64, f64, f64)> = timeline
+            .segments
+            .iter()
+            .map(|s| (s.start, s.end, s.speed))
+            .collect();
+        // The overlapping ramp starts where the first one ends
+        assert_eq!(
+            ranges,
+            vec![
+                (0.0, 10.0, 1.0),
+                (10.0, 20.0, 2.0),
+                (20.0, 25.0, 4.0),
+                (25.0, 30.0, 1.0)
+            ]
+        );
+        assert!((timeline.output_duration() - 21.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_timeline_filters() {
+        let identity = Timeline::build(10.0, &[], &DurationPolicy::default()).unwrap();
+        let (filters, v, a) = identity.filters("out0", Some("0:a"), "tl");
+        assert_eq!(
+            filters,
+            vec![
+                "[out0]trim=duration=10,setpts=PTS-STARTPTS[tlv0]",
+                "[0:a]atrim=duration=10,asetpts=PTS-STARTPTS[tla0]"
+            ]
+        );
+        assert_eq!((v.as_str(), a.as_deref()), ("tlv0", Some("tla0")));
+
+        let ramped =
+            Timeline::build(10.0, &[ramp(4.0, 10.0, 3.0)], &DurationPolicy::default()).unwrap();
+        let (filters, v, a) = ramped.filters("out0", None, "tl");
+        assert_eq!(a, None);
+        assert_eq!(v, "tlv");
+        assert_eq!(filters[0], "[out0]split=2[tlsv0][tlsv1]");
+        assert_eq!(
+            filters[2],
+            "[tlsv1]trim=start=4:end=10,setpts=(PTS-STARTPTS)/3[tlv1]"
+        );
+        assert_eq!(filters[3], "[tlv0][tlv1]concat=n=2:v=1:a=0[tlv]");
+
+        let (filters, _, a) = ramped.filters("out0", Some("0:a"), "tl");
+        assert_eq!(a.as_deref(), Some("tla"));
+        assert!(filters.contains(
+            &"[tlsa1]atrim=start=4:end=10,asetpts=PTS-STARTPTS,atempo=2,atempo=1.5[tla1]"
+                .to_string()
+        ));
+        assert!(filters
+            .last()
+            .unwrap()
+            .ends_with("concat=n=2:v=1:a=1[tlv][tla]"));
+    }
+
+    #[test]
+    fn test_atempo_factors() {
+        assert!(atempo_factors(1.0).is_empty());
+        assert_eq!(atempo_factors(1.25), vec![1.25]);
+        assert_eq!(atempo_factors(2.0), vec![2.0]);
+        assert_eq!(atempo_factors(4.0), vec![2.0, 2.0]);
+        assert_eq!(atempo_factors(0.25), vec![0.5, 0.5]);
+    }
+}
@@ -1,7 +1,8 @@
 //! Terminal User Interface module for YT ShortMaker
 //! Built with Ratatui for a rich interactive experience
 
-use std::io::{self, Stdout};
+use std::cell::Cell;
+use std::io::{self, Stdout, Write};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -10,8 +11,9 @@ use std::time::Instant;
 
 use anyhow::Result;
 use crossterm::{
+    cursor::MoveTo,
     event::{DisableMouseCapture, EnableMouseCapture, KeyCode},
-    execute,
+    execute, queue,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
@@ -25,6 +27,7 @@ use ratatui::{
 use tokio::sync::mpsc;
 
 use crate::config::AppConfig;
+use crate::termimage::{GraphicsProtocol, RgbImage};
 use crate::types::{VideoMoment, APP_NAME, APP_VERSION};
 
 /// Messages sent from background tasks to the TUI
@@ -42,8 +45,15 @@ pub enum AppMessage {
     Complete(String),
     /// Error occurred
     Error(String),
-    /// Shorts generation confirmation
-    RequestShortsConfirm(usize),
+    /// Analysis finished: review the moments before extraction
+    RequestMomentReview,
+    /// Preview frame of the selected plano rendered (image path) or failed
+    ExportPreview(Result<String, String>),
+    /// Preview of the plano editor rendered (image path) or failed
+    EditorPreview(Result<String, String>),
+    /// Frames of a moment in the review loaded for the preview generation
+    /// they were requested at
+    ReviewFrames(u64, Result<RgbImage, String>),
 
     /// Processing finished, ready to exit
     Finished,
@@ -77,8 +87,8 @@ pub enum AppScreen {
     FormatConfirm,
     /// Main processing dashboard
     Processing,
-    /// Shorts generation confirmation
-    ShortsConfirm(usize),
+    /// Review and edit the moments before extraction
+    MomentReview,
 
     /// Completed
     Done,
@@ -104,6 +114,8 @@ pub enum AppScreen {
     ExportSelectPlano,
     /// Preview the export result
     ExportPreview,
+    /// Edit the layers of the highlighted plano
+    PlanoEditor,
     /// Export processing
     ExportProcessing,
     /// Confirmation for cancelling export processing
@@ -140,6 +152,32 @@ pub struct SettingItem {
     pub description: String,
 }
 
+/// Size of each moment frame in the review (start and end are stacked)
+const REVIEW_FRAME_SIZE: (u32, u32) = (320, 180);
+
+/// Time without edits in the review before the session is saved
+pub const REVIEW_SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Start and end frames of a moment taken from `video`, stacked
+pub fn load_review_frames(video: &str, start: &str, end: &str) -> Result<RgbImage> {
+    let (width, height) = REVIEW_FRAME_SIZE;
+    let frame = |timestamp: &str| {
+        let seconds = crate::video::parse_timestamp_to_seconds(timestamp)?;
+        RgbImage::load_frame(video, seconds as f64, width, height)
+    };
+    let (start, end) = (frame(start)?, frame(end)?);
+    let mut frames = RgbImage::new(width, height * 2 + 8);
+    frames.paste(&start, 0, 0);
+    frames.paste(&end, 0, height as i64 + 8);
+    Ok(frames)
+}
+
+/// Size of the preview frame kept for drawing in the terminal (1/4 of the output)
+const PREVIEW_IMAGE_SIZE: (u32, u32) = (
+    crate::exporter::OUTPUT_WIDTH / 4,
+    crate::exporter::OUTPUT_HEIGHT / 4,
+);
+
 /// Main application state
 pub struct App {
     /// Current screen
@@ -167,6 +205,8 @@ pub struct App {
     pub language_index: usize,
     /// Found moments
     pub moments: Vec<VideoMoment>,
+    /// Moments loaded from a file, extracted without analysis
+    pub imported_moments: Option<Vec<VideoMoment>>,
     /// User input buffer
     pub input: String,
     /// Cursor position in input
@@ -206,10 +246,16 @@ pub struct App {
     // -- Export Shorts State --
     /// Selected folders containing clips
     pub export_clip_folders: Vec<String>,
-    /// Selected plano file path
-    pub export_plano_path: Option<String>,
-    /// Loaded plano objects
-    pub export_plano: Vec<crate::exporter::PlanoObject>,
+    /// Selected planos (each one produces its own output per clip)
+    pub export_planos: Vec<crate::exporter::LoadedPlano>,
+    /// Highlighted plano, used for preview and editing
+    pub export_plano_index: usize,
+    /// Planos offered by the library (presets and saved planos)
+    pub export_library: Vec<crate::library::LibraryEntry>,
+    /// Highlighted library entry
+    pub export_library_index: usize,
+    /// Whether the arrow keys move in the library (true) or in the selection
+    pub export_library_focus: bool,
     /// Export folder selection index
     pub export_folder_index: usize,
     /// Path to generated preview image
@@ -218,6 +264,68 @@ pub struct App {
     pub export_output_dir: Option<String>,
     /// Video path for preview (instead of fallback image)
     pub export_preview_video_path: Option<String>,
+    /// Set when the preview frame should be rendered (picked up by the main loop)
+    pub export_preview_requested: bool,
+    /// Whether the preview frame is being rendered
+    pub export_preview_running: bool,
+    /// Open the preview frame outside the terminal once it is rendered
+    pub export_preview_open: bool,
+    /// Set when a preview clip should be rendered (picked up by the main loop)
+    pub export_preview_clip_requested: bool,
+    /// Preview frame shown inside the terminal
+    pub export_preview_image: Option<RgbImage>,
+    /// Draw layer boxes and indices over the preview
+    pub export_preview_boxes: bool,
+    /// Bumped whenever the drawn preview changes (image, boxes, protocol)
+    pub export_preview_generation: u64,
+    /// Cells of the preview image, when drawn with kitty/sixel (set while rendering)
+    export_preview_area: Cell<Option<Rect>>,
+    /// Kitty/sixel image currently on screen: (cells, generation, protocol)
+    graphics_shown: Option<(Rect, u64, GraphicsProtocol)>,
+    /// How images are drawn in this terminal
+    pub graphics_protocol: GraphicsProtocol,
+
+    // -- Plano Editor State --
+    /// Plano open in the editor
+    pub plano_editor: Option<crate::plano_editor::PlanoEditor>,
+    /// Whether the arrow keys move in the fields (true) or in the layers
+    pub plano_editor_fields_focus: bool,
+    /// Text of the field being edited
+    pub plano_editor_input: Option<String>,
+    /// A preview of the edited plano should be rendered (in the background)
+    pub plano_editor_preview_requested: bool,
+    /// A preview of the edited plano is being rendered
+    pub plano_editor_preview_running: bool,
+    /// Esc was pressed once with unsaved changes
+    pub plano_editor_discard_warned: bool,
+
+    // -- Moment Review State --
+    /// Highlighted moment
+    pub review_index: usize,
+    /// Whether the arrow keys nudge the end (true) or the start of the moment
+    pub review_end_focus: bool,
+    /// Moment text being edited: which field and the text typed so far
+    pub review_input: Option<(ReviewField, String)>,
+    /// Video the moment frames are taken from (the low-res download)
+    pub review_video: Option<String>,
+    /// Start and end frames of the highlighted moment
+    review_frames: Option<RgbImage>,
+    /// Set when the frames of the highlighted moment changed (picked up by the main loop)
+    pub review_frames_requested: bool,
+    /// Whether the moment frames are being loaded
+    pub review_frames_running: bool,
+    /// When moments were last edited, while the session is not saved yet
+    /// (picked up by the main loop)
+    pub review_dirty: Option<Instant>,
+}
+
+/// Text of a moment edited in the review screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewField {
+    Start,
+    End,
+    Category,
+    Description,
 }
 
 impl App {
@@ -247,6 +355,7 @@ impl App {
             config: None,
             has_error: false,
             menu_index: 0,
+            imported_moments: None,
             settings_index: 0,
             language_index: 0,
             editing_setting: false,
@@ -255,12 +364,39 @@ impl App {
             api_keys_index: 0,
             cancellation_token: Arc::new(AtomicBool::new(false)),
             export_clip_folders: Vec::new(),
-            export_plano_path: None,
-            export_plano: Vec::new(),
+            export_planos: Vec::new(),
+            export_plano_index: 0,
+            export_library: Vec::new(),
+            export_library_index: 0,
+            export_library_focus: true,
             export_folder_index: 0,
             export_preview_path: None,
             export_output_dir: None,
             export_preview_video_path: None,
+            export_preview_requested: false,
+            export_preview_running: false,
+            export_preview_open: false,
+            export_preview_clip_requested: false,
+            export_preview_image: None,
+            export_preview_boxes: false,
+            export_preview_generation: 0,
+            export_preview_area: Cell::new(None),
+            graphics_shown: None,
+            graphics_protocol: GraphicsProtocol::detect(),
+            plano_editor: None,
+            plano_editor_fields_focus: false,
+            plano_editor_input: None,
+            plano_editor_preview_requested: false,
+            plano_editor_preview_running: false,
+            plano_editor_discard_warned: false,
+            review_index: 0,
+            review_end_focus: false,
+            review_input: None,
+            review_video: None,
+            review_frames: None,
+            review_frames_requested: false,
+            review_frames_running: false,
+            review_dirty: None,
         }
     }
 
@@ -310,6 +446,41 @@ impl App {
                     kind: SettingType::Float,
                     description: rust_i18n::t!("desc_zoom").to_string(),
                 },
+                SettingItem {
+                    name: "Jump Cuts".to_string(),
+                    key: "jump_cut".to_string(),
+                    value: config.jump_cut.enabled.to_string(),
+                    kind: SettingType::Bool,
+                    description: rust_i18n::t!("desc_jump_cut").to_string(),
+                },
+                SettingItem {
+                    name: "Silence Threshold".to_string(),
+                    key: "silence_threshold".to_string(),
+                    value: config.jump_cut.threshold_db.to_string(),
+                    kind: SettingType::Float,
+                    description: rust_i18n::t!("desc_silence_threshold").to_string(),
+                },
+                SettingItem {
+                    name: "Min Silence".to_string(),
+                    key: "min_silence".to_string(),
+                    value: config.jump_cut.min_silence.to_string(),
+                    kind: SettingType::Float,
+                    description: rust_i18n::t!("desc_min_silence").to_string(),
+                },
+                SettingItem {
+                    name: "Silence Padding".to_string(),
+                    key: "silence_padding".to_string(),
+                    value: config.jump_cut.padding.to_string(),
+                    kind: SettingType::Float,
+                    description: rust_i18n::t!("desc_silence_padding").to_string(),
+                },
+                SettingItem {
+                    name: "NLE Timelines".to_string(),
+                    key: "timelines".to_string(),
+                    value: config.export_timelines.to_string(),
+                    kind: SettingType::Bool,
+                    description: rust_i18n::t!("desc_timelines").to_string(),
+                },
                 SettingItem {
                     name: "Use Fast Model".to_string(),
                     key: "fast_model".to_string(),
@@ -340,6 +511,13 @@ impl App {
                         config.shorts_config.background_opacity = val.parse().unwrap_or(0.4)
                     }
                     "zoom" => config.shorts_config.main_video_zoom = val.parse().unwrap_or(0.7),
+                    "jump_cut" => config.jump_cut.enabled = val.parse().unwrap_or(false),
+                    "silence_threshold" => {
+                        config.jump_cut.threshold_db = val.parse().unwrap_or(-35.0)
+                    }
+                    "min_silence" => config.jump_cut.min_silence = val.parse().unwrap_or(0.6),
+                    "silence_padding" => config.jump_cut.padding = val.parse().unwrap_or(0.15),
+                    "timelines" => config.export_timelines = val.parse().unwrap_or(false),
                     "fast_model" => config.use_fast_model = val.parse().unwrap_or(true),
                     _ => {}
                 }
@@ -730,11 +908,11 @@ impl App {
                     if self.menu_index > 0 {
                         self.menu_index -= 1;
                     } else {
-                        self.menu_index = 6; // Loop to bottom (7 items: 0-6)
+                        self.menu_index = 7; // Loop to bottom (8 items: 0-7)
                     }
                 }
                 KeyCode::Down => {
-                    if self.menu_index < 6 {
+                    if self.menu_index < 7 {
                         self.menu_index += 1;
                     } else {
                         self.menu_index = 0; // Loop to top
@@ -747,14 +925,50 @@ impl App {
                             self.input.clear();
                             self.cursor_pos = 0;
                             self.moments.clear();
+                            self.imported_moments = None;
                         }
                         1 => {
+                            // Import Moments
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Moments", &["json", "csv", "txt"])
+                                .pick_file()
+                            {
+                                let path_str = path.to_string_lossy().to_string();
+                                match crate::moments::load_moments(&path_str) {
+                                    Ok(moments) => {
+                                        self.log(
+                                            LogLevel::Success,
+                                            rust_i18n::t!(
+                                                "import_moments_loaded",
+                                                count = moments.len(),
+                                                path = path_str
+                                            )
+                                            .to_string(),
+                                        );
+                                        self.imported_moments = Some(moments);
+                                        self.screen = AppScreen::UrlInput;
+                                        self.input.clear();
+                                        self.cursor_pos = 0;
+                                        self.moments.clear();
+                                    }
+                                    Err(e) => self.log(
+                                        LogLevel::Error,
+                                        rust_i18n::t!(
+                                            "import_moments_error",
+                                            error = e.to_string()
+                                        )
+                                        .to_string(),
+                                    ),
+                                }
+                            }
+                        }
+                        2 => {
                             // Export Shorts
                             self.screen = AppScreen::ExportShorts;
                             self.export_clip_folders.clear();
                             self.export_folder_index = 0;
                         }
-                        2 => {
+                        3 => {
                             if let Some(config) = &self.config {
                                 self.language_index = match config.language.as_str() {
                                     "es" => 1,
@@ -764,12 +978,12 @@ impl App {
                             }
                             self.screen = AppScreen::LanguageMenu;
                         }
-                        3 => {
+                        4 => {
                             self.reload_settings_items();
                             self.settings_index = 0;
                             self.screen = AppScreen::SettingsEditor;
                         }
-                        4 => {
+                        5 => {
                             // Security
                             // Initialize input state
                             if let Some(config) = &self.config {
@@ -784,12 +998,12 @@ impl App {
                             }
                             self.screen = AppScreen::SecuritySetup;
                         }
-                        5 => {
+                        6 => {
                             // API Keys
                             self.screen = AppScreen::ApiKeysManager;
                             self.api_keys_index = 0;
                         }
-                        6 => self.should_quit = true, // Exit
+                        7 => self.should_quit = true, // Exit
                         _ => {}
                     }
                 }
@@ -899,7 +1113,7 @@ impl App {
                 _ => {}
             },
 
-            AppScreen::ResumePrompt(_) | AppScreen::FormatConfirm | AppScreen::ShortsConfirm(_) => {
+            AppScreen::ResumePrompt(_) | AppScreen::FormatConfirm => {
                 match key {
                     KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
                         self.confirm_response = Some(true);
@@ -949,6 +1163,8 @@ impl App {
                     self.export_folder_index = 0;
                 }
                 KeyCode::Char('p') | KeyCode::Char('P') => {
+                    self.refresh_library();
+                    self.export_library_focus = self.export_planos.is_empty();
                     self.screen = AppScreen::ExportSelectPlano;
                 }
                 KeyCode::Char('t') | KeyCode::Char('T') => {
@@ -971,104 +1187,11 @@ impl App {
                 }
                 KeyCode::Char('v') | KeyCode::Char('V') => {
                     // Auto-reload plano if loaded from file
-                    if let Some(path) = &self.export_plano_path {
-                        match crate::exporter::load_plano(path) {
-                            Ok(plano) => {
-                                self.export_plano = plano;
-                                self.log(
-                                    LogLevel::Info,
-                                    rust_i18n::t!("export_plano_reloaded", path = path).to_string(),
-                                );
-                            }
-                            Err(e) => {
-                                self.log(
-                                    LogLevel::Error,
-                                    rust_i18n::t!(
-                                        "export_plano_reload_error",
-                                        error = e.to_string()
-                                    )
-                                    .to_string(),
-                                );
-                            }
-                        }
-                    }
-
-                    // Generate preview
-                    if !self.export_plano.is_empty() {
-                        self.log(
-                            LogLevel::Info,
-                            rust_i18n::t!("export_generating_preview").to_string(),
-                        );
-
-                        // Use unique filename to force image viewer to open new instance
-                        let timestamp = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs();
-                        let output_dir = std::env::temp_dir();
-                        let preview_filename = format!("yt_shortmaker_preview_{}.png", timestamp);
-                        let preview_path = output_dir.join(preview_filename);
-                        let preview_str = preview_path.to_string_lossy().to_string();
-
-                        let result = if let Some(video_path) = &self.export_preview_video_path {
-                            crate::exporter::generate_preview_from_video(
-                                video_path,
-                                &self.export_plano,
-                                &preview_str,
-                            )
-                        } else {
-                            crate::exporter::generate_preview_embedded(
-                                &self.export_plano,
-                                &preview_str,
-                            )
-                        };
-
-                        match result {
-                            Ok(_) => {
-                                self.export_preview_path = Some(preview_str.clone());
-                                self.log(
-                                    LogLevel::Success,
-                                    rust_i18n::t!("export_preview_generated", path = preview_str)
-                                        .to_string(),
-                                );
-
-                                self.log(
-                                    LogLevel::Info,
-                                    rust_i18n::t!("export_opening_preview").to_string(),
-                                );
-                                #[cfg(target_os = "windows")]
-                                {
-                                    let _ = std::process::Command::new("cmd")
-                                        .args(["/C", "start", "", &preview_str])
-                                        .spawn();
-                                }
-                                #[cfg(target_os = "macos")]
-                                {
-                                    let _ = std::process::Command::new("open")
-                                        .arg(&preview_str)
-                                        .spawn();
-                                }
-                                #[cfg(target_os = "linux")]
-                                {
-                                    let _ = std::process::Command::new("xdg-open")
-                                        .arg(&preview_str)
-                                        .spawn();
-                                }
-                            }
-                            Err(e) => {
-                                self.log(
-                                    LogLevel::Error,
-                                    rust_i18n::t!("export_preview_error", error = e.to_string())
-                                        .to_string(),
-                                );
-                            }
-                        }
+                    self.reload_selected_plano();
+                    if self.generate_preview() {
+                        // Opened outside the terminal once it is rendered
+                        self.export_preview_open = true;
                         self.screen = AppScreen::ExportPreview;
-                    } else {
-                        self.log(
-                            LogLevel::Warning,
-                            rust_i18n::t!("export_select_template_first").to_string(),
-                        );
                     }
                 }
                 KeyCode::Char('o') | KeyCode::Char('O') => {
@@ -1093,7 +1216,7 @@ impl App {
                             LogLevel::Warning,
                             rust_i18n::t!("export_select_clips_first").to_string(),
                         );
-                    } else if self.export_plano.is_empty() {
+                    } else if self.export_planos.is_empty() {
                         self.log(
                             LogLevel::Warning,
                             rust_i18n::t!("export_select_template").to_string(),
@@ -1157,60 +1280,115 @@ impl App {
                 _ => {}
             },
             AppScreen::ExportSelectPlano => match key {
+                KeyCode::Tab => {
+                    self.export_library_focus = !self.export_library_focus;
+                }
+                KeyCode::Up => {
+                    let index = if self.export_library_focus {
+                        &mut self.export_library_index
+                    } else {
+                        &mut self.export_plano_index
+                    };
+                    *index = index.saturating_sub(1);
+                }
+                KeyCode::Down => {
+                    let (index, len) = if self.export_library_focus {
+                        (&mut self.export_library_index, self.export_library.len())
+                    } else {
+                        (&mut self.export_plano_index, self.export_planos.len())
+                    };
+                    if *index + 1 < len {
+                        *index += 1;
+                    }
+                }
+                KeyCode::Char('a') | KeyCode::Char('A') | KeyCode::Char(' ') => {
+                    // Add the highlighted library plano to the selection
+                    self.add_library_plano();
+                }
+                KeyCode::Char('s') | KeyCode::Char('S') => {
+                    // Keep a copy of the highlighted selected plano in the library
+                    self.save_selected_to_library();
+                }
+                KeyCode::Char('o') | KeyCode::Char('O') => {
+                    let dir = crate::library::library_dir();
+                    let _ = std::fs::create_dir_all(&dir);
+                    if let Err(e) = open::that(&dir) {
+                        self.log(LogLevel::Error, format!("{}: {}", dir.display(), e));
+                    }
+                }
                 KeyCode::Char('l') | KeyCode::Char('L') => {
-                    // Load existing plano file
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("JSON", &["json"])
-                        .pick_file()
+                    // Load one or more existing plano files
+                    if let Some(paths) = rfd::FileDialog::new()
+                        .add_filter("Plano", &["json", "zip"])
+                        .pick_files()
                     {
-                        let path_str = path.to_string_lossy().to_string();
-                        match crate::exporter::load_plano(&path_str) {
-                            Ok(plano) => {
-                                self.export_plano_path = Some(path_str);
-                                self.export_plano = plano;
-                                self.log(LogLevel::Success, "Plantilla cargada".to_string());
+                        for path in paths {
+                            let path_str = path.to_string_lossy().to_string();
+                            if self.export_planos.iter().any(|p| p.path == path_str) {
+                                continue;
                             }
-                            Err(e) => {
-                                self.log(
-                                    LogLevel::Error,
-                                    format!("Error cargando plantilla: {}", e),
-                                );
+                            match crate::exporter::LoadedPlano::load(&path_str) {
+                                Ok(loaded) => {
+                                    self.export_planos.push(loaded);
+                                    self.export_plano_index = self.export_planos.len() - 1;
+                                    self.log(LogLevel::Success, "Plantilla cargada".to_string());
+                                    self.validate_selected_plano();
+                                }
+                                Err(e) => {
+                                    self.log(
+                                        LogLevel::Error,
+                                        format!("Error cargando plantilla: {}", e),
+                                    );
+                                }
                             }
                         }
                     }
                 }
-                KeyCode::Char('n') | KeyCode::Char('N') => {
-                    // Create new default plano
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("JSON", &["json"])
-                        .set_file_name("plano.json")
-                        .save_file()
+                // Remove highlighted plano from the selection
+                KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete
+                    if self.export_plano_index < self.export_planos.len() =>
+                {
+                    self.export_planos.remove(self.export_plano_index);
+                    if self.export_plano_index > 0
+                        && self.export_plano_index >= self.export_planos.len()
                     {
-                        let path_str = path.to_string_lossy().to_string();
-                        let default_plano = crate::exporter::create_default_plano();
-                        if let Err(e) = crate::exporter::save_plano(&path_str, &default_plano) {
-                            self.log(LogLevel::Error, format!("Error guardando plantilla: {}", e));
-                        } else {
-                            self.export_plano_path = Some(path_str);
-                            self.export_plano = default_plano;
-                            self.log(LogLevel::Success, "Plantilla creada".to_string());
-                        }
+                        self.export_plano_index -= 1;
                     }
                 }
+                KeyCode::Char('m') | KeyCode::Char('M') => {
+                    // Edit the layers of the highlighted plano here
+                    if self.export_library_focus {
+                        self.add_library_plano();
+                    }
+                    self.open_plano_editor();
+                }
+                KeyCode::Char('v') | KeyCode::Char('V') => {
+                    // Re-read the file (it may have been edited) and check it
+                    self.reload_selected_plano();
+                    self.validate_selected_plano();
+                }
                 KeyCode::Char('e') | KeyCode::Char('E') => {
-                    // Open in external editor
-                    if let Some(ref path) = self.export_plano_path {
+                    // Open the highlighted plano (library or selection) in an external editor
+                    let path = if self.export_library_focus {
+                        self.export_library
+                            .get(self.export_library_index)
+                            .and_then(|entry| crate::library::entry_file(entry).ok())
+                            .map(|path| path.to_string_lossy().to_string())
+                    } else {
+                        self.selected_plano().map(|p| p.path.clone())
+                    };
+                    if let Some(path) = path {
                         #[cfg(target_os = "windows")]
                         {
-                            let _ = std::process::Command::new("notepad").arg(path).spawn();
+                            let _ = std::process::Command::new("notepad").arg(&path).spawn();
                         }
                         #[cfg(not(target_os = "windows"))]
                         {
-                            let _ = std::process::Command::new("xdg-open").arg(path).spawn();
+                            let _ = std::process::Command::new("xdg-open").arg(&path).spawn();
                         }
                     }
                 }
-                KeyCode::Esc => {
+                KeyCode::Enter | KeyCode::Esc => {
                     self.screen = AppScreen::ExportShorts;
                 }
                 _ => {}
@@ -1218,87 +1396,43 @@ impl App {
             AppScreen::ExportPreview => match key {
                 KeyCode::Char('g') | KeyCode::Char('G') => {
                     // Auto-reload plano if loaded from file (Same logic as 'V' had)
-                    if let Some(path) = &self.export_plano_path {
-                        match crate::exporter::load_plano(path) {
-                            Ok(plano) => {
-                                self.export_plano = plano;
-                                self.log(
-                                    LogLevel::Info,
-                                    rust_i18n::t!("export_plano_reloaded", path = path).to_string(),
-                                );
-                            }
-                            Err(e) => {
-                                self.log(
-                                    LogLevel::Error,
-                                    rust_i18n::t!(
-                                        "export_plano_reload_error",
-                                        error = e.to_string()
-                                    )
-                                    .to_string(),
-                                );
-                            }
+                    self.reload_selected_plano();
+                    self.generate_preview();
+                }
+                KeyCode::Char('o') | KeyCode::Char('O') => {
+                    if let Some(path) = self.export_preview_path.clone() {
+                        self.log(
+                            LogLevel::Info,
+                            rust_i18n::t!("export_opening_preview").to_string(),
+                        );
+                        if let Err(e) = open::that(&path) {
+                            self.log(LogLevel::Error, format!("Failed to open preview: {}", e));
                         }
                     }
-
-                    // Generate preview
-                    if !self.export_plano.is_empty() {
+                }
+                KeyCode::Char('b') | KeyCode::Char('B') => {
+                    self.export_preview_boxes = !self.export_preview_boxes;
+                    self.export_preview_generation += 1;
+                }
+                KeyCode::Char('p') | KeyCode::Char('P') => {
+                    self.graphics_protocol = self.graphics_protocol.next();
+                    self.export_preview_generation += 1;
+                }
+                KeyCode::Char('c') | KeyCode::Char('C') => {
+                    self.reload_selected_plano();
+                    if self
+                        .selected_plano()
+                        .is_some_and(|p| !p.plano.layers.is_empty())
+                    {
                         self.log(
                             LogLevel::Info,
-                            rust_i18n::t!("export_generating_preview").to_string(),
-                        );
-
-                        // Use unique filename to force image viewer to open new instance
-                        let timestamp = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs();
-                        let output_dir = std::env::temp_dir();
-                        let preview_filename = format!("yt_shortmaker_preview_{}.png", timestamp);
-                        let preview_path = output_dir.join(preview_filename);
-                        let preview_str = preview_path.to_string_lossy().to_string();
-
-                        let result = if let Some(video_path) = &self.export_preview_video_path {
-                            crate::exporter::generate_preview_from_video(
-                                video_path,
-                                &self.export_plano,
-                                &preview_str,
+                            rust_i18n::t!(
+                                "export_preview_clip_rendering",
+                                seconds = crate::exporter::PREVIEW_CLIP_SECONDS
                             )
-                        } else {
-                            crate::exporter::generate_preview_embedded(
-                                &self.export_plano,
-                                &preview_str,
-                            )
-                        };
-
-                        match result {
-                            Ok(_) => {
-                                self.export_preview_path = Some(preview_str.clone());
-                                self.log(
-                                    LogLevel::Success,
-                                    rust_i18n::t!("export_preview_generated", path = preview_str)
-                                        .to_string(),
-                                );
-
-                                // Open the preview
-                                self.log(
-                                    LogLevel::Info,
-                                    rust_i18n::t!("export_opening_preview").to_string(),
-                                );
-                                if let Err(e) = open::that(&preview_str) {
-                                    self.log(
-                                        LogLevel::Error,
-                                        format!("Failed to open preview: {}", e),
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                self.log(
-                                    LogLevel::Error,
-                                    rust_i18n::t!("export_preview_error", error = e.to_string())
-                                        .to_string(),
-                                );
-                            }
-                        }
+                            .to_string(),
+                        );
+                        self.export_preview_clip_requested = true;
                     } else {
                         self.log(
                             LogLevel::Warning,
@@ -1311,6 +1445,8 @@ impl App {
                 }
                 _ => {}
             },
+            AppScreen::PlanoEditor => self.handle_plano_editor_key(key),
+            AppScreen::MomentReview => self.handle_review_key(key),
             AppScreen::ExportProcessing => {
                 if let KeyCode::Esc = key {
                     self.screen = AppScreen::ExportProcessingCancellationConfirm;
@@ -1345,10 +1481,520 @@ impl App {
             match key {
                 KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => {
                     self.screen = AppScreen::MainMenu;
-                    self.export_plano_path = None;
+                    self.export_planos.clear();
+                    self.export_plano_index = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Plano highlighted in the export selection, if any
+    pub fn selected_plano(&self) -> Option<&crate::exporter::LoadedPlano> {
+        self.export_planos.get(self.export_plano_index)
+    }
+
+    /// Reload the plano library, keeping the highlighted entry in range
+    fn refresh_library(&mut self) {
+        self.export_library = crate::library::load_library();
+        if self.export_library_index >= self.export_library.len() {
+            self.export_library_index = self.export_library.len().saturating_sub(1);
+        }
+    }
+
+    /// Add the highlighted library plano to the selection
+    /// Presets are copied into the library folder first so they can be edited
+    fn add_library_plano(&mut self) {
+        let Some(entry) = self.export_library.get(self.export_library_index) else {
+            return;
+        };
+        let path = match crate::library::entry_file(entry) {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(e) => {
+                self.log(LogLevel::Error, format!("Error guardando plantilla: {}", e));
+                return;
+            }
+        };
+
+        if let Some(index) = self.export_planos.iter().position(|p| p.path == path) {
+            self.export_plano_index = index;
+            return;
+        }
+        match crate::exporter::LoadedPlano::load(&path) {
+            Ok(loaded) => {
+                let name = loaded.name();
+                self.export_planos.push(loaded);
+                self.export_plano_index = self.export_planos.len() - 1;
+                self.log(
+                    LogLevel::Success,
+                    rust_i18n::t!("export_plano_added", name = name).to_string(),
+                );
+                self.validate_selected_plano();
+            }
+            Err(e) => {
+                self.log(LogLevel::Error, format!("Error cargando plantilla: {}", e));
+            }
+        }
+        // A preset used for the first time now also has a saved copy
+        self.refresh_library();
+    }
+
+    /// Save a copy of the highlighted selected plano into the library
+    fn save_selected_to_library(&mut self) {
+        let Some(loaded) = self.selected_plano() else {
+            return;
+        };
+        match crate::library::save_to_library(loaded) {
+            Ok(path) => {
+                self.log(
+                    LogLevel::Success,
+                    rust_i18n::t!("export_plano_saved_library", path = path.display()).to_string(),
+                );
+                self.refresh_library();
+            }
+            Err(e) => {
+                self.log(LogLevel::Error, format!("Error guardando plantilla: {}", e));
+            }
+        }
+    }
+
+    /// Check the highlighted plano and log every problem found
+    fn validate_selected_plano(&mut self) {
+        let Some(loaded) = self.selected_plano() else {
+            return;
+        };
+        let name = loaded.name();
+        let issues = crate::validation::validate_plano(&loaded.plano);
+
+        if issues.is_empty() {
+            self.log(
+                LogLevel::Success,
+                rust_i18n::t!("export_plano_valid", name = name).to_string(),
+            );
+            return;
+        }
+        self.log(
+            LogLevel::Warning,
+            rust_i18n::t!("export_plano_issues", count = issues.len(), name = name).to_string(),
+        );
+        for issue in issues {
+            let level = match issue.severity {
+                crate::validation::Severity::Error => LogLevel::Error,
+                crate::validation::Severity::Warning => LogLevel::Warning,
+            };
+            self.log(level, issue.to_string());
+        }
+    }
+
+    /// Open the highlighted selected plano in the plano editor
+    fn open_plano_editor(&mut self) {
+        let Some(path) = self.selected_plano().map(|p| p.path.clone()) else {
+            self.log(
+                LogLevel::Warning,
+                rust_i18n::t!("export_select_template_first").to_string(),
+            );
+            return;
+        };
+        match crate::plano_editor::PlanoEditor::open(&path) {
+            Ok(editor) => {
+                if editor.has_comments {
+                    self.log(
+                        LogLevel::Warning,
+                        rust_i18n::t!("plano_editor_comments_warning").to_string(),
+                    );
+                }
+                self.plano_editor = Some(editor);
+                self.plano_editor_fields_focus = false;
+                self.plano_editor_input = None;
+                self.plano_editor_discard_warned = false;
+                self.plano_editor_preview_requested = true;
+                self.screen = AppScreen::PlanoEditor;
+                self.reload_selected_plano();
+            }
+            Err(e) => self.log(
+                LogLevel::Error,
+                rust_i18n::t!("plano_editor_open_error", error = format!("{:#}", e)).to_string(),
+            ),
+        }
+    }
+
+    /// Handle a key in the plano editor
+    fn handle_plano_editor_key(&mut self, key: KeyCode) {
+        let Some(editor) = self.plano_editor.as_mut() else {
+            self.screen = AppScreen::ExportSelectPlano;
+            return;
+        };
+
+        // Typing a new value for a field
+        if let Some(input) = self.plano_editor_input.as_mut() {
+            match key {
+                KeyCode::Enter => {
+                    let value = std::mem::take(input);
+                    self.plano_editor_input = None;
+                    match editor.set_field(&value) {
+                        Ok(()) => self.plano_editor_changed(),
+                        Err(e) => self.log(LogLevel::Error, e.to_string()),
+                    }
+                }
+                KeyCode::Esc => self.plano_editor_input = None,
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let changed = match key {
+            KeyCode::Tab => {
+                self.plano_editor_fields_focus = !self.plano_editor_fields_focus;
+                false
+            }
+            KeyCode::Up if self.plano_editor_fields_focus => {
+                editor.field_index = editor.field_index.saturating_sub(1);
+                false
+            }
+            KeyCode::Down if self.plano_editor_fields_focus => {
+                if editor.field_index + 1 < editor.fields().len() {
+                    editor.field_index += 1;
+                }
+                false
+            }
+            KeyCode::Up => {
+                editor.select_layer(editor.layer_index.saturating_sub(1));
+                false
+            }
+            KeyCode::Down => {
+                editor.select_layer(editor.layer_index + 1);
+                false
+            }
+            KeyCode::Enter if !self.plano_editor_fields_focus => {
+                self.plano_editor_fields_focus = true;
+                false
+            }
+            KeyCode::Enter => {
+                let (Some(field), Some(layer)) = (editor.field(), editor.layer()) else {
+                    return;
+                };
+                match field.next_choice(layer) {
+                    // Fields with a few choices cycle, like bool settings
+                    Some(next) => editor.set_field(&next).is_ok(),
+                    None => {
+                        self.plano_editor_input = Some(field.value(layer));
+                        false
+                    }
+                }
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => editor.move_layer(true),
+            KeyCode::Char('-') => editor.move_layer(false),
+            KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete => editor.remove_layer(),
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.add_editor_layer("clip", None);
+                false
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                self.add_editor_layer("shader", None);
+                false
+            }
+            KeyCode::Char('i') | KeyCode::Char('I') => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg", "gif", "webp", "apng"])
+                    .pick_file()
+                {
+                    self.add_editor_layer("image", Some(&path.to_string_lossy()));
+                }
+                false
+            }
+            KeyCode::Char('v') | KeyCode::Char('V') => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Video", &["mp4", "mkv", "webm", "mov"])
+                    .pick_file()
+                {
+                    self.add_editor_layer("video", Some(&path.to_string_lossy()));
+                }
+                false
+            }
+            KeyCode::Char('g') | KeyCode::Char('G') => {
+                self.plano_editor_preview_requested = true;
+                false
+            }
+            KeyCode::Char('w') | KeyCode::Char('W') => {
+                self.save_plano_editor();
+                false
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                self.export_preview_boxes = !self.export_preview_boxes;
+                self.export_preview_generation += 1;
+                false
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                self.graphics_protocol = self.graphics_protocol.next();
+                self.export_preview_generation += 1;
+                false
+            }
+            KeyCode::Esc if editor.modified && !self.plano_editor_discard_warned => {
+                self.plano_editor_discard_warned = true;
+                self.log(
+                    LogLevel::Warning,
+                    rust_i18n::t!("plano_editor_unsaved").to_string(),
+                );
+                false
+            }
+            KeyCode::Esc => {
+                if editor.modified {
+                    // The preview shows the discarded edits
+                    self.export_preview_image = None;
+                    self.export_preview_generation += 1;
+                }
+                self.plano_editor = None;
+                self.screen = AppScreen::ExportSelectPlano;
+                self.validate_selected_plano();
+                false
+            }
+            _ => false,
+        };
+        if changed {
+            self.plano_editor_changed();
+        }
+    }
+
+    /// Add a new layer in front of the highlighted one in the plano editor
+    fn add_editor_layer(&mut self, kind: &str, path: Option<&str>) {
+        let Some(editor) = self.plano_editor.as_mut() else {
+            return;
+        };
+        match crate::plano_editor::new_layer(kind, path) {
+            Ok(layer) => {
+                editor.add_layer(layer);
+                self.plano_editor_changed();
+            }
+            Err(e) => self.log(LogLevel::Error, e.to_string()),
+        }
+    }
+
+    /// Mark the edited plano as changed and refresh its preview
+    /// The file is only written on [W], so it is not rewritten on every keypress
+    fn plano_editor_changed(&mut self) {
+        let Some(editor) = self.plano_editor.as_mut() else {
+            return;
+        };
+        editor.modified = true;
+        self.plano_editor_discard_warned = false;
+        self.plano_editor_preview_requested = true;
+    }
+
+    /// Write the edited plano to its file and reload it into the selection
+    fn save_plano_editor(&mut self) {
+        let Some(editor) = self.plano_editor.as_mut() else {
+            return;
+        };
+        if let Err(e) = editor.save() {
+            self.log(
+                LogLevel::Error,
+                rust_i18n::t!("plano_editor_save_error", error = e.to_string()).to_string(),
+            );
+            return;
+        }
+        let path = editor.path.clone();
+        self.log(
+            LogLevel::Success,
+            rust_i18n::t!("plano_editor_saved", path = path).to_string(),
+        );
+        match crate::exporter::load_plano(&path) {
+            Ok(plano) => {
+                if let Some(loaded) = self.export_planos.iter_mut().find(|p| p.path == path) {
+                    loaded.plano = plano;
+                }
+            }
+            Err(e) => self.log(
+                LogLevel::Error,
+                rust_i18n::t!("export_plano_reload_error", error = e.to_string()).to_string(),
+            ),
+        }
+    }
+
+    /// Handle a key in the moment review
+    fn handle_review_key(&mut self, key: KeyCode) {
+        // Typing a new value for a moment field
+        if let Some((_, input)) = self.review_input.as_mut() {
+            match key {
+                KeyCode::Enter => {
+                    if let Some((field, value)) = self.review_input.take() {
+                        self.set_moment_field(field, value);
+                    }
+                }
+                KeyCode::Esc => self.review_input = None,
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
                 }
                 _ => {}
             }
+            return;
+        }
+
+        match key {
+            KeyCode::Up if self.review_index > 0 => {
+                self.review_index -= 1;
+                self.refresh_review_frames();
+            }
+            KeyCode::Down if self.review_index + 1 < self.moments.len() => {
+                self.review_index += 1;
+                self.refresh_review_frames();
+            }
+            KeyCode::Char(' ') => {
+                if let Some(moment) = self.moments.get_mut(self.review_index) {
+                    moment.rejected = !moment.rejected;
+                    self.review_dirty = Some(Instant::now());
+                }
+            }
+            KeyCode::Tab => self.review_end_focus = !self.review_end_focus,
+            KeyCode::Left => self.nudge_moment(-1),
+            KeyCode::Right => self.nudge_moment(1),
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                let field = if self.review_end_focus {
+                    ReviewField::End
+                } else {
+                    ReviewField::Start
+                };
+                self.edit_moment_field(field);
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.edit_moment_field(ReviewField::Category)
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                self.edit_moment_field(ReviewField::Description)
+            }
+            KeyCode::Enter | KeyCode::Char('x') | KeyCode::Char('X') => {
+                if self.moments.iter().any(|m| !m.rejected) {
+                    self.confirm_response = Some(true);
+                } else {
+                    self.log(
+                        LogLevel::Warning,
+                        rust_i18n::t!("review_none_accepted").to_string(),
+                    );
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.confirm_response = Some(false),
+            _ => {}
+        }
+    }
+
+    /// Start typing a field of the highlighted moment, beginning with its value
+    fn edit_moment_field(&mut self, field: ReviewField) {
+        let Some(moment) = self.moments.get(self.review_index) else {
+            return;
+        };
+        let value = match field {
+            ReviewField::Start => &moment.start_time,
+            ReviewField::End => &moment.end_time,
+            ReviewField::Category => &moment.category,
+            ReviewField::Description => &moment.description,
+        };
+        self.review_input = Some((field, value.clone()));
+    }
+
+    /// Apply text typed for a field of the highlighted moment
+    fn set_moment_field(&mut self, field: ReviewField, value: String) {
+        let Some(moment) = self.moments.get(self.review_index) else {
+            return;
+        };
+        let value = value.trim().to_string();
+        let (mut start, mut end) = (moment.start_time.clone(), moment.end_time.clone());
+        match field {
+            ReviewField::Start => start = value.clone(),
+            ReviewField::End => end = value.clone(),
+            ReviewField::Category if value.is_empty() => return,
+            ReviewField::Category | ReviewField::Description => {}
+        }
+        if let Err(e) = check_moment_range(&start, &end) {
+            self.log(LogLevel::Error, e.to_string());
+            return;
+        }
+
+        let moment = &mut self.moments[self.review_index];
+        match field {
+            ReviewField::Start | ReviewField::End => {
+                moment.start_time = start;
+                moment.end_time = end;
+            }
+            ReviewField::Category => moment.category = value,
+            ReviewField::Description => moment.description = value,
+        }
+        self.review_dirty = Some(Instant::now());
+        if matches!(field, ReviewField::Start | ReviewField::End) {
+            self.refresh_review_frames();
+        }
+    }
+
+    /// Move the start or end of the highlighted moment by `delta` seconds
+    fn nudge_moment(&mut self, delta: i64) {
+        let field = if self.review_end_focus {
+            ReviewField::End
+        } else {
+            ReviewField::Start
+        };
+        let Some(moment) = self.moments.get(self.review_index) else {
+            return;
+        };
+        let current = match field {
+            ReviewField::End => &moment.end_time,
+            _ => &moment.start_time,
+        };
+        match crate::video::nudge_timestamp(current, delta) {
+            Ok(value) => self.set_moment_field(field, value),
+            Err(e) => self.log(LogLevel::Error, e.to_string()),
+        }
+    }
+
+    /// Load the frames of the highlighted moment again (in the background)
+    /// Frames still loading for the previous moment are dropped when they arrive
+    pub fn refresh_review_frames(&mut self) {
+        self.review_frames_requested = true;
+        self.export_preview_generation += 1;
+    }
+
+    /// Render the preview frame of the highlighted plano in the background
+    /// Returns false when there is no plano to preview
+    fn generate_preview(&mut self) -> bool {
+        let has_layers = self
+            .selected_plano()
+            .is_some_and(|p| !p.plano.layers.is_empty());
+        if has_layers {
+            self.log(
+                LogLevel::Info,
+                rust_i18n::t!("export_generating_preview").to_string(),
+            );
+            self.export_preview_requested = true;
+        } else {
+            self.log(
+                LogLevel::Warning,
+                rust_i18n::t!("export_select_template_first").to_string(),
+            );
+        }
+        has_layers
+    }
+
+    /// Reload the highlighted plano from disk (picks up external edits)
+    fn reload_selected_plano(&mut self) {
+        let Some(path) = self.selected_plano().map(|p| p.path.clone()) else {
+            return;
+        };
+        match crate::exporter::load_plano(&path) {
+            Ok(plano) => {
+                self.export_planos[self.export_plano_index].plano = plano;
+                self.log(
+                    LogLevel::Info,
+                    rust_i18n::t!("export_plano_reloaded", path = path).to_string(),
+                );
+            }
+            Err(e) => {
+                self.log(
+                    LogLevel::Error,
+                    rust_i18n::t!("export_plano_reload_error", error = e.to_string()).to_string(),
+                );
+            }
         }
     }
 
@@ -1374,12 +2020,99 @@ impl App {
                 self.result_message = Some(msg);
             }
 
-            AppMessage::RequestShortsConfirm(count) => {
-                self.screen = AppScreen::ShortsConfirm(count);
+            AppMessage::EditorPreview(result) => {
+                self.plano_editor_preview_running = false;
+                match result {
+                    Ok(path) => {
+                        match RgbImage::load(&path, PREVIEW_IMAGE_SIZE.0, PREVIEW_IMAGE_SIZE.1) {
+                            Ok(image) => self.export_preview_image = Some(image),
+                            Err(e) => self.log(
+                                LogLevel::Error,
+                                rust_i18n::t!("export_preview_error", error = e.to_string())
+                                    .to_string(),
+                            ),
+                        }
+                        self.export_preview_path = Some(path);
+                    }
+                    Err(e) => {
+                        self.export_preview_image = None;
+                        self.log(
+                            LogLevel::Error,
+                            rust_i18n::t!("export_preview_error", error = e).to_string(),
+                        );
+                    }
+                }
+                self.export_preview_generation += 1;
+            }
+
+            AppMessage::ExportPreview(result) => {
+                self.export_preview_running = false;
+                let open = std::mem::take(&mut self.export_preview_open);
+                match result {
+                    Ok(path) => {
+                        self.log(
+                            LogLevel::Success,
+                            rust_i18n::t!("export_preview_generated", path = path.clone())
+                                .to_string(),
+                        );
+                        // Shown inside the terminal, [O] opens it outside
+                        match RgbImage::load(&path, PREVIEW_IMAGE_SIZE.0, PREVIEW_IMAGE_SIZE.1) {
+                            Ok(image) => self.export_preview_image = Some(image),
+                            Err(e) => self.log(
+                                LogLevel::Error,
+                                rust_i18n::t!("export_preview_error", error = e.to_string())
+                                    .to_string(),
+                            ),
+                        }
+                        if open {
+                            self.log(
+                                LogLevel::Info,
+                                rust_i18n::t!("export_opening_preview").to_string(),
+                            );
+                            if let Err(e) = open::that(&path) {
+                                self.log(LogLevel::Error, format!("Failed to open preview: {}", e));
+                            }
+                        }
+                        self.export_preview_path = Some(path);
+                    }
+                    Err(e) => self.log(
+                        LogLevel::Error,
+                        rust_i18n::t!("export_preview_error", error = e).to_string(),
+                    ),
+                }
+                self.export_preview_generation += 1;
+            }
+
+            AppMessage::ReviewFrames(generation, result) => {
+                self.review_frames_running = false;
+                // Frames of a moment that is no longer highlighted (or edited since)
+                if generation != self.export_preview_generation {
+                    self.review_frames_requested = true;
+                    return;
+                }
+                self.review_frames = match result {
+                    Ok(frames) => Some(frames),
+                    Err(e) => {
+                        log::warn!("Failed to load moment frames: {}", e);
+                        None
+                    }
+                };
+                self.export_preview_generation += 1;
+            }
+
+            AppMessage::RequestMomentReview => {
+                self.screen = AppScreen::MomentReview;
+                self.review_index = 0;
+                self.review_end_focus = false;
+                self.review_input = None;
                 self.confirm_response = None;
             }
 
             AppMessage::Finished => {
+                if self.screen == AppScreen::MomentReview {
+                    // Analysis is done, the review decides what happens next
+                    return;
+                }
                 if self.screen == AppScreen::ExportProcessing
                     || self.screen == AppScreen::ExportProcessingCancellationConfirm
                 {
@@ -1392,6 +2125,19 @@ impl App {
     }
 }
 
+/// Check that both timestamps are valid and the moment is not empty
+fn check_moment_range(start: &str, end: &str) -> Result<()> {
+    let start_seconds = crate::video::parse_timestamp_to_seconds(start)?;
+    let end_seconds = crate::video::parse_timestamp_to_seconds(end)?;
+    if start_seconds >= end_seconds {
+        anyhow::bail!(
+            "{}",
+            rust_i18n::t!("review_invalid_range", start = start, end = end)
+        );
+    }
+    Ok(())
+}
+
 /// Setup the terminal for TUI
 pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
@@ -1494,7 +2240,7 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
         AppScreen::UrlInput => render_url_input(frame, app, area),
         AppScreen::FormatConfirm => render_format_confirm(frame, area),
         AppScreen::Processing => render_processing(frame, app, area),
-        AppScreen::ShortsConfirm(count) => render_shorts_confirm(frame, *count, area),
+        AppScreen::MomentReview => render_moment_review(frame, app, area),
 
         AppScreen::Done => render_done(frame, app, area),
         AppScreen::ApiKeysManager => render_api_keys_manager(frame, app, area),
@@ -1508,6 +2254,7 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
         AppScreen::ExportSelectFolders => render_export_select_folders(frame, app, area),
         AppScreen::ExportSelectPlano => render_export_select_plano(frame, app, area),
         AppScreen::ExportPreview => render_export_preview(frame, app, area),
+        AppScreen::PlanoEditor => render_plano_editor(frame, app, area),
         AppScreen::ExportProcessing => render_export_processing(frame, app, area),
         AppScreen::ExportProcessingCancellationConfirm => {
             render_export_processing(frame, app, area); // Render background
@@ -1808,6 +2555,7 @@ fn render_main_menu(frame: &mut Frame, app: &App, area: Rect) {
     // Dynamic localization for options
     let options = [
         rust_i18n::t!("menu_start"),
+        rust_i18n::t!("menu_import_moments"),
         rust_i18n::t!("menu_export_shorts"),
         rust_i18n::t!("language"),
         rust_i18n::t!("menu_settings"),
@@ -2010,8 +2758,11 @@ fn render_url_input(frame: &mut Frame, app: &App, area: Rect) {
         .split(inner);
 
     // Instructions
-    let instructions =
-        Paragraph::new(rust_i18n::t!("url_instr")).style(Style::default().fg(Color::Gray));
+    let instructions = match &app.imported_moments {
+        Some(moments) => rust_i18n::t!("url_instr_import", count = moments.len()),
+        None => rust_i18n::t!("url_instr"),
+    };
+    let instructions = Paragraph::new(instructions).style(Style::default().fg(Color::Gray));
     frame.render_widget(instructions, input_layout[0]);
 
     // Input field
@@ -2149,30 +2900,134 @@ fn render_processing(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(moments_list, layout[2]);
 }
 
-fn render_shorts_confirm(frame: &mut Frame, count: usize, area: Rect) {
+/// Moment review: accept/reject, nudge and edit every moment before extraction
+fn render_moment_review(frame: &mut Frame, app: &App, area: Rect) {
+    let accepted = app.moments.iter().filter(|m| !m.rejected).count();
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Green))
-        .title(format!(" {} ", rust_i18n::t!("analysis_complete")));
+        .title(format!(
+            " {} - {} ",
+            rust_i18n::t!("analysis_complete"),
+            rust_i18n::t!(
+                "review_accepted",
+                accepted = accepted,
+                count = app.moments.len()
+            )
+        ));
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
 
-    let text = Text::from(vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            rust_i18n::t!("analysis_found_msg", count = count),
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(rust_i18n::t!("analysis_ask")),
-        Line::from(""),
-        Line::from(rust_i18n::t!("analysis_yes")),
-        Line::from(rust_i18n::t!("analysis_no")),
-        Line::from(""),
-    ]);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(inner_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),    // Moments
+            Constraint::Length(3), // Description/Edit area
+            Constraint::Length(2), // Help
+        ])
+        .split(columns[0]);
 
-    let paragraph = Paragraph::new(text).block(block);
-    frame.render_widget(paragraph, area);
+    // Moments, the nudged timestamp of the highlighted one underlined
+    let items: Vec<ListItem> = app
+        .moments
+        .iter()
+        .enumerate()
+        .map(|(i, moment)| {
+            let selected = i == app.review_index;
+            let (mark, color) = if moment.rejected {
+                ("✘", Color::DarkGray)
+            } else {
+                ("✔", Color::Green)
+            };
+            let time_style = |end: bool| {
+                let style = Style::default().fg(if moment.rejected {
+                    Color::DarkGray
+                } else {
+                    Color::Cyan
+                });
+                if selected && end == app.review_end_focus {
+                    style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD)
+                } else {
+                    style
+                }
+            };
+            let line = Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{} {} {:>2}. ",
+                        if selected { ">" } else { " " },
+                        mark,
+                        i + 1
+                    ),
+                    Style::default().fg(color),
+                ),
+                Span::styled(&moment.start_time, time_style(false)),
+                Span::raw(" - "),
+                Span::styled(&moment.end_time, time_style(true)),
+                Span::raw(" "),
+                Span::styled(
+                    format!("({}) ", moment.category),
+                    Style::default().fg(if moment.rejected {
+                        Color::DarkGray
+                    } else {
+                        Color::Magenta
+                    }),
+                ),
+                Span::styled(&moment.description, Style::default().fg(color)),
+            ]);
+            let item = ListItem::new(line);
+            if selected {
+                item.style(Style::default().bg(Color::Rgb(40, 40, 40)))
+            } else {
+                item
+            }
+        })
+        .collect();
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(app.review_index));
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!(
+        " {} ",
+        rust_i18n::t!("moments_found_title", count = app.moments.len())
+    )));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    // Full description of the highlighted moment, or the field being edited
+    if let Some((field, input)) = &app.review_input {
+        let label = match field {
+            ReviewField::Start => rust_i18n::t!("review_field_start"),
+            ReviewField::End => rust_i18n::t!("review_field_end"),
+            ReviewField::Category => rust_i18n::t!("review_field_category"),
+            ReviewField::Description => rust_i18n::t!("review_field_description"),
+        };
+        let edit_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green))
+            .title(format!(
+                " {}: {} ",
+                rust_i18n::t!("settings_editing"),
+                label
+            ));
+        let input = Paragraph::new(input.as_str())
+            .block(edit_block)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(input, chunks[1]);
+    } else if let Some(moment) = app.moments.get(app.review_index) {
+        let description = Paragraph::new(moment.description.as_str())
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(description, chunks[1]);
+    }
+
+    let help = Paragraph::new(rust_i18n::t!("review_help"))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(help, chunks[2]);
+
+    render_preview_image(frame, app, columns[1], &rust_i18n::t!("review_frames_none"));
 }
 
 fn render_done(frame: &mut Frame, app: &App, area: Rect) {
@@ -2240,9 +3095,10 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
             }
         }
         AppScreen::UrlInput => rust_i18n::t!("shortcuts_url"),
-        AppScreen::ResumePrompt(_) | AppScreen::FormatConfirm | AppScreen::ShortsConfirm(_) => {
+        AppScreen::ResumePrompt(_) | AppScreen::FormatConfirm => {
             rust_i18n::t!("shortcuts_confirm")
         }
+        AppScreen::MomentReview => rust_i18n::t!("shortcuts_review"),
         AppScreen::Processing => rust_i18n::t!("shortcuts_process"),
         AppScreen::Done => rust_i18n::t!("shortcuts_done"),
         _ => rust_i18n::t!("shortcuts_default"),
@@ -2580,9 +3436,10 @@ fn render_export_shorts(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(folders, chunks[1]);
 
     // Plano status
-    let plano_text = match &app.export_plano_path {
-        Some(path) => format!("📋 {}", path),
-        None => rust_i18n::t!("export_no_plano").to_string(),
+    let plano_text = match app.export_planos.as_slice() {
+        [] => rust_i18n::t!("export_no_plano").to_string(),
+        [single] => format!("📋 {}", single.path),
+        many => rust_i18n::t!("export_planos_count", count = many.len()).to_string(),
     };
     let plano = Paragraph::new(plano_text)
         .style(Style::default().fg(Color::Yellow))
@@ -2701,49 +3558,234 @@ fn render_export_select_plano(frame: &mut Frame, app: &App, area: Rect) {
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(2)
+        .margin(1)
         .constraints([
-            Constraint::Length(4), // Current plano
-            Constraint::Min(6),    // Options
-            Constraint::Length(3), // Help
+            Constraint::Min(10),   // Library + thumbnail
+            Constraint::Length(6), // Selected planos
+            Constraint::Length(4), // Options
+            Constraint::Length(1), // Help
         ])
         .split(inner_area);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(30), Constraint::Length(26)])
+        .split(chunks[0]);
 
-    // Current plano
-    let current = match &app.export_plano_path {
-        Some(path) => rust_i18n::t!("export_plano_current", path = path).to_string(),
-        None => rust_i18n::t!("export_plano_none").to_string(),
+    let focused = |active: bool| {
+        if active {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }
     };
-    let current_para = Paragraph::new(current)
-        .style(Style::default().fg(Color::White))
-        .alignment(Alignment::Center);
-    frame.render_widget(current_para, chunks[0]);
+    let highlight = |active: bool| {
+        if active {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Black).bg(Color::Gray)
+        }
+    };
+
+    // Library
+    let library_items: Vec<ListItem> = app
+        .export_library
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let icon = match entry.source {
+                crate::library::LibrarySource::Preset(_) => "⭐",
+                crate::library::LibrarySource::Saved(_) => "📁",
+            };
+            let style = if i == app.export_library_index {
+                highlight(app.export_library_focus)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(format!(" {} {} ", icon, entry.name)).style(style)
+        })
+        .collect();
+    let library = List::new(library_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(app.export_library_focus))
+            .title(format!(" {} ", rust_i18n::t!("export_library_title"))),
+    );
+    frame.render_widget(library, top[0]);
 
-    // Options
+    // Thumbnail of the highlighted plano
+    let thumbnail_layers = if app.export_library_focus {
+        app.export_library
+            .get(app.export_library_index)
+            .map(|entry| entry.plano.layers.as_slice())
+    } else {
+        app.selected_plano().map(|p| p.plano.layers.as_slice())
+    };
+    render_plano_thumbnail(frame, thumbnail_layers.unwrap_or_default(), top[1]);
+
+    // Selected planos
+    let selection_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(focused(!app.export_library_focus))
+        .title(format!(" {} ", rust_i18n::t!("export_planos_title")));
+    if app.export_planos.is_empty() {
+        let none_para = Paragraph::new(rust_i18n::t!("export_plano_none"))
+            .style(Style::default().fg(Color::White))
+            .alignment(Alignment::Center)
+            .block(selection_block);
+        frame.render_widget(none_para, chunks[1]);
+    } else {
+        let items: Vec<ListItem> = app
+            .export_planos
+            .iter()
+            .enumerate()
+            .map(|(i, loaded)| {
+                let style = if i == app.export_plano_index {
+                    highlight(!app.export_library_focus)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(" 📋 {} ({}) ", loaded.name(), loaded.path)).style(style)
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(selection_block), chunks[1]);
+    }
+
+    // Options, two per line
+    let option = |key: &'static str, color: Color, text: std::borrow::Cow<'static, str>| {
+        vec![
+            Span::styled(key, Style::default().fg(color)),
+            Span::raw(format!("{}   ", text)),
+        ]
+    };
     let options = Text::from(vec![
-        Line::from(vec![
-            Span::styled("[L] ", Style::default().fg(Color::Cyan)),
-            Span::raw(rust_i18n::t!("export_plano_opt_load")),
-        ]),
-        Line::from(vec![
-            Span::styled("[N] ", Style::default().fg(Color::Green)),
-            Span::raw(rust_i18n::t!("export_plano_opt_new")),
-        ]),
-        Line::from(vec![
-            Span::styled("[E] ", Style::default().fg(Color::Yellow)),
-            Span::raw(rust_i18n::t!("export_plano_opt_edit")),
-        ]),
+        Line::from(
+            [
+                option("[A] ", Color::Green, rust_i18n::t!("export_plano_opt_add")),
+                option("[S] ", Color::Green, rust_i18n::t!("export_plano_opt_save")),
+            ]
+            .concat(),
+        ),
+        Line::from(
+            [
+                option("[L] ", Color::Cyan, rust_i18n::t!("export_plano_opt_load")),
+                option(
+                    "[O] ",
+                    Color::Cyan,
+                    rust_i18n::t!("export_plano_opt_folder"),
+                ),
+            ]
+            .concat(),
+        ),
+        Line::from(
+            [
+                option(
+                    "[E] ",
+                    Color::Yellow,
+                    rust_i18n::t!("export_plano_opt_edit"),
+                ),
+                option(
+                    "[V] ",
+                    Color::Magenta,
+                    rust_i18n::t!("export_plano_opt_validate"),
+                ),
+            ]
+            .concat(),
+        ),
+        Line::from(
+            [
+                option(
+                    "[M] ",
+                    Color::Yellow,
+                    rust_i18n::t!("export_plano_opt_editor"),
+                ),
+                option("[D] ", Color::Red, rust_i18n::t!("export_plano_opt_remove")),
+            ]
+            .concat(),
+        ),
     ]);
-    let opts = Paragraph::new(options)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
-    frame.render_widget(opts, chunks[1]);
+    let opts = Paragraph::new(options).alignment(Alignment::Center);
+    frame.render_widget(opts, chunks[2]);
 
     // Help
     let help = Paragraph::new(rust_i18n::t!("export_plano_help"))
         .style(Style::default().fg(Color::Gray))
         .alignment(Alignment::Center);
-    frame.render_widget(help, chunks[2]);
+    frame.render_widget(help, chunks[3]);
+}
+
+/// Colour of a layer in thumbnails and preview boxes
+fn layer_rgb(layer: &crate::exporter::PlanoObject) -> [u8; 3] {
+    match layer {
+        crate::exporter::PlanoObject::Clip { .. } => [0, 255, 255],
+        crate::exporter::PlanoObject::Image { .. } => [255, 255, 0],
+        crate::exporter::PlanoObject::Video { .. } => [255, 0, 255],
+        crate::exporter::PlanoObject::Shader { .. } => [160, 160, 160],
+    }
+}
+
+/// Sketch of a plano: the box of every layer on a 9:16 canvas, numbered
+/// back to front (clips cyan, images yellow, videos magenta, shaders gray)
+fn render_plano_thumbnail(frame: &mut Frame, layers: &[crate::exporter::PlanoObject], area: Rect) {
+    use crate::exporter::{OUTPUT_HEIGHT, OUTPUT_WIDTH};
+    use ratatui::widgets::canvas::{Canvas, Rectangle};
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", rust_i18n::t!("export_plano_thumbnail")));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Terminal cells are about twice as tall as wide
+    let height = inner
+        .height
+        .min((inner.width as u32 * 32 / 9) as u16 / 2)
+        .max(1);
+    let width = (height as u32 * 9 * 2 / 16) as u16;
+    let canvas_area = Rect {
+        x: inner.x + inner.width.saturating_sub(width) / 2,
+        y: inner.y,
+        width: width.min(inner.width),
+        height,
+    };
+
+    let (canvas_w, canvas_h) = (OUTPUT_WIDTH as f64, OUTPUT_HEIGHT as f64);
+    let canvas = Canvas::default()
+        .background_color(Color::Black)
+        .x_bounds([0.0, canvas_w])
+        .y_bounds([0.0, canvas_h])
+        .paint(|ctx| {
+            ctx.draw(&Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: canvas_w,
+                height: canvas_h,
+                color: Color::DarkGray,
+            });
+            for (i, layer) in layers.iter().enumerate() {
+                let [r, g, b] = layer_rgb(layer);
+                let color = Color::Rgb(r, g, b);
+                let (x, y, w, h) = layer.canvas_box();
+                let (x, y, w, h) = (x as f64, y as f64, w as f64, h as f64);
+                // Canvas y grows upwards
+                let bottom = canvas_h - y - h;
+                ctx.draw(&Rectangle {
+                    x,
+                    y: bottom,
+                    width: w,
+                    height: h,
+                    color,
+                });
+                ctx.print(
+                    (x + 40.0).clamp(0.0, canvas_w),
+                    (bottom + h - 80.0).clamp(0.0, canvas_h),
+                    Span::styled(i.to_string(), Style::default().fg(color)),
+                );
+            }
+        });
+    frame.render_widget(canvas, canvas_area);
 }
 
 fn render_export_preview(frame: &mut Frame, app: &App, area: Rect) {
@@ -2755,21 +3797,29 @@ fn render_export_preview(frame: &mut Frame, app: &App, area: Rect) {
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
 
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(inner_area);
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(2)
+        .margin(1)
         .constraints([
             Constraint::Length(3), // Status
-            Constraint::Min(10),   // Plano layers
-            Constraint::Length(3), // Actions
+            Constraint::Min(6),    // Plano layers
+            Constraint::Length(4), // Actions
         ])
-        .split(inner_area);
-
+        .split(columns[0]);
     // Status
-    let status = if app.export_plano.is_empty() {
+    let layers: &[crate::exporter::PlanoObject] = app
+        .selected_plano()
+        .map(|p| p.plano.layers.as_slice())
+        .unwrap_or_default();
+    let status = if layers.is_empty() {
         rust_i18n::t!("export_preview_status_none").to_string()
     } else {
-        rust_i18n::t!("export_preview_status_ok", count = app.export_plano.len()).to_string()
+        rust_i18n::t!("export_preview_status_ok", count = layers.len()).to_string()
     };
     let status_para = Paragraph::new(status)
         .style(Style::default().fg(Color::White))
@@ -2777,8 +3827,7 @@ fn render_export_preview(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(status_para, chunks[0]);
 
     // Plano layers list
-    let items: Vec<ListItem> = app
-        .export_plano
+    let items: Vec<ListItem> = layers
         .iter()
         .enumerate()
         .map(|(i, obj)| {
@@ -2815,11 +3864,295 @@ fn render_export_preview(frame: &mut Frame, app: &App, area: Rect) {
 
     // Actions
     let help_text = format!(
-        "{}  [Enter] Volver  [Esc] Volver",
-        rust_i18n::t!("export_preview_help_generate")
+        "{}\n{}\n{}\n[Enter] Volver  [Esc] Volver",
+        rust_i18n::t!("export_preview_help_generate"),
+        rust_i18n::t!(
+            "export_preview_help_clip",
+            seconds = crate::exporter::PREVIEW_CLIP_SECONDS
+        ),
+        rust_i18n::t!("export_preview_help_image")
     );
     let actions = Paragraph::new(help_text)
         .style(Style::default().fg(Color::Gray))
         .alignment(Alignment::Center);
     frame.render_widget(actions, chunks[2]);
+
+    render_preview_image(
+        frame,
+        app,
+        columns[1],
+        &rust_i18n::t!("export_preview_image_none"),
+    );
+}
+
+/// Plano editor: layers on the left, fields of the highlighted layer below
+/// them and the preview, refreshed after every change, on the right
+fn render_plano_editor(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(editor) = &app.plano_editor else {
+        return;
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(
+            " ✏️ {}: {} ",
+            rust_i18n::t!("plano_editor_title"),
+            editor.path
+        ));
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(inner_area);
+    let fields = editor.fields();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),                                 // Layers
+            Constraint::Length(fields.len().max(1) as u16 + 2), // Fields
+            Constraint::Length(3),                              // Help/Edit area
+        ])
+        .split(columns[0]);
+
+    let focused = |active: bool| {
+        if active {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        }
+    };
+    let highlight = |active: bool| {
+        if active {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Black).bg(Color::Gray)
+        }
+    };
+
+    // Layers, back to front
+    let layer_items: Vec<ListItem> = editor
+        .plano
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| {
+            let (kind, name) = match layer {
+                crate::exporter::PlanoObject::Clip { .. } => {
+                    (rust_i18n::t!("export_preview_layer_clip"), "")
+                }
+                crate::exporter::PlanoObject::Image { path, .. } => {
+                    (rust_i18n::t!("export_preview_layer_image"), path.as_str())
+                }
+                crate::exporter::PlanoObject::Shader { .. } => {
+                    (rust_i18n::t!("export_preview_layer_shader"), "")
+                }
+                crate::exporter::PlanoObject::Video { path, .. } => {
+                    (rust_i18n::t!("export_preview_layer_video"), path.as_str())
+                }
+            };
+            let [r, g, b] = layer_rgb(layer);
+            let style = if i == editor.layer_index {
+                highlight(!app.plano_editor_fields_focus)
+            } else {
+                Style::default().fg(Color::Rgb(r, g, b))
+            };
+            let id = layer.id().map(|id| format!(" #{}", id)).unwrap_or_default();
+            ListItem::new(format!(" {}: {}{} {} ", i, kind, id, name)).style(style)
+        })
+        .collect();
+    let layers = List::new(layer_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(!app.plano_editor_fields_focus))
+            .title(format!(
+                " {} ",
+                rust_i18n::t!("export_preview_layers_title")
+            )),
+    );
+    frame.render_widget(layers, chunks[0]);
+
+    // Fields of the highlighted layer
+    let field_items: Vec<ListItem> = match editor.layer() {
+        Some(layer) => fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let value = field.value(layer);
+                let value = if value.is_empty() {
+                    "-".to_string()
+                } else {
+                    value
+                };
+                let style = if i == editor.field_index {
+                    highlight(app.plano_editor_fields_focus)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(" {:<12}: {} ", field.label(), value)).style(style)
+            })
+            .collect(),
+        None => vec![ListItem::new(rust_i18n::t!("plano_editor_empty"))
+            .style(Style::default().fg(Color::DarkGray))],
+    };
+    let field_list = List::new(field_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(app.plano_editor_fields_focus))
+            .title(format!(" {} ", rust_i18n::t!("plano_editor_fields"))),
+    );
+    frame.render_widget(field_list, chunks[1]);
+
+    // Help or edit box
+    if let Some(input) = &app.plano_editor_input {
+        let edit_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green))
+            .title(format!(" {} ", rust_i18n::t!("settings_editing")));
+        let input = Paragraph::new(input.as_str())
+            .block(edit_block)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(input, chunks[2]);
+    } else {
+        let help = if app.plano_editor_fields_focus {
+            rust_i18n::t!("plano_editor_help_fields")
+        } else {
+            rust_i18n::t!("plano_editor_help_layers")
+        };
+        let help = Paragraph::new(help)
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(help, chunks[2]);
+    }
+
+    render_preview_image(
+        frame,
+        app,
+        columns[1],
+        &rust_i18n::t!("export_preview_image_none"),
+    );
+}
+
+/// Preview frame inside the terminal, with optional layer boxes
+fn render_preview_image(frame: &mut Frame, app: &App, area: Rect, empty_hint: &str) {
+    let protocol = app.graphics_protocol;
+    let title = format!(
+        " {} ({}) ",
+        rust_i18n::t!("export_preview_image_title"),
+        protocol.label()
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    app.export_preview_area.set(None);
+
+    let Some(image) = shown_image(app) else {
+        let hint = Paragraph::new(empty_hint)
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(hint, inner);
+        return;
+    };
+
+    let (cols, rows) = crate::termimage::fit_cells(
+        (image.width, image.height),
+        (inner.width, inner.height),
+        protocol.cell_size(),
+    );
+    let cells = Rect {
+        x: inner.x + inner.width.saturating_sub(cols) / 2,
+        y: inner.y + inner.height.saturating_sub(rows) / 2,
+        width: cols.min(inner.width),
+        height: rows.min(inner.height),
+    };
+    if protocol.is_graphics() {
+        // Drawn by `draw_graphics` once the frame is on screen
+        crate::termimage::skip_cells(cells, frame.buffer_mut());
+        app.export_preview_area.set(Some(cells));
+    } else if let Some(image) = preview_frame(app, (cells.width as u32, cells.height as u32 * 2)) {
+        crate::termimage::render_half_blocks(&image, cells, frame.buffer_mut());
+    }
+}
+
+/// Image drawn by the preview pane of the current screen
+fn shown_image(app: &App) -> Option<&RgbImage> {
+    match app.screen {
+        AppScreen::MomentReview => app.review_frames.as_ref(),
+        _ => app.export_preview_image.as_ref(),
+    }
+}
+
+/// The preview image at `size` pixels, with the layer boxes when enabled
+/// Boxes are drawn after scaling so thin lines survive small sizes
+fn preview_frame(app: &App, size: (u32, u32)) -> Option<RgbImage> {
+    let mut image = shown_image(app)?.resized(size.0, size.1);
+    if !app.export_preview_boxes || app.screen == AppScreen::MomentReview {
+        return Some(image);
+    }
+    let layers = app
+        .selected_plano()
+        .map(|p| p.plano.layers.as_slice())
+        .unwrap_or_default();
+    let scale = image.width as f64 / crate::exporter::OUTPUT_WIDTH as f64;
+    let line = (image.width as i64 / 180).max(1);
+    let label = (image.width as i64 / 135).max(1);
+    for (i, layer) in layers.iter().enumerate() {
+        let (x, y, w, h) = layer.canvas_box();
+        let px = |v: f64| (v * scale).round() as i64;
+        let (x, y) = (px(x as f64), px(y as f64));
+        let color = layer_rgb(layer);
+        image.draw_box(x, y, px(w as f64), px(h as f64), line, color);
+        image.draw_number(x + line, y + line, i, label, color);
+    }
+    Some(image)
+}
+
+/// Write the kitty/sixel preview image once a frame is drawn
+/// These images bypass the ratatui buffer (their cells are skipped). Returns
+/// true when an image was taken off screen, so the caller clears the terminal
+/// and ratatui redraws the cells it covered.
+pub fn draw_graphics(app: &mut App) -> Result<bool> {
+    let protocol = app.graphics_protocol;
+    let wanted = match app.export_preview_area.get() {
+        Some(cells)
+            if matches!(
+                app.screen,
+                AppScreen::ExportPreview | AppScreen::PlanoEditor | AppScreen::MomentReview
+            ) && protocol.is_graphics() =>
+        {
+            Some((cells, app.export_preview_generation, protocol))
+        }
+        _ => None,
+    };
+    if wanted == app.graphics_shown {
+        return Ok(false);
+    }
+
+    let mut out = io::stdout();
+    let removed = app.graphics_shown.take();
+    if let Some((_, _, shown)) = removed {
+        write!(out, "{}", crate::termimage::clear_sequence(shown))?;
+    }
+    if let Some((cells, _, _)) = wanted {
+        let size = match (protocol, shown_image(app)) {
+            (GraphicsProtocol::Kitty, Some(image)) => (image.width, image.height),
+            _ => {
+                let (cw, ch) = protocol.cell_size();
+                (cells.width as u32 * cw, cells.height as u32 * ch)
+            }
+        };
+        if let Some(image) = preview_frame(app, size) {
+            queue!(out, MoveTo(cells.x, cells.y))?;
+            let sequence = crate::termimage::encode(protocol, &image, cells.width, cells.height);
+            write!(out, "{}", sequence)?;
+        }
+        app.graphics_shown = wanted;
+    }
+    out.flush()?;
+    Ok(removed.is_some() && wanted.is_none())
 }
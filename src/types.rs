@@ -19,6 +19,9 @@ pub struct VideoMoment {
     pub description: String,
     #[serde(default)]
     pub dialogue: Vec<DialoguePhrase>,
+    /// Left out of the extraction in the moment review
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rejected: bool,
 }
 
 /// Represents a video chunk with start time and duration
@@ -0,0 +1,547 @@
+//! Plano validation for YT ShortMaker
+//! Finds problems that the renderer would otherwise skip or hide silently
+//!
+//! Each problem names the layer by its index in the array and the field that
+//! causes it (e.g. `layers[2].position.width`).
+
+use std::fmt;
+use std::path::Path;
+
+use crate::animation::TimeValue;
+use crate::exporter::{
+    image_exists, Fit, Keyframe, LayerTiming, Plano, PlanoObject, Position, PositionValue,
+    ShaderEffect, SizeValue, MIN_ZOOM, OUTPUT_HEIGHT, OUTPUT_WIDTH,
+};
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The plano does not render as written
+    Error,
+    /// The plano renders, but probably not as intended
+    Warning,
+}
+
+/// A problem found in a plano
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Field path, e.g. `layers[2].keyframes[0].x`
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Collects issues while walking the plano
+#[derive(Default)]
+struct Report {
+    issues: Vec<Issue>,
+}
+
+impl Report {
+    fn error(&mut self, field: &str, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Error,
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn warning(&mut self, field: &str, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Warning,
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn file(&mut self, field: &str, path: &str, exists: bool) {
+        if !exists {
+            self.error(field, format!("file not found: {}", path));
+        }
+    }
+}
+
+/// Check a plano; an empty result means no problems were found
+pub fn validate_plano(plano: &Plano) -> Vec<Issue> {
+    let mut report = Report::default();
+    // Bare arrays are reported as `layers` too: the index is what matters
+    let prefix = "layers";
+
+    if let Some(ref intro) = plano.intro {
+        report.file("intro.path", &intro.path, Path::new(&intro.path).exists());
+    }
+    if let Some(ref outro) = plano.outro {
+        report.file("outro.path", &outro.path, Path::new(&outro.path).exists());
+    }
+
+    for unset in &plano.unset {
+        report.warning(
+            &unset.field,
+            format!("variable `{}` has no value and no default", unset.name),
+        );
+    }
+
+    let mut rects = Vec::new();
+    for (i, layer) in plano.layers.iter().enumerate() {
+        let base = format!("{}[{}]", prefix, i);
+        rects.push(check_layer(&mut report, &base, layer));
+    }
+
+    // Overrides only ever replace the first layer with an id
+    for (i, layer) in plano.layers.iter().enumerate() {
+        let Some(id) = layer.id() else { continue };
+        if let Some(j) = plano.layers[..i].iter().position(|l| l.id() == Some(id)) {
+            report.warning(
+                &format!("{}[{}].id", prefix, i),
+                format!("id \"{}\" is already used by {}[{}]", id, prefix, j),
+            );
+        }
+    }
+
+    // Layers below an opaque full-screen layer never show
+    for (i, layer) in plano.layers.iter().enumerate() {
+        let cover = plano
+            .layers
+            .iter()
+            .enumerate()
+            .skip(i + 1)
+            .find(|(j, above)| rects[*j].is_some_and(covers_canvas) && is_opaque(above));
+        if let Some((j, _)) = cover {
+            report.warning(
+                &format!("{}[{}]", prefix, i),
+                format!(
+                    "{} layer is hidden behind {}[{}], an opaque full-screen layer",
+                    layer_kind(layer),
+                    prefix,
+                    j
+                ),
+            );
+        }
+    }
+
+    report.issues
+}
+
+/// Check one layer; returns its static rectangle when it can be resolved
+fn check_layer(report: &mut Report, base: &str, layer: &PlanoObject) -> Option<Rect> {
+    let (position, keyframes, effects, timing) = match layer {
+        PlanoObject::Clip {
+            source,
+            position,
+            effects,
+            keyframes,
+            timing,
+            offset,
+            zoom,
+            ..
+        } => {
+            if let Some(source) = source {
+                report.file(
+                    &format!("{}.source", base),
+                    source,
+                    Path::new(source).exists(),
+                );
+            }
+            if *offset < 0.0 {
+                report.error(
+                    &format!("{}.offset", base),
+                    format!("negative offset {}", offset),
+                );
+            }
+            if !(MIN_ZOOM..=1.0).contains(zoom) {
+                report.error(
+                    &format!("{}.zoom", base),
+                    format!("zoom {} outside {} - 1.0", zoom, MIN_ZOOM),
+                );
+            }
+            (position, keyframes, effects.as_slice(), timing)
+        }
+        PlanoObject::Image {
+            path,
+            position,
+            opacity,
+            fps,
+            optional,
+            effects,
+            keyframes,
+            timing,
+            ..
+        } => {
+            report.file(
+                &format!("{}.path", base),
+                path,
+                *optional || image_exists(path),
+            );
+            check_opacity(report, &format!("{}.opacity", base), *opacity);
+            if fps.is_some_and(|fps| fps <= 0.0) {
+                report.error(&format!("{}.fps", base), "fps must be positive".to_string());
+            }
+            (position, keyframes, effects.as_slice(), timing)
+        }
+        PlanoObject::Shader {
+            effect,
+            position,
+            keyframes,
+            timing,
+            ..
+        } => {
+            check_effect(report, &format!("{}.effect", base), effect);
+            (position, keyframes, &[][..], timing)
+        }
+        PlanoObject::Video {
+            path,
+            position,
+            opacity,
+            effects,
+            keyframes,
+            timing,
+            ..
+        } => {
+            report.file(&format!("{}.path", base), path, Path::new(path).exists());
+            check_opacity(report, &format!("{}.opacity", base), *opacity);
+            (position, keyframes, effects.as_slice(), timing)
+        }
+    };
+
+    for (i, effect) in effects.iter().enumerate() {
+        check_effect(report, &format!("{}.effects[{}]", base, i), effect);
+    }
+    for (i, keyframe) in keyframes.iter().enumerate() {
+        check_keyframe(report, &format!("{}.keyframes[{}]", base, i), keyframe);
+    }
+    check_timing(report, base, timing);
+
+    let rect = check_position(report, &format!("{}.position", base), position)?;
+    if keyframes.is_empty() && rect.is_outside_canvas() {
+        report.warning(
+            &format!("{}.position", base),
+            format!(
+                "element at ({}, {}) sized {}x{} is outside the {}x{} canvas",
+                rect.x, rect.y, rect.w, rect.h, OUTPUT_WIDTH, OUTPUT_HEIGHT
+            ),
+        );
+    }
+    Some(rect)
+}
+
+/// Resolved static placement of a layer
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+}
+
+impl Rect {
+    fn is_outside_canvas(&self) -> bool {
+        self.x >= OUTPUT_WIDTH as i32
+            || self.y >= OUTPUT_HEIGHT as i32
+            || self.x + self.w as i32 <= 0
+            || self.y + self.h as i32 <= 0
+    }
+}
+
+fn covers_canvas(rect: Rect) -> bool {
+    rect.x <= 0
+        && rect.y <= 0
+        && rect.x + rect.w as i32 >= OUTPUT_WIDTH as i32
+        && rect.y + rect.h as i32 >= OUTPUT_HEIGHT as i32
+}
+
+/// Whether a layer paints every pixel of its box for the whole clip
+fn is_opaque(layer: &PlanoObject) -> bool {
+    let see_through = |effects: &[ShaderEffect]| {
+        effects
+            .iter()
+            .any(|e| matches!(e, ShaderEffect::Chromakey { .. }))
+    };
+    let always_on = |timing: &LayerTiming| {
+        timing.start.is_none()
+            && timing.end.is_none()
+            && timing.fade_in == 0.0
+            && timing.fade_out == 0.0
+    };
+
+    match layer {
+        PlanoObject::Clip {
+            effects,
+            keyframes,
+            style,
+            timing,
+            ..
+        } => {
+            keyframes.is_empty()
+                && style.corner_radius == 0
+                && !see_through(effects)
+                && always_on(timing)
+        }
+        PlanoObject::Video {
+            loop_video,
+            keep_last_frame,
+            opacity,
+            fit,
+            effects,
+            keyframes,
+            style,
+            timing,
+            ..
+        } => {
+            (*loop_video || *keep_last_frame)
+                && *opacity >= 1.0
+                && *fit != Fit::Contain
+                && keyframes.is_empty()
+                && style.corner_radius == 0
+                && !see_through(effects)
+                && always_on(timing)
+        }
+        // Images may have transparency, shaders only filter what is below
+        PlanoObject::Image { .. } | PlanoObject::Shader { .. } => false,
+    }
+}
+
+fn layer_kind(layer: &PlanoObject) -> &'static str {
+    match layer {
+        PlanoObject::Clip { .. } => "clip",
+        PlanoObject::Image { .. } => "image",
+        PlanoObject::Shader { .. } => "shader",
+        PlanoObject::Video { .. } => "video",
+    }
+}
+
+/// Check every value of a position; returns the rectangle if all are valid
+fn check_position(report: &mut Report, base: &str, position: &Position) -> Option<Rect> {
+    let width_ok = check_size(report, &format!("{}.width", base), &position.width);
+    let height_ok = check_size(report, &format!("{}.height", base), &position.height);
+    let x_ok = check_coordinate(report, &format!("{}.x", base), &position.x);
+    let y_ok = check_coordinate(report, &format!("{}.y", base), &position.y);
+    if !(width_ok && height_ok && x_ok && y_ok) {
+        return None;
+    }
+
+    let w = position.width.resolve(OUTPUT_WIDTH);
+    let h = position.height.resolve(OUTPUT_HEIGHT);
+    Some(Rect {
+        x: position.x.resolve(OUTPUT_WIDTH, w),
+        y: position.y.resolve(OUTPUT_HEIGHT, h),
+        w,
+        h,
+    })
+}
+
+/// Parse "NN%" into a number
+fn parse_percentage(keyword: &str) -> Option<Option<f32>> {
+    let number = keyword.trim().strip_suffix('%')?;
+    Some(number.trim().parse::<f32>().ok().filter(|p| p.is_finite()))
+}
+
+fn check_coordinate(report: &mut Report, field: &str, value: &PositionValue) -> bool {
+    let PositionValue::Keyword(keyword) = value else {
+        return true;
+    };
+    if value.is_center() {
+        return true;
+    }
+    match parse_percentage(keyword) {
+        Some(Some(pct)) if (0.0..=100.0).contains(&pct) => true,
+        Some(_) => {
+            report.error(
+                field,
+                format!("invalid percentage \"{}\" (expected 0% - 100%)", keyword),
+            );
+            false
+        }
+        None => {
+            report.error(
+                field,
+                format!("unknown value \"{}\", it resolves to 0", keyword),
+            );
+            false
+        }
+    }
+}
+
+fn check_size(report: &mut Report, field: &str, value: &SizeValue) -> bool {
+    match value {
+        SizeValue::Pixels(0) => {
+            report.error(field, "size is 0".to_string());
+            false
+        }
+        SizeValue::Pixels(_) => true,
+        SizeValue::Keyword(keyword) if keyword.eq_ignore_ascii_case("full") => true,
+        SizeValue::Keyword(keyword) => match parse_percentage(keyword) {
+            Some(Some(pct)) if pct > 0.0 => true,
+            Some(_) => {
+                report.error(
+                    field,
+                    format!("invalid percentage \"{}\" (must be above 0%)", keyword),
+                );
+                false
+            }
+            None => {
+                report.error(
+                    field,
+                    format!("unknown value \"{}\", it resolves to full size", keyword),
+                );
+                false
+            }
+        },
+    }
+}
+
+fn check_time(report: &mut Report, field: &str, value: &TimeValue) {
+    let TimeValue::Keyword(keyword) = value else {
+        return;
+    };
+    let valid = match parse_percentage(keyword) {
+        Some(pct) => pct.is_some_and(|p| (0.0..=100.0).contains(&p)),
+        None => keyword.trim().parse::<f64>().is_ok(),
+    };
+    if !valid {
+        report.error(
+            field,
+            format!("invalid time \"{}\", it resolves to 0", keyword),
+        );
+    }
+}
+
+fn check_opacity(report: &mut Report, field: &str, opacity: f32) {
+    if !(0.0..=1.0).contains(&opacity) {
+        report.error(field, format!("opacity {} is outside 0.0 - 1.0", opacity));
+    }
+}
+
+fn check_keyframe(report: &mut Report, base: &str, keyframe: &Keyframe) {
+    check_time(report, &format!("{}.time", base), &keyframe.time);
+    if let Some(ref x) = keyframe.x {
+        check_coordinate(report, &format!("{}.x", base), x);
+    }
+    if let Some(ref y) = keyframe.y {
+        check_coordinate(report, &format!("{}.y", base), y);
+    }
+    if let Some(ref width) = keyframe.width {
+        check_size(report, &format!("{}.width", base), width);
+    }
+    if let Some(ref height) = keyframe.height {
+        check_size(report, &format!("{}.height", base), height);
+    }
+    if let Some(opacity) = keyframe.opacity {
+        check_opacity(report, &format!("{}.opacity", base), opacity);
+    }
+}
+
+fn check_timing(report: &mut Report, base: &str, timing: &LayerTiming) {
+    if let Some(ref start) = timing.start {
+        check_time(report, &format!("{}.start", base), start);
+    }
+    if let Some(ref end) = timing.end {
+        check_time(report, &format!("{}.end", base), end);
+    }
+    if let (Some(TimeValue::Seconds(start)), Some(TimeValue::Seconds(end))) =
+        (&timing.start, &timing.end)
+    {
+        if *start >= 0.0 && *end >= 0.0 && end <= start {
+            report.error(
+                &format!("{}.end", base),
+                format!("ends at {}s, before it starts at {}s", end, start),
+            );
+        }
+    }
+    for (name, value) in [("fade_in", timing.fade_in), ("fade_out", timing.fade_out)] {
+        if value < 0.0 {
+            report.error(
+                &format!("{}.{}", base, name),
+                format!("negative fade {}", value),
+            );
+        }
+    }
+}
+
+fn check_effect(report: &mut Report, field: &str, effect: &ShaderEffect) {
+    if let ShaderEffect::Lut { path } = effect {
+        report.file(&format!("{}.path", field), path, Path::new(path).exists());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::{create_default_plano, parse_plano};
+
+    fn fields(issues: &[Issue]) -> Vec<&str> {
+        issues.iter().map(|i| i.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_default_plano_is_valid() {
+        let plano = Plano::from(create_default_plano());
+        assert!(validate_plano(&plano).is_empty());
+    }
+
+    #[test]
+    fn test_reports_field_paths() {
+        let plano = parse_plano(
+            r#"[
+                { "type": "clip", "id": "main",
+                  "position": { "x": "center", "y": "abc", "width": "120%", "height": "-5%" } },
+                { "type": "image", "path": "missing/logo.png", "opacity": 1.5,
+                  "position": { "x": 2000, "y": 0, "width": 100, "height": 100 } },
+                { "type": "clip", "id": "main", "position": { "x": 0, "y": 0, "width": "full", "height": "full" },
+                  "zoom": 1.5, "keyframes": [ { "time": "soon", "x": "150%" } ] }
+            ]"#,
+        )
+        .unwrap();
+        let issues = validate_plano(&plano);
+        let fields = fields(&issues);
+        assert!(fields.contains(&"layers[0].position.height"));
+        assert!(fields.contains(&"layers[0].position.y"));
+        // 120% is a valid (zoomed) size
+        assert!(!fields.contains(&"layers[0].position.width"));
+        assert!(fields.contains(&"layers[1].path"));
+        assert!(fields.contains(&"layers[1].opacity"));
+        assert!(fields.contains(&"layers[2].keyframes[0].time"));
+        assert!(fields.contains(&"layers[2].keyframes[0].x"));
+        assert!(fields.contains(&"layers[2].zoom"));
+        assert!(fields.contains(&"layers[2].id"));
+
+        let outside = issues
+            .iter()
+            .find(|i| i.field == "layers[1].position")
+            .unwrap();
+        assert_eq!(outside.severity, Severity::Warning);
+        assert!(outside.message.contains("outside"));
+    }
+
+    #[test]
+    fn test_hidden_layers() {
+        let plano = parse_plano(
+            r#"{
+                "layers": [
+                    { "type": "shader", "effect": { "type": "blur" },
+                      "position": { "x": 0, "y": 0, "width": "full", "height": "full" } },
+                    { "type": "clip", "position": { "x": 0, "y": 0, "width": "full", "height": "full" } },
+                    { "type": "clip", "position": { "x": 0, "y": 0, "width": "full", "height": "full" },
+                      "effects": [ { "type": "chromakey" } ] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let issues = validate_plano(&plano);
+        // Only the shader is covered: the top clip is keyed, so not opaque
+        assert_eq!(fields(&issues), vec!["layers[0]"]);
+        assert!(issues[0].message.contains("layers[1]"));
+    }
+
+    #[test]
+    fn test_center_larger_than_canvas() {
+        // Used to underflow on u32
+        let value = PositionValue::Keyword("center".to_string());
+        assert_eq!(value.resolve(1080, 1280), -100);
+    }
+}
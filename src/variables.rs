@@ -0,0 +1,189 @@
+//! Plano variables for YT ShortMaker
+//! Fills `{{name}}` placeholders in a plano with values of the clip being exported
+//!
+//! Values come from a sidecar file next to the clip ("clip.vars.json"), which
+//! is written with the fields of the moment when the clip is extracted and can
+//! be extended by hand (e.g. a `crop_x` or `title` per clip).
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::types::VideoMoment;
+
+/// Values for `{{name}}` placeholders
+pub type PlanoVars = BTreeMap<String, Value>;
+
+/// A placeholder left without a value: no clip variable and no default
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unset {
+    /// Field path, e.g. `layers[2].crop.x_from`
+    pub field: String,
+    /// Variable name
+    pub name: String,
+}
+
+/// Sidecar file holding the variables of a clip ("clip.mp4" -> "clip.vars.json")
+pub fn sidecar_path(clip_path: &str) -> PathBuf {
+    Path::new(clip_path).with_extension("vars.json")
+}
+
+/// Variables of a clip; empty when it has no (valid) sidecar
+pub fn load_clip_vars(clip_path: &str) -> PlanoVars {
+    let path = sidecar_path(clip_path);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return PlanoVars::new();
+    };
+    match serde_json::from_str(&content) {
+        Ok(vars) => vars,
+        Err(e) => {
+            log::warn!("Ignoring invalid variables file {:?}: {}", path, e);
+            PlanoVars::new()
+        }
+    }
+}
+
+/// Store the fields of the moment a clip was cut from
+/// Values added by hand to an existing sidecar are kept
+pub fn save_moment_vars(clip_path: &str, moment: &VideoMoment) -> Result<()> {
+    let mut vars = load_clip_vars(clip_path);
+    for (name, value) in [
+        ("category", &moment.category),
+        ("description", &moment.description),
+        ("start_time", &moment.start_time),
+        ("end_time", &moment.end_time),
+    ] {
+        vars.insert(name.to_string(), Value::String(value.clone()));
+    }
+
+    let path = sidecar_path(clip_path);
+    let content = serde_json::to_string_pretty(&vars)?;
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Moment category of a clip, used to pick a plano from `categories`
+pub fn category(vars: &PlanoVars) -> Option<&str> {
+    vars.get("category").and_then(Value::as_str)
+}
+
+/// Whether plano JSON has any placeholder
+pub fn has_placeholders(content: &str) -> bool {
+    content.contains("{{")
+}
+
+/// Replace the placeholders in the strings of plano JSON
+///
+/// A string that is only a placeholder (`"{{crop_x}}"`) becomes the value
+/// itself, so numbers stay numbers. Inside a longer string the value is
+/// inserted as text. `{{name|default}}` is used when the variable is not set;
+/// unset variables without a default become an empty text, or are left as
+/// they are when they are the whole string (see `schema::fill_placeholders`).
+pub fn substitute(content: &str, vars: &PlanoVars) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find('"') {
+        out.push_str(&rest[..start]);
+        let literal = &rest[start..];
+        let end = string_end(literal);
+        out.push_str(&substitute_string(&literal[..end], vars));
+        rest = &literal[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Length of the string literal at the start of `s`, quotes included
+fn string_end(s: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn substitute_string(literal: &str, vars: &PlanoVars) -> String {
+    let inner = match literal.len() {
+        2.. if literal.ends_with('"') => &literal[1..literal.len() - 1],
+        _ => return literal.to_string(),
+    };
+    if !has_placeholders(inner) {
+        return literal.to_string();
+    }
+
+    if let Some(placeholder) = whole_placeholder(inner) {
+        return match lookup(placeholder, vars) {
+            Some(value) => value.to_string(),
+            None => literal.to_string(),
+        };
+    }
+
+    let mut out = String::from('"');
+    let mut rest = inner;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let text = match lookup(&rest[start + 2..start + len], vars) {
+            Some(Value::String(s)) => s,
+            Some(Value::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        };
+        let escaped = Value::String(text).to_string();
+        out.push_str(&escaped[1..escaped.len() - 1]);
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out.push('"');
+    out
+}
+
+/// Placeholder of a string that is nothing else (`{{crop_x}}` -> `crop_x`)
+pub fn whole_placeholder(text: &str) -> Option<&str> {
+    text.strip_prefix("{{")
+        .and_then(|s| s.strip_suffix("}}"))
+        .filter(|s| !s.contains("{{") && !s.contains("}}"))
+}
+
+/// Value of a placeholder (`name` or `name|default`)
+fn lookup(placeholder: &str, vars: &PlanoVars) -> Option<Value> {
+    let (name, default) = match placeholder.split_once('|') {
+        Some((name, default)) => (name, Some(default.trim())),
+        None => (placeholder, None),
+    };
+    vars.get(name.trim()).cloned().or_else(|| {
+        default.map(|d| serde_json::from_str(d).unwrap_or_else(|_| Value::String(d.to_string())))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let vars: PlanoVars = serde_json::from_str(
+            r#"{ "crop_x": 480, "title": "Best \"clutch\"", "category": "Funny" }"#,
+        )
+        .unwrap();
+        let json = r#"{ "x_from": "{{crop_x}}", "x_to": "{{crop_to|1440}}", "y_from": "{{missing}}",
+            "path": "titles/{{category}}.png", "comment": "{{title}} ({{ missing | none }})" }"#;
+        let value: Value = serde_json::from_str(&substitute(json, &vars)).unwrap();
+
+        assert_eq!(value["x_from"], 480);
+        assert_eq!(value["x_to"], 1440);
+        assert_eq!(value["y_from"], "{{missing}}");
+        assert_eq!(value["path"], "titles/Funny.png");
+        assert_eq!(value["comment"], r#"Best "clutch" (none)"#);
+
+        // Text without placeholders is left alone
+        let plain = r#"{ "a": "b \" {", "c": [1, 2] }"#;
+        assert_eq!(substitute(plain, &vars), plain);
+    }
+}
@@ -9,6 +9,7 @@ use std::sync::Arc;
 use tokio::process::Command;
 use tokio::time::Duration;
 
+use crate::encoding::ExportProfile;
 use crate::types::VideoChunk;
 use regex::Regex;
 
@@ -149,6 +150,59 @@ pub fn get_video_duration_precise(file_path: &str) -> Result<f64> {
     Ok(duration)
 }
 
+/// Get the frame rate of the first video stream as a fraction (e.g. 30000/1001)
+pub fn get_frame_rate(file_path: &str) -> Result<(u32, u32)> {
+    let output = std::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=r_frame_rate",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            file_path,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    let rate = String::from_utf8_lossy(&output.stdout);
+    let (num, den) = rate
+        .trim()
+        .split_once('/')
+        .context("Failed to parse frame rate")?;
+    let num: u32 = num.parse().context("Invalid frame rate")?;
+    let den: u32 = den.parse().context("Invalid frame rate")?;
+    if num == 0 || den == 0 {
+        return Err(anyhow!("Invalid frame rate: {}/{}", num, den));
+    }
+    Ok((num, den))
+}
+
+/// Check whether a media file has at least one audio stream
+pub fn has_audio_stream(file_path: &str) -> bool {
+    std::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a",
+            "-show_entries",
+            "stream=index",
+            "-of",
+            "csv=p=0",
+            file_path,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .map(|o| !String::from_utf8_lossy(&o.stdout).trim().is_empty())
+        .unwrap_or(false)
+}
+
 /// Download low resolution video for analysis (silent mode)
 pub async fn download_low_res(
     url: &str,
@@ -413,6 +467,7 @@ pub async fn extract_clip(
     start_time: &str,
     end_time: &str,
     output_path: &str,
+    profile: &ExportProfile,
     cancellation_token: Arc<AtomicBool>,
 ) -> Result<()> {
     if cancellation_token.load(Ordering::Relaxed) {
@@ -429,7 +484,7 @@ pub async fn extract_clip(
 
     let duration = end_sec - start_sec;
 
-    let mut args = vec![
+    let args = vec![
         "-hide_banner".to_string(),
         "-loglevel".to_string(),
         "error".to_string(),
@@ -441,30 +496,15 @@ pub async fn extract_clip(
         duration.to_string(),
     ];
 
-    // Always use CPU
-    args.extend_from_slice(&[
-        "-c:v".to_string(),
-        "libx264".to_string(),
-        "-preset".to_string(),
-        "ultrafast".to_string(),
-        "-c:a".to_string(),
-        "aac".to_string(),
-    ]);
-
-    args.push("-y".to_string());
-    args.push(output_path.to_string());
-
-    let mut command = Command::new("ffmpeg");
-    command.args(&args);
-
-    let output = run_command_with_cancellation(command, cancellation_token).await?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("ffmpeg extraction failed: {}", stderr.trim()));
-    }
-
-    Ok(())
+    crate::encoding::run_encode(
+        &args,
+        profile,
+        duration as f64,
+        output_path,
+        cancellation_token,
+    )
+    .await
+    .context("ffmpeg extraction failed")
 }
 
 /// Format seconds to HH:MM:SS timestamp
@@ -489,6 +529,14 @@ pub fn parse_timestamp_to_seconds(timestamp: &str) -> Result<u64> {
     Ok(hours * 3600 + minutes * 60 + seconds)
 }
 
+/// Move an HH:MM:SS timestamp by `delta` seconds (not before 00:00:00)
+pub fn nudge_timestamp(timestamp: &str, delta: i64) -> Result<String> {
+    let seconds = parse_timestamp_to_seconds(timestamp)?;
+    Ok(format_seconds_to_timestamp(
+        seconds.saturating_add_signed(delta),
+    ))
+}
+
 /// Validate Media URL
 pub fn validate_media_url(url: &str) -> bool {
     let url_lower = url.to_lowercase();
@@ -537,6 +585,14 @@ mod tests {
         assert_eq!(parse_timestamp_to_seconds("00:00:00").unwrap(), 0);
     }
 
+    #[test]
+    fn test_nudge_timestamp() {
+        assert_eq!(nudge_timestamp("00:00:59", 1).unwrap(), "00:01:00");
+        assert_eq!(nudge_timestamp("01:00:00", -5).unwrap(), "00:59:55");
+        assert_eq!(nudge_timestamp("00:00:02", -5).unwrap(), "00:00:00");
+        assert!(nudge_timestamp("1:02", 1).is_err());
+    }
+
     #[test]
     fn test_validate_media_url() {
         assert!(validate_media_url("https://www.youtube.com/watch?v=abc123"));